@@ -1,28 +1,59 @@
 use anyhow::{Context, Result};
-use chrono::NaiveDate;
-use clap::Parser;
-use globset::Glob;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use encoding_rs::Encoding;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use ignore::WalkBuilder;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use memmap2::Mmap;
+use notify::{EventKind, RecursiveMode, Watcher};
+use owo_colors::Style;
 use rayon::prelude::*;
 use regex::{Regex, RegexBuilder};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, IsTerminal};
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, SystemTime};
 
 /// Default number of lines to scan for metadata when not in full-text mode.
 /// This is enough to capture typical frontmatter (usually < 10 lines) plus
 /// a few lines of content for inline metadata detection.
 const DEFAULT_HEAD_LINES: usize = 10;
 
+/// Default number of body lines scanned after the frontmatter closes, so inline
+/// metadata right below a long frontmatter block is not cut off.
+const DEFAULT_BODY_HEAD_LINES: usize = 5;
+
+/// Default length of a --summary excerpt, in characters.
+const DEFAULT_SUMMARY_CHARS: usize = 200;
+
 /// Maximum number of lines to read for frontmatter to prevent memory issues.
 /// Frontmatter exceeding this limit will cause the file to be skipped with an error.
 const MAX_FRONTMATTER_LINES: usize = 1000;
 
+/// Files smaller than this are read normally even with --mmap,
+/// since mapping them costs more than copying.
+const MMAP_MIN_BYTES: u64 = 64 * 1024;
+
+/// Exit status when no file matched the filters (like grep).
+const EXIT_NO_MATCH: u8 = 1;
+
+/// Exit status for genuine errors (invalid arguments, bad patterns, etc.).
+const EXIT_ERROR: u8 = 2;
+
 /// Directories to always skip during file enumeration.
 /// These are common build artifacts, dependencies, caches, and tool-specific directories.
 const EXCLUDED_DIRS: &[&str] = &[
+    // Version control (reachable with --hidden)
+    ".git",
     // Build artifacts
     "target",
     "build",
@@ -62,35 +93,349 @@ const EXCLUDED_DIRS: &[&str] = &[
     ".tox",
 ];
 
+/// Keys available for ordering the result list
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum SortKey {
+    /// Alphabetically by file path (like ls)
+    Path,
+    /// By filesystem modification time, oldest first
+    Mtime,
+    /// By inode change time (creation time where there is none), oldest first
+    Ctime,
+    /// By title (the YAML title, else the first H1), case-insensitively; untitled files last
+    Title,
+    /// By the latest frontmatter or inline date, oldest first; undated files last
+    Date,
+}
+
+/// How paths are compared when sorting by path or breaking ties
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum PathOrder {
+    /// By the bytes of each path component (uppercase before lowercase)
+    #[default]
+    Byte,
+    /// Case-insensitively
+    Ci,
+    /// Case-insensitively, with digit runs compared as numbers (file2 before file10)
+    Natural,
+}
+
+/// How --sort-field values are compared
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum SortFieldKind {
+    /// As plain strings
+    Lexical,
+    /// As numbers (e.g. 2 before 10)
+    Numeric,
+    /// As semantic versions (e.g. 1.2.0 before 1.10.0; a leading 'v' is allowed)
+    Semver,
+}
+
+/// Serialization used by --print-frontmatter
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum DumpFormat {
+    /// YAML, as written in frontmatter
+    Yaml,
+    /// Pretty-printed JSON
+    Json,
+}
+
+/// Parser used for the frontmatter block
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum FrontmatterFormat {
+    /// Pick by fence: `---` is YAML, `+++` is TOML
+    #[default]
+    Auto,
+    /// YAML, whatever the fence
+    Yaml,
+    /// TOML, whatever the fence
+    Toml,
+    /// A JSON object, whatever the fence
+    Json,
+    /// An MDX `export const meta = {...}` block (like --mdx); fenced blocks are ignored
+    Mdx,
+}
+
+impl FrontmatterFormat {
+    /// Name used in parse warnings
+    fn label(self) -> &'static str {
+        match self {
+            FrontmatterFormat::Auto | FrontmatterFormat::Yaml => "YAML",
+            FrontmatterFormat::Toml => "TOML",
+            FrontmatterFormat::Json => "JSON",
+            FrontmatterFormat::Mdx => "MDX",
+        }
+    }
+}
+
+/// Metadata used to group results into sections
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum GroupBy {
+    /// One section per distinct frontmatter tag
+    Tag,
+    /// One section per distinct author
+    Author,
+}
+
+/// When to highlight matches in formatted output
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum ColorChoice {
+    /// Only when stdout is a terminal and NO_COLOR is unset
+    #[default]
+    Auto,
+    /// Always emit color codes
+    Always,
+    /// Never emit color codes
+    Never,
+}
+
+/// How field filters on distinct field names combine
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum FieldLogic {
+    /// Every distinct field must match (AND)
+    #[default]
+    All,
+    /// At least one field must match (OR)
+    Any,
+}
+
+/// A filter type whose sense --invert flips
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum InvertTarget {
+    /// --tag, --tag-glob and plain --tags entries
+    Tag,
+    /// --title and --title-regex
+    Title,
+    /// --author
+    Author,
+    /// --field
+    Field,
+}
+
+/// Subcommands; without one, fmd lists the matching files
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+enum Commands {
+    /// List the distinct tags (YAML and inline) of the matching files, sorted.
+    /// To search a directory named 'tags' instead, write './tags'
+    Tags {
+        /// Directories to search, as for the default search
+        dirs: Vec<PathBuf>,
+    },
+}
+
 /// fmd — Find Markdown files by metadata
 #[derive(Parser, Debug)]
-#[command(name = "fmd")]
+#[command(
+    name = "fmd",
+    disable_help_subcommand = true,
+    args_override_self = true
+)]
 #[command(about = "Find Markdown files by metadata - Search by tags, frontmatter, and custom fields", long_about = None)]
 struct Args {
-    /// Directories to search (default: current directory)
-    #[arg(default_value = ".")]
+    #[command(subcommand)]
+    command: Option<Commands>,
+
+    /// Directories to search (default: $FMD_DIRS, else current directory; glob patterns like 'vault/*/notes' are expanded)
     dirs: Vec<PathBuf>,
 
     /// Filter by tag (can be specified multiple times, OR logic)
     #[arg(short = 't', long = "tag")]
     tags: Vec<String>,
 
+    /// Read saved options from a file (one or more tokens per line, '#' starts a comment);
+    /// options on the command line come after and override them
+    #[arg(long = "query-file", value_name = "PATH")]
+    query_file: Option<PathBuf>,
+
+    /// Read more directories to search from a file (one per line, '#' starts a comment)
+    #[arg(long = "dirs-from", value_name = "PATH")]
+    dirs_from: Option<PathBuf>,
+
+    /// Read additional --tag values from a file (one per line, '#' starts a comment)
+    #[arg(long = "tags-file", value_name = "PATH")]
+    tags_file: Option<PathBuf>,
+
+    /// Treat --tag values as globs ('*' and '?' wildcards), e.g. 'proj-*'
+    #[arg(long = "tag-glob")]
+    tag_glob: bool,
+
+    /// Namespace stripped from tags before matching, e.g. 'team/' so that
+    /// --tag rust matches team/rust; tags outside the namespace never match
+    #[arg(long = "tag-prefix", value_name = "PREFIX")]
+    tag_prefix: Option<String>,
+
+    /// Frontmatter field also holding tags, besides 'tags' (can be repeated), e.g. 'categories'
+    #[arg(long = "tag-field", value_name = "FIELD")]
+    tag_fields: Vec<String>,
+
+    /// Only files with at least N distinct tags (YAML, --tag-field and inline #tags in the scanned lines)
+    #[arg(long = "min-tags", value_name = "N")]
+    min_tags: Option<usize>,
+
+    /// Only files with at most N distinct tags; 0 finds untagged files
+    #[arg(long = "max-tags", value_name = "N")]
+    max_tags: Option<usize>,
+
+    /// Tag spec: "+tag" requires, "-tag" excludes, bare "tag" is OR-optional (whitespace-separated)
+    #[arg(long = "tags", allow_hyphen_values = true)]
+    tag_specs: Vec<String>,
+
     /// Filter by title (can be specified multiple times, OR logic)
     #[arg(short = 'T', long = "title")]
     titles: Vec<String>,
 
+    /// Filter by the frontmatter `aliases` field, a name or list of names (OR logic)
+    #[arg(long = "alias", value_name = "NAME")]
+    aliases: Vec<String>,
+
+    /// Also match --title and --title-regex against the file name without its extension
+    #[arg(long = "filename-as-title")]
+    filename_as_title: bool,
+
+    /// Filter by title with a regex (frontmatter title or heading text, OR logic)
+    #[arg(long = "title-regex", value_name = "REGEX")]
+    title_regexes: Vec<String>,
+
+    /// Filter by link target: substring of a `[text](url)` URL or `[[wikilink]]` (OR logic)
+    #[arg(long = "links-to", value_name = "PATTERN")]
+    links_to: Vec<String>,
+
+    /// Only files with at least N links (`[text](url)` and `[[wikilinks]]`); use --full-text to count the whole file
+    #[arg(long = "min-links", value_name = "N")]
+    min_links: Option<usize>,
+
+    /// Only files with at most N links; 0 finds files without links
+    #[arg(long = "max-links", value_name = "N")]
+    max_links: Option<usize>,
+
+    /// Don't count links in code blocks or `code spans` for --min-links and --max-links
+    #[arg(long = "skip-code-blocks")]
+    skip_code_blocks: bool,
+
+    /// Only files with at least one task checkbox (`- [ ]` or `- [x]`) in the scanned lines
+    #[arg(long = "has-tasks")]
+    has_tasks: bool,
+
+    /// Only files with at least one open task (`- [ ]`) in the scanned lines
+    #[arg(long = "has-open-tasks")]
+    has_open_tasks: bool,
+
+    /// Filter by a regex matched against each line of the whole file (OR logic)
+    #[arg(long = "grep", value_name = "REGEX")]
+    greps: Vec<String>,
+
+    /// Print each --grep hit as 'path:line:text' instead of just the path
+    #[arg(long = "grep-lines", requires = "greps")]
+    grep_lines: bool,
+
+    /// Filter by heading text, optionally at a level: 'LEVEL:TEXT' (e.g. '2:Agenda') or 'TEXT' (OR logic)
+    #[arg(long = "heading", value_name = "[LEVEL:]TEXT")]
+    headings: Vec<String>,
+
+    /// Filter by the first paragraph of prose (can be specified multiple times, OR logic)
+    #[arg(long = "description", value_name = "PATTERN")]
+    descriptions: Vec<String>,
+
     /// Filter by author (can be specified multiple times, OR logic)
     #[arg(short = 'a', long = "author")]
     authors: Vec<String>,
 
+    /// Require all --author patterns to match (AND logic instead of OR)
+    #[arg(long = "author-all")]
+    author_all: bool,
+
     /// Filter by filename (can be specified multiple times, OR logic)
     #[arg(short = 'n', long = "name")]
     names: Vec<String>,
 
-    /// Filter by frontmatter field (format: "field:pattern", OR logic)
+    /// Match --name patterns against the whole path instead of the filename
+    #[arg(long = "name-full")]
+    name_full: bool,
+
+    /// Filter by filename without its extension, e.g. '2024$' (can be specified multiple times, OR logic)
+    #[arg(long = "stem", value_name = "PATTERN")]
+    stems: Vec<String>,
+
+    /// Filter by full path regex, with '/' separators (can be specified multiple times, OR logic)
+    #[arg(long = "path")]
+    paths: Vec<String>,
+
+    /// Filter by frontmatter field (format: "field:pattern", OR logic for the same field)
     #[arg(short = 'f', long = "field")]
     fields: Vec<String>,
 
+    /// Require every comma-separated value in the field, e.g. "categories:a,b" (format like --field)
+    #[arg(long = "field-all", value_name = "FIELD:VALUES")]
+    field_all: Vec<String>,
+
+    /// Require the field to equal the value exactly (trimmed, case-insensitive); arrays need an
+    /// equal element, e.g. "status:done" (format like --field)
+    #[arg(long = "field-eq", value_name = "FIELD:VALUE")]
+    field_eq: Vec<String>,
+
+    /// Character separating the field name from the pattern in --field specs
+    #[arg(long = "field-separator", value_name = "CHAR", default_value_t = ':')]
+    field_separator: char,
+
+    /// Only include files with a parsed frontmatter block (empty or invalid blocks don't count)
+    #[arg(long = "require-frontmatter", conflicts_with = "no_frontmatter")]
+    require_frontmatter: bool,
+
+    /// Only include files without a parsed frontmatter block (including empty or invalid blocks)
+    #[arg(long = "no-frontmatter")]
+    no_frontmatter: bool,
+
+    /// Only include files that define this field (can be specified multiple times)
+    #[arg(long = "has-field", value_name = "NAME")]
+    has_fields: Vec<String>,
+
+    /// Exclude files that define this field, even with an empty value (can be specified multiple times)
+    #[arg(
+        long = "missing-field",
+        visible_alias = "field-missing",
+        value_name = "NAME"
+    )]
+    missing_fields: Vec<String>,
+
+    /// Exclude drafts (files whose `draft` field is true)
+    #[arg(long = "no-drafts")]
+    no_drafts: bool,
+
+    /// Flip the sense of one filter type (can be repeated), e.g. '--title draft --invert title'
+    /// keeps files whose title does not contain draft
+    #[arg(long = "invert", value_enum, value_name = "TYPE")]
+    inverts: Vec<InvertTarget>,
+
+    /// Include files matching any filter type (tags, title, fields, --name, ...) instead of all;
+    /// exclusions such as --missing-field still apply
+    #[arg(long = "any")]
+    any: bool,
+
+    /// Include files whose name matches --name OR whose content passes the other filters,
+    /// instead of requiring both (exclusions such as --no-drafts still apply)
+    #[arg(long = "name-or-content", requires = "names")]
+    name_or_content: bool,
+
+    /// How --field filters on different field names combine
+    #[arg(long = "field-logic", value_enum, default_value_t = FieldLogic::All)]
+    field_logic: FieldLogic,
+
+    /// Let --field patterns match keys and values inside nested mappings
+    #[arg(long = "deep-fields")]
+    deep_fields: bool,
+
+    /// Only accept inline `key: value` metadata starting at column 0 (no indentation)
+    #[arg(long = "inline-metadata-prefix")]
+    inline_metadata_prefix: bool,
+
+    /// Treat common synonyms as the same field (author: by, authors; date: published, pubdate)
+    #[arg(long = "resolve-frontmatter-aliases")]
+    resolve_aliases: bool,
+
+    /// Add field synonyms (format: "field=alias1,alias2"; can be repeated)
+    #[arg(long = "field-alias", value_name = "FIELD=ALIASES")]
+    field_aliases: Vec<String>,
+
     /// Filter files with dates after this date (format: YYYY-MM-DD)
     #[arg(long = "date-after")]
     date_after: Option<String>,
@@ -99,80 +444,464 @@ struct Args {
     #[arg(long = "date-before")]
     date_before: Option<String>,
 
-    /// File pattern to match
-    #[arg(long = "glob", default_value = "**/*.md")]
-    glob: String,
+    /// Make --date-after and --date-before exclusive (dates on the bound don't match)
+    #[arg(long = "exclusive-dates")]
+    exclusive_dates: bool,
+
+    /// Let files without any date pass --date-after/--date-before instead of failing them
+    #[arg(long = "include-dateless")]
+    include_dateless: bool,
+
+    /// File pattern to match (default: **/*.md unless --ext is given)
+    #[arg(long = "glob")]
+    glob: Option<String>,
+
+    /// Match files with this extension, e.g. 'md' or 'mdx' (can be repeated, unioned with --glob)
+    #[arg(long = "ext", value_name = "EXT")]
+    extensions: Vec<String>,
+
+    /// Only search files changed since this git revision (via `git diff --name-only REV`)
+    #[arg(long = "since-commit", value_name = "REV")]
+    since_commit: Option<String>,
+
+    /// Read the files to search from stdin, one per line (NUL-delimited with -0)
+    #[arg(long = "stdin")]
+    stdin: bool,
+
+    /// Cache the enumerated file list in this file and reuse it while no directory changed
+    #[arg(long = "cache", value_name = "PATH")]
+    cache: Option<PathBuf>,
+
+    /// Don't fail when none of the given directories exist
+    #[arg(long = "allow-missing-dirs")]
+    allow_missing_dirs: bool,
 
     /// Limit search depth (1=current dir only, default: unlimited)
     #[arg(short = 'd', long = "depth")]
     depth: Option<usize>,
 
-    /// Lines to scan for metadata
-    #[arg(long = "head", default_value_t = DEFAULT_HEAD_LINES)]
+    /// Skip files shallower than this depth (2=skip files directly in the search dir)
+    #[arg(long = "depth-min", value_name = "N")]
+    depth_min: Option<usize>,
+
+    /// Include hidden files and directories (dotfiles), e.g. with --glob '**/.*.md'
+    #[arg(long = "hidden")]
+    hidden: bool,
+
+    /// Don't descend into directories on other file systems (network mounts, /proc, ...)
+    #[arg(long = "one-file-system")]
+    one_file_system: bool,
+
+    /// Lines to scan for metadata; 0 reads only the frontmatter
+    #[arg(long = "head", visible_alias = "first-n-lines", default_value_t = DEFAULT_HEAD_LINES)]
     head_lines: usize,
 
+    /// Bytes to scan for metadata instead of --head lines; a frontmatter block is still read to its end
+    #[arg(long = "head-bytes", value_name = "N", conflicts_with = "head_lines")]
+    head_bytes: Option<usize>,
+
+    /// Lines to scan after the frontmatter ends, even past --head
+    /// (default: 5, or 0 with --head 0)
+    #[arg(long = "body-head", value_name = "N")]
+    body_head_lines: Option<usize>,
+
     /// Search full file content (not just first N lines)
     #[arg(long = "full-text")]
     full_text: bool,
 
-    /// Case-insensitive matching for --name filter
+    /// Memory-map large files for whole-file scans (--full-text, --end-matter, --grep)
+    #[arg(long = "mmap")]
+    mmap: bool,
+
+    /// Buffer size for reading files line by line (default 8 KiB); each of the --threads
+    /// workers holds one buffer
+    #[arg(long = "read-buffer-size", value_name = "BYTES")]
+    read_buffer_size: Option<usize>,
+
+    /// Number of files read in parallel (default: one per CPU); fewer suit spinning disks
+    #[arg(long = "threads", value_name = "N")]
+    threads: Option<usize>,
+
+    /// Case-insensitive matching for --name, --path, --glob, --title-regex and --grep
     #[arg(short = 'i', long = "ignore-case")]
     ignore_case: bool,
 
-    /// Use NUL-delimited output (safe for xargs -0)
-    #[arg(short = '0', long)]
+    /// Use NUL-delimited output (safe for xargs -0); shorthand for --separator '\0'
+    #[arg(short = '0', long, visible_alias = "null-separator")]
     nul: bool,
 
+    /// Print this after each output line instead of a newline; '\t', '\n', '\0' and '\\' are escapes
+    #[arg(long = "separator", value_name = "STR", conflicts_with = "nul")]
+    separator: Option<String>,
+
     /// Show verbose output including warnings and errors
     #[arg(short = 'v', long = "verbose")]
     verbose: bool,
+
+    /// Print the files that would be scanned (after --name/--path) without reading them
+    #[arg(long = "list-only")]
+    list_only: bool,
+
+    /// Print the files that failed to read or parse, with the error, instead of matches
+    #[arg(long = "files-with-errors", conflicts_with = "list_only")]
+    files_with_errors: bool,
+
+    /// Report parse warnings and read errors on stderr as JSON lines ({path, kind, message})
+    #[arg(long = "errors-json")]
+    errors_json: bool,
+
+    /// Show a progress bar on stderr while scanning (only when stdout and stderr are terminals)
+    #[arg(long = "progress")]
+    progress: bool,
+
+    /// Sort results by the given key
+    #[arg(long = "sort", value_enum, default_value_t = SortKey::Path)]
+    sort: SortKey,
+
+    /// Sort results by the value of this field instead (files without it sort last)
+    #[arg(long = "sort-field", value_name = "NAME")]
+    sort_field: Option<String>,
+
+    /// How --sort-field values are compared
+    #[arg(long = "sort-field-kind", value_enum, default_value_t = SortFieldKind::Lexical)]
+    sort_field_kind: SortFieldKind,
+
+    /// How paths compare for --sort path and ties in other sorts
+    #[arg(long = "sort-order", value_enum, value_name = "ORDER", default_value_t = PathOrder::Byte)]
+    sort_order: PathOrder,
+
+    /// Reverse the sort order (e.g. newest first with --sort mtime)
+    #[arg(long = "reverse")]
+    reverse: bool,
+
+    /// Print at most N matches, after sorting
+    #[arg(long = "limit", value_name = "N")]
+    limit: Option<usize>,
+
+    /// The N latest-dated files, newest first (--sort date --reverse --limit N)
+    #[arg(long = "newest", value_name = "N", conflicts_with_all = ["oldest", "sort", "sort_field", "reverse", "limit"])]
+    newest: Option<usize>,
+
+    /// The N earliest-dated files, oldest first (--sort date --limit N)
+    #[arg(long = "oldest", value_name = "N", conflicts_with_all = ["sort", "sort_field", "reverse", "limit"])]
+    oldest: Option<usize>,
+
+    /// With --sort date, date files without a frontmatter date by their modification time
+    #[arg(long = "date-fallback-mtime")]
+    date_fallback_mtime: bool,
+
+    /// Read title, author, tags and fields from under this top-level frontmatter key
+    #[arg(long = "field-root", value_name = "KEY")]
+    field_root: Option<String>,
+
+    /// Also read metadata from a YAML sidecar named after the file plus EXT
+    /// (e.g. '.meta.yml' for note.md.meta.yml); sidecar values override the frontmatter
+    #[arg(long = "sidecar-ext", value_name = "EXT")]
+    sidecar_ext: Option<String>,
+
+    /// Decode files with this encoding (e.g. gbk, shift_jis) instead of UTF-8
+    #[arg(long = "encoding")]
+    encoding: Option<String>,
+
+    /// Print each matched file's extracted dates after its path
+    #[arg(long = "print-dates")]
+    print_dates: bool,
+
+    /// Print each matched file's parsed frontmatter under its path (not available with -0 or --separator)
+    #[arg(long = "print-frontmatter", conflicts_with_all = ["nul", "separator"])]
+    print_frontmatter: bool,
+
+    /// Format used by --print-frontmatter
+    #[arg(long = "print-frontmatter-format", value_enum, value_name = "FORMAT", default_value_t = DumpFormat::Yaml)]
+    print_frontmatter_format: DumpFormat,
+
+    /// Print the contents of each matched file, after a `==> path <==` header
+    /// (with -0 or --separator, each file's contents end with the separator instead)
+    #[arg(long = "cat")]
+    cat: bool,
+
+    /// With --cat, leave out the frontmatter block
+    #[arg(long = "strip-frontmatter", requires = "cat")]
+    strip_frontmatter: bool,
+
+    /// Print each matched file's first prose paragraph after its path
+    #[arg(long = "summary")]
+    summary: bool,
+
+    /// Maximum characters of the --summary excerpt
+    #[arg(long = "summary-length", value_name = "N", default_value_t = DEFAULT_SUMMARY_CHARS)]
+    summary_length: usize,
+
+    /// Print the lines behind each match (inline tags, inline authors, --grep hits) under its path
+    /// (not available with -0 or --separator)
+    #[arg(long = "show-match", conflicts_with_all = ["nul", "separator"])]
+    show_match: bool,
+
+    /// Lines of trailing context to print after each --show-match line
+    #[arg(
+        short = 'A',
+        long = "after-context",
+        value_name = "N",
+        default_value_t = 0,
+        requires = "show_match"
+    )]
+    after_context: usize,
+
+    /// Pick one of the matches with fzf and open it in $VISUAL or $EDITOR (terminals only)
+    #[arg(long = "open", conflicts_with_all = ["list_only", "files_with_errors"])]
+    open: bool,
+
+    /// Keep running and print the results again whenever a matching file under the search dirs changes
+    #[arg(long = "watch", conflicts_with = "open")]
+    watch: bool,
+
+    /// Output results as a JSON array of records
+    #[arg(long = "json")]
+    json: bool,
+
+    /// Output results as a TOML document with one [[match]] table per file
+    #[arg(long = "toml", conflicts_with = "json")]
+    toml: bool,
+
+    /// Print each match using a template with {path}, {title}, {tags}, {author}, {dates} and {description}
+    #[arg(long = "format", value_name = "TEMPLATE")]
+    format: Option<String>,
+
+    /// Print path, title and tags for each match (tab-separated)
+    #[arg(short = 'l', long = "long")]
+    long: bool,
+
+    /// Highlight matched titles and tags in --format/--long output
+    #[arg(long = "color", value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Group results into sections by tag or author (not available with -0 or --separator)
+    #[arg(long = "group-by", value_enum, value_name = "KEY", conflicts_with_all = ["nul", "separator"])]
+    group_by: Option<GroupBy>,
+
+    /// Group results under a header per parent directory (not available with -0 or --separator)
+    #[arg(long = "group-by-dir", conflicts_with_all = ["nul", "separator"])]
+    group_by_dir: bool,
+
+    /// Print the distinct values of FIELD across matching files, with counts
+    #[arg(long = "field-values", value_name = "FIELD")]
+    field_values: Option<String>,
+
+    /// Parse MDX `export const meta = {...}` blocks as frontmatter
+    #[arg(long = "mdx")]
+    mdx: bool,
+
+    /// Parser for the frontmatter block; 'auto' goes by the fence (--- YAML, +++ TOML), 'mdx' reads an export instead
+    #[arg(long = "frontmatter-format", value_enum, value_name = "FORMAT", default_value_t = FrontmatterFormat::Auto)]
+    frontmatter_format: FrontmatterFormat,
+
+    /// Also read metadata from a `---`-fenced block at the end of the file (reads whole files)
+    #[arg(long = "end-matter")]
+    end_matter: bool,
+
+    /// Always exit with status 0 when no error occurs, even if nothing matched
+    #[arg(long = "no-status")]
+    no_status: bool,
 }
 
 /// Pre-compiled filters for efficient matching
+#[derive(Default)]
 struct CompiledFilters {
     /// Tag patterns: (lowercase_pattern, regex) for matching both YAML and inline tags
     tag_patterns: Vec<(String, Regex)>,
 
+    /// Tag globs from --tag-glob: (yaml_regex, inline_regex)
+    tag_glob_patterns: Vec<(Regex, Regex)>,
+
+    /// Where YAML tags are read from and which namespace they must be in
+    tag_options: TagOptions,
+
+    /// Tag patterns that must all match ("+tag" in --tags)
+    required_tag_patterns: Vec<(String, Regex)>,
+
+    /// Bounds on the number of distinct tags (--min-tags, --max-tags)
+    min_tags: Option<usize>,
+    max_tags: Option<usize>,
+
+    /// Tag patterns that must not match ("-tag" in --tags)
+    excluded_tag_patterns: Vec<(String, Regex)>,
+
     /// Pre-lowercased title patterns for case-insensitive matching
     title_patterns: Vec<String>,
 
+    /// Pre-compiled title regexes (--title-regex)
+    title_regexes: Vec<Regex>,
+
+    /// Pre-lowercased note alias patterns (--alias)
+    alias_patterns: Vec<String>,
+
+    /// Treat the file stem as a title source (--filename-as-title)
+    filename_as_title: bool,
+
+    /// Heading filters as (level, lowercased text); `None` matches any level
+    heading_patterns: Vec<(Option<usize>, String)>,
+
+    /// Pre-lowercased link target patterns (--links-to)
+    link_patterns: Vec<String>,
+
+    /// Require task checkboxes: any (--has-tasks) or open ones (--has-open-tasks)
+    has_tasks: bool,
+    has_open_tasks: bool,
+
+    /// Bounds on the number of links (--min-links, --max-links)
+    min_links: Option<usize>,
+    max_links: Option<usize>,
+
+    /// Count only links outside code blocks and spans (--skip-code-blocks)
+    skip_code_blocks: bool,
+
+    /// Pre-compiled body regexes (--grep)
+    grep_patterns: Vec<Regex>,
+
+    /// Pre-lowercased description (first paragraph) patterns
+    description_patterns: Vec<String>,
+
     /// Pre-lowercased author patterns for case-insensitive matching
     author_patterns: Vec<String>,
 
+    /// Require every author pattern to match (AND) instead of any (OR)
+    author_match_all: bool,
+
+    /// Combine filter types with OR instead of AND, names and paths included (--any)
+    match_any: bool,
+
+    /// Filter types whose result is negated (--invert)
+    inverts: Vec<InvertTarget>,
+
     /// Pre-compiled regex patterns for filename matching
     name_patterns: Vec<Regex>,
 
+    /// Match name patterns against the whole path (--name-full)
+    name_full_path: bool,
+
+    /// A --name match stands in for the positive content filters (--name-or-content)
+    name_or_content: bool,
+
+    /// Pre-compiled regex patterns for full path matching
+    path_patterns: Vec<Regex>,
+
+    /// Pre-compiled regex patterns for the file stem (--stem)
+    stem_patterns: Vec<Regex>,
+
     /// Pre-parsed field filters (field_name, pattern_lowercase)
     field_patterns: Vec<(String, String)>,
 
+    /// --field-all filters: (field_name, lowercased values that must all be present)
+    field_all_patterns: Vec<(String, Vec<String>)>,
+
+    /// --field-eq filters: (field_name, lowercased value the field must equal)
+    field_eq_patterns: Vec<(String, String)>,
+
+    /// Fields that must be present (--has-field)
+    required_fields: Vec<String>,
+
+    /// Whether a frontmatter must be present (--require-frontmatter) or absent (--no-frontmatter)
+    has_frontmatter: Option<bool>,
+
+    /// Fields that must be absent (--missing-field)
+    missing_fields: Vec<String>,
+
+    /// Drop files marked `draft: true` (--no-drafts)
+    exclude_drafts: bool,
+
+    /// How field filters on distinct field names combine
+    field_logic: FieldLogic,
+
+    /// Field synonyms consulted by author, field and date filters
+    field_aliases: FieldAliases,
+
+    /// How field values are looked up (inline key placement, nested mappings)
+    field_options: FieldMatchOptions,
+
     /// Date filter: files with dates on or after this date
     date_after: Option<NaiveDate>,
 
     /// Date filter: files with dates on or before this date
     date_before: Option<NaiveDate>,
+
+    /// Exclude dates equal to the bounds (--exclusive-dates)
+    dates_exclusive: bool,
+
+    /// Files without any date pass the date filters (--include-dateless)
+    include_dateless: bool,
 }
 
 impl CompiledFilters {
     fn from_args(args: &Args) -> Result<Self> {
-        // Compile tag regex patterns
+        // Tags from --tags-file are ORed with --tag values
+        let mut tags = args.tags.clone();
+        if let Some(path) = &args.tags_file {
+            tags.extend(read_tags_file(path)?);
+        }
+
+        // Compile tag regex patterns, inside the --tag-prefix namespace if any
+        let tag_prefix = args.tag_prefix.as_deref().unwrap_or_default();
         let mut tag_patterns = Vec::new();
-        for tag in &args.tags {
-            let pattern = tag.strip_prefix('#').unwrap_or(tag);
-            // Use word boundaries instead of lookbehind/lookahead (not supported in Rust regex)
-            // Match #tag with optional surrounding non-word characters
-            let regex = RegexBuilder::new(&format!(
-                r"(^|[^[:word:]])#{}([^[:word:]]|$)",
-                regex::escape(pattern)
-            ))
-            .case_insensitive(true)
-            .build()
-            .with_context(|| format!("Failed to compile tag pattern: {}", tag))?;
-            tag_patterns.push((pattern.to_lowercase(), regex));
+        let mut tag_glob_patterns = Vec::new();
+        for tag in &tags {
+            if args.tag_glob {
+                tag_glob_patterns.push(compile_tag_glob(tag, tag_prefix)?);
+            } else {
+                tag_patterns.push(compile_tag_pattern(tag, tag_prefix)?);
+            }
+        }
+
+        // Split --tags specs into required, optional and excluded tags
+        let mut required_tag_patterns = Vec::new();
+        let mut excluded_tag_patterns = Vec::new();
+        for spec in &args.tag_specs {
+            for token in spec.split_whitespace() {
+                let (target, tag) = if let Some(tag) = token.strip_prefix('+') {
+                    (&mut required_tag_patterns, tag)
+                } else if let Some(tag) = token.strip_prefix('-') {
+                    (&mut excluded_tag_patterns, tag)
+                } else {
+                    (&mut tag_patterns, token)
+                };
+                if tag.is_empty() || tag == "#" {
+                    return Err(anyhow::anyhow!("Empty tag in --tags spec '{}'", spec));
+                }
+                target.push(compile_tag_pattern(tag, tag_prefix)?);
+            }
         }
 
         // Pre-lowercase title patterns
         let title_patterns = args.titles.iter().map(|t| t.to_lowercase()).collect();
 
+        // Compile title regexes (case-insensitive with -i)
+        let mut title_regexes = Vec::new();
+        for title in &args.title_regexes {
+            let regex = RegexBuilder::new(title)
+                .case_insensitive(args.ignore_case)
+                .build()
+                .with_context(|| format!("Failed to compile title regex: {}", title))?;
+            title_regexes.push(regex);
+        }
+
+        let heading_patterns = args
+            .headings
+            .iter()
+            .map(|spec| parse_heading_spec(spec))
+            .collect::<Result<_>>()?;
+
+        // Compile grep regexes (case-insensitive with -i)
+        let mut grep_patterns = Vec::new();
+        for grep in &args.greps {
+            let regex = RegexBuilder::new(grep)
+                .case_insensitive(args.ignore_case)
+                .build()
+                .with_context(|| format!("Failed to compile grep pattern: {}", grep))?;
+            grep_patterns.push(regex);
+        }
+
+        // Pre-lowercase description patterns
+        let description_patterns = args.descriptions.iter().map(|d| d.to_lowercase()).collect();
+
         // Pre-lowercase author patterns
         let author_patterns = args.authors.iter().map(|a| a.to_lowercase()).collect();
 
@@ -186,40 +915,57 @@ impl CompiledFilters {
             name_patterns.push(regex);
         }
 
+        // Compile full path regex patterns
+        let mut path_patterns = Vec::new();
+        for path in &args.paths {
+            let regex = RegexBuilder::new(path)
+                .case_insensitive(args.ignore_case)
+                .build()
+                .with_context(|| format!("Failed to compile path pattern: {}", path))?;
+            path_patterns.push(regex);
+        }
+
+        // Compile file stem regex patterns
+        let mut stem_patterns = Vec::new();
+        for stem in &args.stems {
+            let regex = RegexBuilder::new(stem)
+                .case_insensitive(args.ignore_case)
+                .build()
+                .with_context(|| format!("Failed to compile stem pattern: {}", stem))?;
+            stem_patterns.push(regex);
+        }
+
         // Parse field filters
+        let separator = args.field_separator;
+        if separator.is_whitespace() {
+            return Err(anyhow::anyhow!(
+                "Invalid --field-separator: whitespace is not allowed"
+            ));
+        }
         let mut field_patterns = Vec::new();
         for field_spec in &args.fields {
-            let (field, pattern) = field_spec.split_once(':').ok_or_else(|| {
-                anyhow::anyhow!(
-                    "Invalid field filter format: '{}'. Expected 'field:pattern'",
-                    field_spec
-                )
-            })?;
-
-            let field_trimmed = field.trim();
-            let pattern_trimmed = pattern.trim();
-
-            // Validate that both field and pattern are non-empty
-            if field_trimmed.is_empty() && pattern_trimmed.is_empty() {
-                return Err(anyhow::anyhow!(
-                    "Both field and pattern cannot be empty in filter '{}'",
-                    field_spec
-                ));
-            }
-            if field_trimmed.is_empty() {
-                return Err(anyhow::anyhow!(
-                    "Field name cannot be empty in filter '{}'",
-                    field_spec
-                ));
-            }
-            if pattern_trimmed.is_empty() {
+            field_patterns.push(parse_field_spec(field_spec, separator)?);
+        }
+        let mut field_all_patterns = Vec::new();
+        for field_spec in &args.field_all {
+            let (field, values) = parse_field_spec(field_spec, separator)?;
+            let values: Vec<String> = values
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+                .collect();
+            if values.is_empty() {
                 return Err(anyhow::anyhow!(
                     "Pattern cannot be empty in filter '{}'",
                     field_spec
                 ));
             }
-
-            field_patterns.push((field_trimmed.to_string(), pattern_trimmed.to_lowercase()));
+            field_all_patterns.push((field, values));
+        }
+        let mut field_eq_patterns = Vec::new();
+        for field_spec in &args.field_eq {
+            field_eq_patterns.push(parse_field_spec(field_spec, separator)?);
         }
 
         // Parse date filters
@@ -251,72 +997,555 @@ impl CompiledFilters {
 
         Ok(CompiledFilters {
             tag_patterns,
+            tag_glob_patterns,
+            tag_options: TagOptions {
+                prefix: tag_prefix.to_lowercase(),
+                fields: args.tag_fields.clone(),
+            },
+            required_tag_patterns,
+            min_tags: args.min_tags,
+            max_tags: args.max_tags,
+            excluded_tag_patterns,
             title_patterns,
+            alias_patterns: args.aliases.iter().map(|a| a.to_lowercase()).collect(),
+            title_regexes,
+            filename_as_title: args.filename_as_title,
+            heading_patterns,
+            link_patterns: args.links_to.iter().map(|l| l.to_lowercase()).collect(),
+            has_tasks: args.has_tasks,
+            has_open_tasks: args.has_open_tasks,
+            min_links: args.min_links,
+            max_links: args.max_links,
+            skip_code_blocks: args.skip_code_blocks,
+            grep_patterns,
+            description_patterns,
             author_patterns,
+            author_match_all: args.author_all,
+            match_any: args.any,
+            inverts: args.inverts.clone(),
             name_patterns,
+            name_full_path: args.name_full,
+            name_or_content: args.name_or_content,
+            path_patterns,
+            stem_patterns,
             field_patterns,
+            field_all_patterns,
+            field_eq_patterns,
+            required_fields: args.has_fields.clone(),
+            has_frontmatter: if args.require_frontmatter {
+                Some(true)
+            } else if args.no_frontmatter {
+                Some(false)
+            } else {
+                None
+            },
+            missing_fields: args.missing_fields.clone(),
+            exclude_drafts: args.no_drafts,
+            field_logic: args.field_logic,
             date_after,
             date_before,
+            dates_exclusive: args.exclusive_dates,
+            include_dateless: args.include_dateless,
+            field_aliases: FieldAliases::from_args(args)?,
+            field_options: FieldMatchOptions {
+                inline_line_start: args.inline_metadata_prefix,
+                deep: args.deep_fields,
+            },
         })
     }
-}
 
-#[derive(Debug, Deserialize, Serialize)]
-struct Frontmatter {
-    #[serde(default)]
-    title: Option<String>,
+    /// Whether --invert flips the given filter type.
+    fn inverts(&self, target: InvertTarget) -> bool {
+        self.inverts.contains(&target)
+    }
 
-    #[serde(default)]
-    author: Option<String>,
+    /// Whether any --name pattern matches the file name (or the whole path with --name-full).
+    fn matches_name(&self, path: &Path) -> bool {
+        self.name_patterns.iter().any(|regex| {
+            if self.name_full_path {
+                matches_path(path, regex)
+            } else {
+                matches_filename(path, regex)
+            }
+        })
+    }
+}
 
-    #[serde(default)]
-    tags: Option<TagValue>,
+/// Splits a `field:pattern` spec at the separator, trimming both sides and
+/// lowercasing the pattern. A field name containing the separator can be
+/// double-quoted (`"a:b":pattern`) or escape it with a backslash (`a\:b:pattern`).
+fn parse_field_spec(field_spec: &str, separator: char) -> Result<(String, String)> {
+    let (field, pattern) = split_field_spec(field_spec, separator).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid field filter format: '{}'. Expected 'field{}pattern'",
+            field_spec,
+            separator
+        )
+    })?;
 
-    #[serde(flatten)]
-    extra: HashMap<String, serde_yaml::Value>,
-}
+    let field_trimmed = field.trim();
+    let pattern_trimmed = pattern.trim();
 
-#[derive(Debug, Deserialize, Serialize)]
-#[serde(untagged)]
-enum TagValue {
-    Single(String),
-    Array(Vec<String>),
+    // Validate that both field and pattern are non-empty
+    if field_trimmed.is_empty() && pattern_trimmed.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Both field and pattern cannot be empty in filter '{}'",
+            field_spec
+        ));
+    }
+    if field_trimmed.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Field name cannot be empty in filter '{}'",
+            field_spec
+        ));
+    }
+    if pattern_trimmed.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Pattern cannot be empty in filter '{}'",
+            field_spec
+        ));
+    }
+
+    Ok((field_trimmed.to_string(), pattern_trimmed.to_lowercase()))
 }
 
-impl TagValue {
-    fn contains_tag(&self, pattern: &str) -> bool {
-        let pattern_lower = pattern.to_lowercase();
-        match self {
-            TagValue::Single(tag) => tag.to_lowercase().contains(&pattern_lower),
-            TagValue::Array(tags) => tags
-                .iter()
-                .any(|tag| tag.to_lowercase().contains(&pattern_lower)),
-        }
+/// Splits a field spec at the first separator outside a quoted field name,
+/// unescaping `\<separator>` and `\\` in the name. Returns `None` without a
+/// separator or with an unclosed quote.
+fn split_field_spec(field_spec: &str, separator: char) -> Option<(String, &str)> {
+    if let Some(quoted) = field_spec.trim_start().strip_prefix('"') {
+        let (field, rest) = quoted.split_once('"')?;
+        let pattern = rest.trim_start().strip_prefix(separator)?;
+        return Some((field.to_string(), pattern));
     }
-}
 
-/// Helper function to match a pattern against various YAML value types (case-insensitive)
-fn yaml_value_contains(value: &serde_yaml::Value, pattern_lower: &str) -> bool {
-    match value {
-        serde_yaml::Value::String(s) => s.to_lowercase().contains(pattern_lower),
-        serde_yaml::Value::Number(n) => n.to_string().to_lowercase().contains(pattern_lower),
-        serde_yaml::Value::Bool(b) => b.to_string().to_lowercase().contains(pattern_lower),
-        serde_yaml::Value::Sequence(seq) => {
-            seq.iter().any(|v| yaml_value_contains(v, pattern_lower))
+    let mut field = String::new();
+    let mut chars = field_spec.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == separator {
+            return Some((field, &field_spec[i + c.len_utf8()..]));
         }
-        _ => false,
+        let escaped = match c {
+            '\\' => chars.next_if(|&(_, next)| next == separator || next == '\\'),
+            _ => None,
+        };
+        field.push(escaped.map_or(c, |(_, next)| next));
     }
+    None
 }
 
-/// Helper function to parse a date from a YAML value
-fn parse_date_from_yaml_value(value: &serde_yaml::Value) -> Option<NaiveDate> {
-    match value {
-        serde_yaml::Value::String(s) => NaiveDate::parse_from_str(s, "%Y-%m-%d").ok(),
-        _ => None,
-    }
+/// Options for looking up field values in frontmatter and inline metadata
+#[derive(Clone, Copy, Debug, Default)]
+struct FieldMatchOptions {
+    /// Only treat unindented `key: value` lines as inline metadata
+    inline_line_start: bool,
+
+    /// Also search the keys and values of nested mappings
+    deep: bool,
 }
 
-/// Metadata extracted from a markdown file.
+/// Options for matching YAML tags
+#[derive(Clone, Debug, Default)]
+struct TagOptions {
+    /// Pre-lowercased namespace tags must be in (--tag-prefix), empty for none
+    prefix: String,
+
+    /// Frontmatter fields read as tags in addition to `tags` (--tag-field)
+    fields: Vec<String>,
+}
+
+/// Field synonyms, keyed by canonical field name.
+#[derive(Debug, Default)]
+struct FieldAliases(HashMap<String, Vec<String>>);
+
+impl FieldAliases {
+    /// Synonyms enabled by --resolve-frontmatter-aliases
+    const BUILTIN: &[(&str, &[&str])] = &[
+        ("author", &["by", "authors"]),
+        ("date", &["published", "pubdate"]),
+    ];
+
+    fn from_args(args: &Args) -> Result<Self> {
+        let mut aliases: HashMap<String, Vec<String>> = HashMap::new();
+
+        if args.resolve_aliases {
+            for (field, names) in Self::BUILTIN {
+                aliases
+                    .entry(field.to_string())
+                    .or_default()
+                    .extend(names.iter().map(|name| name.to_string()));
+            }
+        }
+
+        for spec in &args.field_aliases {
+            let (field, names) = spec.split_once('=').ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Invalid field alias format: '{}'. Expected 'field=alias1,alias2'",
+                    spec
+                )
+            })?;
+            let field = field.trim();
+            if field.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Field name cannot be empty in alias '{}'",
+                    spec
+                ));
+            }
+            let entry = aliases.entry(field.to_string()).or_default();
+            for name in names.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                if !entry.iter().any(|existing| existing == name) {
+                    entry.push(name.to_string());
+                }
+            }
+        }
+
+        Ok(FieldAliases(aliases))
+    }
+
+    /// Returns the synonyms registered for a canonical field name.
+    fn aliases_of(&self, field_name: &str) -> &[String] {
+        self.0.get(field_name).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Reads newline-separated tags, skipping blank lines and `#` comment lines.
+fn read_tags_file(path: &Path) -> Result<Vec<String>> {
+    read_list_file(path, "tags")
+}
+
+/// Reads the search roots listed in a --dirs-from file, one per line like a tags
+/// file. Directories that don't exist are dropped, with a warning under --verbose.
+fn read_dirs_file(path: &Path, verbose: bool) -> Result<Vec<PathBuf>> {
+    let dirs = read_list_file(path, "directories")?;
+    Ok(dirs
+        .into_iter()
+        .map(PathBuf::from)
+        .filter(|dir| {
+            let exists = dir.is_dir();
+            if !exists && verbose {
+                eprintln!(
+                    "Warning: Directory not found: {} (listed in {})",
+                    dir.display(),
+                    path.display()
+                );
+            }
+            exists
+        })
+        .collect())
+}
+
+/// Reads the trimmed lines of a list file, skipping blank and `#` comment lines.
+fn read_list_file(path: &Path, kind: &str) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {} file: {}", kind, path.display()))?;
+
+    Ok(content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+/// Compiles a tag into its (lowercase_pattern, regex) pair.
+///
+/// A leading `#` is optional. The regex matches inline `#tag` occurrences,
+/// written as `#{prefix}tag` when a --tag-prefix namespace is given.
+fn compile_tag_pattern(tag: &str, prefix: &str) -> Result<(String, Regex)> {
+    let pattern = tag.strip_prefix('#').unwrap_or(tag);
+    // Use word boundaries instead of lookbehind/lookahead (not supported in Rust regex)
+    // Match #tag with optional surrounding non-word characters
+    let regex = RegexBuilder::new(&format!(
+        r"(^|[^[:word:]])#{}{}([^[:word:]]|$)",
+        regex::escape(prefix),
+        regex::escape(pattern)
+    ))
+    .case_insensitive(true)
+    .build()
+    .with_context(|| format!("Failed to compile tag pattern: {}", tag))?;
+    Ok((pattern.to_lowercase(), regex))
+}
+
+/// Compiles a tag glob into its (yaml_regex, inline_regex) pair.
+///
+/// `*` matches any run of non-whitespace characters and `?` a single one.
+/// YAML tags must match the whole glob, either as the full tag or as the part
+/// after a `/` (so `proj-*` matches `proj-alpha` and `work/proj-alpha`).
+/// Inline tags keep the word-boundary wrapping used by `compile_tag_pattern`.
+/// With a --tag-prefix namespace, both only match tags that start with it.
+fn compile_tag_glob(tag: &str, prefix: &str) -> Result<(Regex, Regex)> {
+    let prefix = regex::escape(prefix);
+    let pattern = tag.strip_prefix('#').unwrap_or(tag);
+    let mut glob_regex = String::new();
+    for ch in pattern.chars() {
+        match ch {
+            '*' => glob_regex.push_str(r"[^\s]*"),
+            '?' => glob_regex.push_str(r"[^\s]"),
+            _ => glob_regex.push_str(&regex::escape(&ch.to_string())),
+        }
+    }
+
+    let yaml_regex = RegexBuilder::new(&format!(r"^{}(?:.*/)?{}$", prefix, glob_regex))
+        .case_insensitive(true)
+        .build()
+        .with_context(|| format!("Failed to compile tag glob: {}", tag))?;
+    let inline_regex = RegexBuilder::new(&format!(
+        r"(^|[^[:word:]])#{}(?:[^\s#]*/)?{}([^[:word:]]|$)",
+        prefix, glob_regex
+    ))
+    .case_insensitive(true)
+    .build()
+    .with_context(|| format!("Failed to compile tag glob: {}", tag))?;
+    Ok((yaml_regex, inline_regex))
+}
+
+/// Options controlling how much of each file is read and how its metadata is parsed
+#[derive(Debug, Default)]
+struct ReadOptions {
+    /// Lines to scan for metadata when not in full-text mode
+    head_lines: usize,
+
+    /// Bytes to scan instead of `head_lines` (--head-bytes)
+    head_bytes: Option<usize>,
+
+    /// Lines always read after the frontmatter closes, so inline metadata
+    /// just below a long frontmatter block is still scanned
+    body_head_lines: usize,
+
+    /// Read the entire file instead of only the first `head_lines` lines
+    full_text: bool,
+
+    /// Also parse `export const meta = {...}` blocks used by MDX files
+    mdx: bool,
+
+    /// Fall back to a `---`-fenced block that ends the file
+    end_matter: bool,
+
+    /// Parser for the fenced frontmatter block (--frontmatter-format)
+    frontmatter_format: FrontmatterFormat,
+
+    /// Memory-map large files instead of reading them whole (--mmap)
+    mmap: bool,
+
+    /// Decode files with this encoding instead of strict UTF-8
+    encoding: Option<&'static Encoding>,
+
+    /// Read metadata from this top-level mapping instead of the frontmatter root
+    field_root: Option<String>,
+
+    /// Suffix of the YAML sidecar merged over the frontmatter (--sidecar-ext)
+    sidecar_ext: Option<String>,
+
+    /// Capacity of the line reader's buffer, if not the default (--read-buffer-size)
+    read_buffer_size: Option<usize>,
+}
+
+impl ReadOptions {
+    fn from_args(args: &Args) -> Result<Self> {
+        let encoding = match &args.encoding {
+            Some(label) => Some(
+                Encoding::for_label(label.trim().as_bytes())
+                    .ok_or_else(|| anyhow::anyhow!("Unknown encoding: '{}'", label))?,
+            ),
+            None => None,
+        };
+        if args.read_buffer_size == Some(0) {
+            return Err(anyhow::anyhow!("--read-buffer-size must be at least 1"));
+        }
+
+        Ok(ReadOptions {
+            head_lines: args.head_lines,
+            head_bytes: args.head_bytes,
+            // --head 0 reads only the frontmatter, unless --body-head asks for more
+            body_head_lines: args.body_head_lines.unwrap_or(if args.head_lines == 0 {
+                0
+            } else {
+                DEFAULT_BODY_HEAD_LINES
+            }),
+            full_text: args.full_text,
+            mdx: args.mdx || args.frontmatter_format == FrontmatterFormat::Mdx,
+            end_matter: args.end_matter,
+            frontmatter_format: args.frontmatter_format,
+            mmap: args.mmap,
+            encoding,
+            field_root: args.field_root.clone(),
+            sidecar_ext: args.sidecar_ext.clone(),
+            read_buffer_size: args.read_buffer_size,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Frontmatter {
+    #[serde(default)]
+    title: Option<String>,
+
+    #[serde(default)]
+    author: Option<String>,
+
+    #[serde(default)]
+    tags: Option<TagValue>,
+
+    #[serde(flatten)]
+    extra: HashMap<String, serde_yaml::Value>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum TagValue {
+    Single(String),
+    Array(Vec<String>),
+    /// Tags with metadata (`- name: rust`), matched by their `name` key.
+    Maps(Vec<HashMap<String, serde_yaml::Value>>),
+}
+
+/// The tag shapes as written in YAML, before trimming.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawTagValue {
+    Single(String),
+    Array(Vec<String>),
+    Maps(Vec<HashMap<String, serde_yaml::Value>>),
+}
+
+impl<'de> Deserialize<'de> for TagValue {
+    /// Trims surrounding whitespace from each tag (e.g. `[" rust ", "cli "]`)
+    /// so exact and glob matching see the tag itself; blank entries are dropped.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match RawTagValue::deserialize(deserializer)? {
+            RawTagValue::Single(tag) => TagValue::Single(tag.trim().to_string()),
+            RawTagValue::Array(tags) => TagValue::Array(
+                tags.iter()
+                    .map(|tag| tag.trim())
+                    .filter(|tag| !tag.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            ),
+            RawTagValue::Maps(maps) => TagValue::Maps(maps),
+        })
+    }
+}
+
+impl TagValue {
+    /// Returns the tag names; maps without a string `name` are skipped.
+    fn names(&self) -> Vec<&str> {
+        match self {
+            TagValue::Single(tag) => vec![tag.as_str()],
+            TagValue::Array(tags) => tags.iter().map(String::as_str).collect(),
+            TagValue::Maps(maps) => maps
+                .iter()
+                .filter_map(|map| map.get("name").and_then(serde_yaml::Value::as_str))
+                .map(str::trim)
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Checks for a tag containing `pattern` once the lowercase `prefix` namespace
+    /// is stripped; tags outside the namespace don't count. Pass "" for no namespace.
+    fn contains_tag(&self, pattern: &str, prefix: &str) -> bool {
+        let pattern_lower = pattern.to_lowercase();
+        self.names().iter().any(|tag| {
+            tag.to_lowercase()
+                .strip_prefix(prefix)
+                .is_some_and(|tag| tag.contains(&pattern_lower))
+        })
+    }
+}
+
+/// Helper function to collect the scalar values of a YAML value as strings.
+///
+/// Sequences contribute each scalar element; mappings and nulls contribute nothing.
+fn yaml_value_strings(value: &serde_yaml::Value, out: &mut Vec<String>) {
+    match value {
+        serde_yaml::Value::String(s) => out.push(s.clone()),
+        serde_yaml::Value::Number(n) => out.push(n.to_string()),
+        serde_yaml::Value::Bool(b) => out.push(b.to_string()),
+        serde_yaml::Value::Sequence(seq) => {
+            for v in seq {
+                yaml_value_strings(v, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Helper function to match a pattern against various YAML value types (case-insensitive)
+///
+/// Mappings only match when `deep` is set, in which case both their keys and
+/// values are searched recursively.
+fn yaml_value_contains(value: &serde_yaml::Value, pattern_lower: &str, deep: bool) -> bool {
+    match value {
+        serde_yaml::Value::String(s) => s.to_lowercase().contains(pattern_lower),
+        serde_yaml::Value::Number(n) => n.to_string().to_lowercase().contains(pattern_lower),
+        serde_yaml::Value::Bool(b) => {
+            b.to_string().contains(pattern_lower) || bool_shorthand(pattern_lower) == Some(*b)
+        }
+        serde_yaml::Value::Sequence(seq) => seq
+            .iter()
+            .any(|v| yaml_value_contains(v, pattern_lower, deep)),
+        serde_yaml::Value::Mapping(map) if deep => map.iter().any(|(k, v)| {
+            yaml_value_contains(k, pattern_lower, deep)
+                || yaml_value_contains(v, pattern_lower, deep)
+        }),
+        _ => false,
+    }
+}
+
+/// Checks that every pattern is found in the value: each in some element of a
+/// sequence, while a scalar can only satisfy a single pattern.
+fn yaml_value_contains_all(
+    value: &serde_yaml::Value,
+    patterns_lower: &[String],
+    deep: bool,
+) -> bool {
+    match value {
+        serde_yaml::Value::Sequence(seq) => patterns_lower
+            .iter()
+            .all(|pattern| seq.iter().any(|v| yaml_value_contains(v, pattern, deep))),
+        _ => match patterns_lower {
+            [pattern] => yaml_value_contains(value, pattern, deep),
+            _ => false,
+        },
+    }
+}
+
+/// Reads a pattern matched against a YAML boolean: `yes`/`on`/`1` mean true and
+/// `no`/`off`/`0` mean false, besides `true` and `false` themselves.
+fn bool_shorthand(pattern_lower: &str) -> Option<bool> {
+    match pattern_lower {
+        "true" | "yes" | "on" | "1" => Some(true),
+        "false" | "no" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Checks if a YAML value equals the pattern exactly (trimmed, case-insensitive);
+/// a sequence matches when one of its elements does.
+fn yaml_value_equals(value: &serde_yaml::Value, pattern_lower: &str, deep: bool) -> bool {
+    match value {
+        serde_yaml::Value::String(s) => s.trim().to_lowercase() == pattern_lower,
+        serde_yaml::Value::Number(n) => n.to_string() == pattern_lower,
+        serde_yaml::Value::Bool(b) => bool_shorthand(pattern_lower) == Some(*b),
+        serde_yaml::Value::Sequence(seq) => seq
+            .iter()
+            .any(|v| yaml_value_equals(v, pattern_lower, deep)),
+        serde_yaml::Value::Mapping(map) if deep => map
+            .values()
+            .any(|v| yaml_value_equals(v, pattern_lower, deep)),
+        _ => false,
+    }
+}
+
+/// Helper function to parse a date from a YAML value
+fn parse_date_from_yaml_value(value: &serde_yaml::Value) -> Option<NaiveDate> {
+    match value {
+        serde_yaml::Value::String(s) => NaiveDate::parse_from_str(s, "%Y-%m-%d").ok(),
+        _ => None,
+    }
+}
+
+/// Metadata extracted from a markdown file.
 ///
 /// Design note: This struct stores `raw_content` as a String, which may seem memory-intensive.
 /// However, this design is intentional and already optimized:
@@ -328,40 +1557,229 @@ fn parse_date_from_yaml_value(value: &serde_yaml::Value) -> Option<NaiveDate> {
 ///
 /// Typical memory usage: ~10KB per file in default mode, ~100KB in full-text mode.
 /// Peak memory with 8 threads: ~800KB (8 files × 100KB), which is acceptable.
+/// With --mmap and --full-text, a large file is searched in its mapping instead.
 struct Metadata {
     frontmatter: Option<Frontmatter>,
-    raw_content: String,
+    raw_content: RawContent,
+}
+
+/// The text the content filters search: read into memory, or the whole mapped file.
+enum RawContent {
+    Owned(String),
+    /// Only built by `Metadata::from_mapped`, once `mapped_text` accepted the mapping
+    Mapped(Mmap),
+}
+
+impl std::ops::Deref for RawContent {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            RawContent::Owned(text) => text,
+            // SAFETY: the mapping was validated as UTF-8 before it was wrapped
+            RawContent::Mapped(map) => unsafe { std::str::from_utf8_unchecked(map) },
+        }
+    }
+}
+
+impl From<String> for RawContent {
+    fn from(text: String) -> Self {
+        RawContent::Owned(text)
+    }
+}
+
+/// A file that passed all filters.
+///
+/// `metadata` is retained when it was read for filtering or is needed by the
+/// output mode; it is `None` when the file was only matched by path.
+struct FileMatch {
+    path: PathBuf,
+    metadata: Option<Metadata>,
 }
 
 impl Metadata {
+    /// Shorthand for `from_file_with_options` with only the scan window configured.
+    #[cfg(test)]
     fn from_file(path: &Path, head_lines: usize, full_text: bool, _verbose: bool) -> Result<Self> {
+        let options = ReadOptions {
+            head_lines,
+            full_text,
+            ..Default::default()
+        };
+        Self::from_file_with_options(path, &options)
+    }
+
+    fn from_file_with_options(path: &Path, options: &ReadOptions) -> Result<Self> {
+        // Only a read of the whole file is worth mapping
+        if options.full_text || options.end_matter {
+            if let Some(map) = map_file(path, options) {
+                return Self::from_mapped(path, map, options);
+            }
+        }
+
         // Read file content efficiently (only what we need)
-        let content = read_file_content(path, head_lines, full_text)?;
+        let mut content = read_file_content_with_options(path, options)?;
 
-        // Try to extract YAML frontmatter
-        let frontmatter = extract_frontmatter(&content, path);
+        // Try to extract YAML frontmatter, then end-matter, then an MDX meta export
+        let mut frontmatter = extract_frontmatter_as(&content, path, options.frontmatter_format);
+        if options.end_matter {
+            if frontmatter.is_none() {
+                frontmatter = extract_end_matter(&content);
+            }
+            // The whole file was read for the tail; scan only the usual window
+            if !options.full_text {
+                content = read_head_window(content.as_bytes(), options, path)?;
+            }
+        }
+        if frontmatter.is_none() && options.mdx {
+            frontmatter = extract_mdx_meta(&content, path);
+        }
+        if let Some(ext) = &options.sidecar_ext {
+            frontmatter = merge_sidecar(frontmatter, path, ext);
+        }
+        if let Some(root) = &options.field_root {
+            frontmatter = frontmatter.map(|fm| descend_field_root(fm, root, path));
+        }
 
         // The content we read is already optimized for the mode
+        Ok(Metadata {
+            frontmatter,
+            raw_content: content.into(),
+        })
+    }
+
+    /// Parses a memory-mapped file. The frontmatter and end-matter are extracted
+    /// from the mapping, and with --full-text the mapping itself is searched, so
+    /// at most the scan window is copied.
+    fn from_mapped(path: &Path, map: Mmap, options: &ReadOptions) -> Result<Self> {
+        let text = mapped_text(&map, path)?;
+        let mut frontmatter = extract_frontmatter_as(text, path, options.frontmatter_format);
+        if frontmatter.is_none() && options.end_matter {
+            frontmatter = extract_end_matter(text);
+        }
+        let content = if options.full_text {
+            RawContent::Mapped(map)
+        } else {
+            RawContent::Owned(read_head_window(text.as_bytes(), options, path)?)
+        };
+        if frontmatter.is_none() && options.mdx {
+            frontmatter = extract_mdx_meta(&content, path);
+        }
+        if let Some(ext) = &options.sidecar_ext {
+            frontmatter = merge_sidecar(frontmatter, path, ext);
+        }
+        if let Some(root) = &options.field_root {
+            frontmatter = frontmatter.map(|fm| descend_field_root(fm, root, path));
+        }
+
         Ok(Metadata {
             frontmatter,
             raw_content: content,
         })
     }
 
-    fn has_tag(&self, pattern_lower: &str, tag_regex: &Regex) -> bool {
+    /// Checks YAML tags (including --tag-field fields) inside the --tag-prefix
+    /// namespace, then inline tags with the regex from `compile_tag_pattern`.
+    fn has_tag(&self, pattern_lower: &str, tag_regex: &Regex, options: &TagOptions) -> bool {
         // Check YAML frontmatter (case-insensitive)
         if let Some(ref fm) = self.frontmatter {
             if let Some(ref tags) = fm.tags {
-                if tags.contains_tag(pattern_lower) {
+                if tags.contains_tag(pattern_lower, &options.prefix) {
                     return true;
                 }
             }
         }
+        if self
+            .tag_field_values(&options.fields)
+            .contains_tag(pattern_lower, &options.prefix)
+        {
+            return true;
+        }
 
         // Check inline tags with regex (case-insensitive, works for both full_text and default mode)
         tag_regex.is_match(&self.raw_content)
     }
 
+    /// Returns the YAML frontmatter tags as string slices.
+    fn yaml_tags(&self) -> Vec<&str> {
+        self.frontmatter
+            .as_ref()
+            .and_then(|fm| fm.tags.as_ref())
+            .map(TagValue::names)
+            .unwrap_or_default()
+    }
+
+    /// Returns the inline `#tags` outside code fences, without the `#`.
+    /// A tag follows whitespace or starts the line, and isn't all digits (`#42`).
+    fn inline_tags(&self) -> Vec<&str> {
+        static INLINE_TAG: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"(?:^|\s)#([[:word:]][[:word:]/-]*)").unwrap());
+
+        lines_outside_code_fences(&self.raw_content)
+            .flat_map(|line| INLINE_TAG.captures_iter(line))
+            .filter_map(|caps| caps.get(1).map(|tag| tag.as_str()))
+            .filter(|tag| !tag.chars().all(|c| c.is_ascii_digit()))
+            .collect()
+    }
+
+    /// Collects the values of the --tag-field fields into one tag list.
+    fn tag_field_values(&self, fields: &[String]) -> TagValue {
+        let mut values = Vec::new();
+        if let Some(fm) = &self.frontmatter {
+            for value in fields.iter().filter_map(|field| fm.extra.get(field)) {
+                yaml_value_strings(value, &mut values);
+            }
+        }
+        TagValue::Array(
+            values
+                .iter()
+                .map(|tag| tag.trim())
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+
+    /// Collects the distinct tags: YAML `tags`, the --tag-field fields and inline `#tags`.
+    fn all_tags(&self, fields: &[String]) -> BTreeSet<String> {
+        let mut tags: BTreeSet<String> = self.yaml_tags().into_iter().map(str::to_string).collect();
+        if let TagValue::Array(values) = self.tag_field_values(fields) {
+            tags.extend(values);
+        }
+        tags.extend(self.inline_tags().into_iter().map(str::to_string));
+        tags
+    }
+
+    /// The tags --tag matches: `all_tags` inside the --tag-prefix namespace,
+    /// with the prefix stripped.
+    fn prefixed_tags(&self, options: &TagOptions) -> BTreeSet<String> {
+        let prefix_len = options.prefix.len();
+        self.all_tags(&options.fields)
+            .into_iter()
+            .filter(|tag| {
+                tag.get(..prefix_len)
+                    .is_some_and(|head| head.to_lowercase() == options.prefix)
+            })
+            .map(|tag| tag[prefix_len..].to_string())
+            .filter(|tag| !tag.is_empty())
+            .collect()
+    }
+
+    fn has_tag_glob(&self, yaml_regex: &Regex, inline_regex: &Regex, options: &TagOptions) -> bool {
+        // YAML tags must match the whole glob
+        if self.yaml_tags().iter().any(|tag| yaml_regex.is_match(tag)) {
+            return true;
+        }
+        if let TagValue::Array(tags) = self.tag_field_values(&options.fields) {
+            if tags.iter().any(|tag| yaml_regex.is_match(tag)) {
+                return true;
+            }
+        }
+
+        // Check inline tags with the word-boundary wrapped glob
+        inline_regex.is_match(&self.raw_content)
+    }
+
     fn has_title(&self, pattern_lower: &str) -> bool {
         // Check YAML frontmatter title
         if let Some(ref fm) = self.frontmatter {
@@ -372,9 +1790,40 @@ impl Metadata {
             }
         }
 
-        // Check markdown headings (levels 1–6), allow leading whitespace
+        // Check markdown headings at any level
+        self.has_heading(None, pattern_lower)
+    }
+
+    /// Returns the alternative names in the frontmatter `aliases` field (as used
+    /// by Obsidian), which holds a single name or a list of them.
+    fn note_aliases(&self) -> Vec<&str> {
+        let Some(value) = self
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.extra.get("aliases"))
+        else {
+            return Vec::new();
+        };
+        match value {
+            serde_yaml::Value::String(alias) => vec![alias.as_str()],
+            serde_yaml::Value::Sequence(seq) => seq.iter().filter_map(|v| v.as_str()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Checks if any of the note's aliases contains the pattern (case-insensitive).
+    fn has_alias(&self, pattern_lower: &str) -> bool {
+        self.note_aliases()
+            .iter()
+            .any(|alias| alias.to_lowercase().contains(pattern_lower))
+    }
+
+    /// Checks markdown headings (levels 1–6, leading whitespace allowed) for a
+    /// substring, optionally only those with exactly `level` hashes.
+    /// Lines inside fenced code blocks (e.g. shell comments) are not headings.
+    fn has_heading(&self, level: Option<usize>, pattern_lower: &str) -> bool {
         const MAX_HEADING_LEVEL: usize = 6;
-        for line in self.raw_content.lines() {
+        for line in lines_outside_code_fences(&self.raw_content) {
             let trimmed = line.trim_start();
             // Count leading '#'
             let mut hashes = 0;
@@ -385,7 +1834,7 @@ impl Metadata {
                     break;
                 }
             }
-            if (1..=MAX_HEADING_LEVEL).contains(&hashes) {
+            if (1..=MAX_HEADING_LEVEL).contains(&hashes) && level.is_none_or(|l| l == hashes) {
                 // Expect a space after the hashes
                 let after = &trimmed[hashes..];
                 if after.starts_with(' ') && after.to_lowercase().contains(pattern_lower) {
@@ -397,72 +1846,329 @@ impl Metadata {
         false
     }
 
-    fn has_author(&self, pattern_lower: &str) -> bool {
-        // Check YAML frontmatter author
-        if let Some(ref fm) = self.frontmatter {
-            if let Some(ref author) = fm.author {
-                if author.to_lowercase().contains(pattern_lower) {
-                    return true;
-                }
-            }
+    /// Checks the frontmatter title and the text of each heading against a regex.
+    /// Heading text excludes the leading `#`s, surrounding spaces and closing `#`s,
+    /// so anchors like `^` and `$` apply to the title itself.
+    fn has_title_regex(&self, regex: &Regex) -> bool {
+        let yaml_title = self.frontmatter.as_ref().and_then(|fm| fm.title.as_deref());
+        if yaml_title.is_some_and(|title| regex.is_match(title)) {
+            return true;
         }
 
-        // Check inline format (author: value)
-        for line in self.raw_content.lines() {
-            let trimmed = line.trim_start();
-            if let Some(colon_pos) = trimmed.find(':') {
-                let key = &trimmed[..colon_pos];
-                if key.eq_ignore_ascii_case("author") {
-                    let value = &trimmed[colon_pos + 1..];
-                    if value.to_lowercase().contains(pattern_lower) {
-                        return true;
+        lines_outside_code_fences(&self.raw_content)
+            .filter_map(atx_heading)
+            .any(|(_, text)| regex.is_match(text))
+    }
+
+    /// Returns the scanned content after the preamble line and frontmatter block, if any.
+    fn body(&self) -> &str {
+        let (preamble_end, block_end) = frontmatter_span(&self.raw_content);
+        &self.raw_content[block_end.unwrap_or(preamble_end)..]
+    }
+
+    /// Returns the title to show and sort by: the YAML title, else the first level-1
+    /// heading of the body (ATX `# Title` or Setext `Title` over `===`) outside code fences.
+    fn effective_title(&self) -> Option<&str> {
+        let yaml_title = self.frontmatter.as_ref().and_then(|fm| fm.title.as_deref());
+        if let Some(title) = yaml_title.filter(|title| !title.trim().is_empty()) {
+            return Some(title);
+        }
+
+        // The last line of prose, which a `===` underline turns into a heading
+        let mut previous: Option<&str> = None;
+        for line in lines_outside_code_fences(self.body()) {
+            let trimmed = line.trim();
+            match atx_heading(line) {
+                Some((1, text)) if !text.is_empty() => return Some(text),
+                Some(_) => previous = None,
+                None if trimmed.starts_with('=') && trimmed.chars().all(|c| c == '=') => {
+                    if previous.is_some() {
+                        return previous;
                     }
                 }
+                None => previous = Some(trimmed).filter(|text| !text.is_empty()),
             }
         }
 
-        false
+        None
     }
 
-    fn has_field(&self, field_name: &str, pattern_lower: &str) -> bool {
-        // Check YAML frontmatter
-        if let Some(ref fm) = self.frontmatter {
-            if let Some(value) = fm.extra.get(field_name) {
-                if yaml_value_contains(value, pattern_lower) {
-                    return true;
-                }
-            }
+    /// Returns the title to print for `{title}`, which is never empty: the
+    /// `effective_title` (YAML title, else the first H1), else the file stem.
+    fn resolved_title(&self, path: &Path) -> String {
+        match self.effective_title() {
+            Some(title) => title.to_string(),
+            None => file_stem_title(path),
         }
+    }
 
-        // Check simple inline format (key: value)
-        // Only search in the value part, not the key
-        for line in self.raw_content.lines() {
-            let trimmed = line.trim_start();
-            if let Some(colon_pos) = trimmed.find(':') {
-                let key = &trimmed[..colon_pos];
-                if key.eq_ignore_ascii_case(field_name) {
-                    let value = &trimmed[colon_pos + 1..];
-                    if value.to_lowercase().contains(pattern_lower) {
-                        return true;
-                    }
+    /// Returns the first block of prose, skipping the frontmatter, blank lines
+    /// and headings. Lines of the block are joined with single spaces.
+    fn first_paragraph(&self) -> Option<String> {
+        let mut paragraph: Vec<&str> = Vec::new();
+        for line in self.body().lines() {
+            let trimmed = line.trim();
+            let is_heading = trimmed.starts_with('#') && !trimmed.starts_with("#!");
+            if trimmed.is_empty() || is_heading {
+                if paragraph.is_empty() {
+                    continue;
                 }
+                break;
             }
+            paragraph.push(trimmed);
         }
 
-        false
+        (!paragraph.is_empty()).then(|| paragraph.join(" "))
     }
 
-    /// Extract dates from the frontmatter or content.
-    /// Checks for date, created, updated, modified fields.
-    /// Returns a list of all valid dates found (deduplicated).
-    fn extract_dates(&self) -> Vec<NaiveDate> {
-        let mut dates = Vec::new();
-        let date_fields = ["date", "created", "updated", "modified"];
+    /// Returns the first prose paragraph, cut to at most `max_chars` characters
+    /// (including a trailing `…` when shortened).
+    fn summary(&self, max_chars: usize) -> Option<String> {
+        let paragraph = self.first_paragraph()?;
+        if paragraph.chars().count() <= max_chars {
+            return Some(paragraph);
+        }
 
-        // Check YAML frontmatter first
-        if let Some(ref fm) = self.frontmatter {
-            for field_name in &date_fields {
-                if let Some(value) = fm.extra.get(*field_name) {
+        let cut: String = paragraph
+            .chars()
+            .take(max_chars.saturating_sub(1))
+            .collect();
+        Some(format!("{}…", cut.trim_end()))
+    }
+
+    fn has_author(&self, pattern_lower: &str, inline_line_start: bool) -> bool {
+        // Check YAML frontmatter author and co-authors
+        if self
+            .frontmatter_authors()
+            .iter()
+            .any(|author| author.to_lowercase().contains(pattern_lower))
+        {
+            return true;
+        }
+
+        // Check inline format (author: value)
+        for line in self.raw_content.lines() {
+            if let Some((key, value)) = split_inline_field(line, inline_line_start) {
+                if key.eq_ignore_ascii_case("author")
+                    && value.to_lowercase().contains(pattern_lower)
+                {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Returns the frontmatter `author` followed by each entry of an `authors` list
+    /// (or a single `authors` string), as used by co-authored notes.
+    fn frontmatter_authors(&self) -> Vec<String> {
+        let mut authors = Vec::new();
+        if let Some(ref fm) = self.frontmatter {
+            authors.extend(fm.author.clone());
+            if let Some(value) = fm.extra.get("authors") {
+                yaml_value_strings(value, &mut authors);
+            }
+        }
+        authors
+    }
+
+    fn has_field(&self, field_name: &str, pattern_lower: &str, options: FieldMatchOptions) -> bool {
+        // Check YAML frontmatter
+        if let Some(ref fm) = self.frontmatter {
+            if let Some(value) = fm.extra.get(field_name) {
+                if yaml_value_contains(value, pattern_lower, options.deep) {
+                    return true;
+                }
+            }
+        }
+
+        // Check simple inline format (key: value)
+        // Only search in the value part, not the key
+        for line in self.raw_content.lines() {
+            if let Some((key, value)) = split_inline_field(line, options.inline_line_start) {
+                if key.eq_ignore_ascii_case(field_name)
+                    && value.to_lowercase().contains(pattern_lower)
+                {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Checks that a field holds all of the values (--field-all); an inline
+    /// `key: value` line counts as a single value.
+    fn has_field_all(
+        &self,
+        field_name: &str,
+        patterns_lower: &[String],
+        options: FieldMatchOptions,
+    ) -> bool {
+        if let Some(value) = self
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.extra.get(field_name))
+        {
+            return yaml_value_contains_all(value, patterns_lower, options.deep);
+        }
+        match patterns_lower {
+            [pattern] => self.has_field(field_name, pattern, options),
+            _ => false,
+        }
+    }
+
+    /// Checks that a field equals the value exactly (--field-eq); the YAML value
+    /// takes precedence over inline `key: value` lines.
+    fn has_field_eq(
+        &self,
+        field_name: &str,
+        pattern_lower: &str,
+        options: FieldMatchOptions,
+    ) -> bool {
+        if let Some(value) = self
+            .frontmatter
+            .as_ref()
+            .and_then(|fm| fm.extra.get(field_name))
+        {
+            return yaml_value_equals(value, pattern_lower, options.deep);
+        }
+        self.raw_content.lines().any(|line| {
+            split_inline_field(line, options.inline_line_start).is_some_and(|(key, value)| {
+                key.eq_ignore_ascii_case(field_name) && value.trim().to_lowercase() == pattern_lower
+            })
+        })
+    }
+
+    /// Checks if a field is defined in the frontmatter or as an inline `key:` line,
+    /// regardless of its value.
+    fn has_field_key(&self, field_name: &str, inline_line_start: bool) -> bool {
+        if let Some(ref fm) = self.frontmatter {
+            let defined = match field_name {
+                "title" => fm.title.is_some(),
+                "author" => fm.author.is_some(),
+                "tags" => fm.tags.is_some(),
+                _ => fm.extra.contains_key(field_name),
+            };
+            if defined {
+                return true;
+            }
+        }
+
+        self.raw_content.lines().any(|line| {
+            split_inline_field(line, inline_line_start)
+                .is_some_and(|(key, _)| key.eq_ignore_ascii_case(field_name))
+        })
+    }
+
+    /// Returns the targets of the links in the scanned content: the URL of each
+    /// `[text](url)` (or image) link, `<...>`-wrapped URLs included, and the
+    /// note name of each `[[wikilink]]`.
+    fn link_targets(&self) -> Vec<&str> {
+        link_targets_in(&self.raw_content).collect()
+    }
+
+    /// Counts the links in the scanned content, like `link_targets`. With
+    /// `skip_code`, links in code fences and inline `code spans` don't count.
+    fn link_count(&self, skip_code: bool) -> usize {
+        static CODE_SPAN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`[^`\n]*`").unwrap());
+
+        if !skip_code {
+            return link_targets_in(&self.raw_content).count();
+        }
+        lines_outside_code_fences(&self.raw_content)
+            .map(|line| link_targets_in(&CODE_SPAN.replace_all(line, "")).count())
+            .sum()
+    }
+
+    /// Counts the task checkboxes outside code fences as (open, done):
+    /// `- [ ]` is open, `- [x]` (or `[X]`) is done. `*` and `+` bullets count too.
+    fn task_counts(&self) -> (usize, usize) {
+        static TASK: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"^\s*[-*+] \[([ xX])\]").unwrap());
+
+        lines_outside_code_fences(&self.raw_content)
+            .filter_map(|line| TASK.captures(line))
+            .fold((0, 0), |(open, done), caps| match &caps[1] {
+                " " => (open + 1, done),
+                _ => (open, done + 1),
+            })
+    }
+
+    /// Checks whether the file is marked as a draft: a frontmatter `draft` that is
+    /// boolean `true` or the string `"true"`, or an inline `draft: true` line when
+    /// there is no frontmatter.
+    fn is_draft(&self, inline_line_start: bool) -> bool {
+        let is_true = |value: &str| value.trim().eq_ignore_ascii_case("true");
+
+        if let Some(ref fm) = self.frontmatter {
+            return match fm.extra.get("draft") {
+                Some(serde_yaml::Value::Bool(draft)) => *draft,
+                Some(serde_yaml::Value::String(draft)) => is_true(draft),
+                _ => false,
+            };
+        }
+
+        self.raw_content.lines().any(|line| {
+            split_inline_field(line, inline_line_start)
+                .is_some_and(|(key, value)| key.eq_ignore_ascii_case("draft") && is_true(value))
+        })
+    }
+
+    /// Collect the values of a field from the frontmatter, or from inline
+    /// `key: value` lines when the frontmatter doesn't declare it.
+    /// Sequence-valued fields contribute each element.
+    fn field_values(&self, field_name: &str, inline_line_start: bool) -> Vec<String> {
+        let mut values = Vec::new();
+
+        if let Some(ref fm) = self.frontmatter {
+            match field_name {
+                "title" => values.extend(fm.title.clone()),
+                "author" => values.extend(fm.author.clone()),
+                "tags" => {
+                    if let Some(tags) = &fm.tags {
+                        values.extend(tags.names().into_iter().map(str::to_string));
+                    }
+                }
+                _ => {
+                    if let Some(value) = fm.extra.get(field_name) {
+                        yaml_value_strings(value, &mut values);
+                    }
+                }
+            }
+            if !values.is_empty() {
+                return values;
+            }
+        }
+
+        for line in self.raw_content.lines() {
+            if let Some((key, value)) = split_inline_field(line, inline_line_start) {
+                let value = value.trim();
+                if key.eq_ignore_ascii_case(field_name) && !value.is_empty() {
+                    values.push(value.to_string());
+                }
+            }
+        }
+
+        values
+    }
+
+    /// Extract dates from the frontmatter or content.
+    /// Checks for date, created, updated, modified fields and their aliases.
+    /// Returns a list of all valid dates found (deduplicated).
+    fn extract_dates(&self, aliases: &FieldAliases) -> Vec<NaiveDate> {
+        let mut dates = Vec::new();
+        let mut date_fields: Vec<&str> = Vec::new();
+        for field_name in ["date", "created", "updated", "modified"] {
+            date_fields.push(field_name);
+            date_fields.extend(aliases.aliases_of(field_name).iter().map(String::as_str));
+        }
+
+        // Check YAML frontmatter first
+        if let Some(ref fm) = self.frontmatter {
+            for field_name in &date_fields {
+                if let Some(value) = fm.extra.get(*field_name) {
                     if let Some(date) = parse_date_from_yaml_value(value) {
                         dates.push(date);
                     }
@@ -491,13 +2197,16 @@ impl Metadata {
         dates
     }
 
-    /// Check if any date matches the date filters
+    /// Check if any date matches the date filters.
+    /// Bounds are inclusive unless `exclusive` is set.
     fn matches_date_filters(
         &self,
         date_after: Option<NaiveDate>,
         date_before: Option<NaiveDate>,
+        exclusive: bool,
+        aliases: &FieldAliases,
     ) -> bool {
-        let dates = self.extract_dates();
+        let dates = self.extract_dates(aliases);
 
         // If no dates found, don't match date filters
         if dates.is_empty() {
@@ -506,18 +2215,159 @@ impl Metadata {
 
         // Check if ANY date satisfies the filters
         dates.iter().any(|date| {
-            let after_check = date_after.is_none_or(|after| date >= &after);
-            let before_check = date_before.is_none_or(|before| date <= &before);
+            let after_check = date_after.is_none_or(|after| {
+                if exclusive {
+                    date > &after
+                } else {
+                    date >= &after
+                }
+            });
+            let before_check = date_before.is_none_or(|before| {
+                if exclusive {
+                    date < &before
+                } else {
+                    date <= &before
+                }
+            });
             after_check && before_check
         })
     }
 }
 
+/// Parses a --heading spec into an optional level and the lowercased text.
+///
+/// A prefix before the first `:` is a level only if it is all digits, so
+/// `Note: draft` searches for that text at any level.
+fn parse_heading_spec(spec: &str) -> Result<(Option<usize>, String)> {
+    if let Some((prefix, text)) = spec.split_once(':') {
+        if !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_digit()) {
+            let level: usize = prefix.parse().unwrap_or(0);
+            if !(1..=6).contains(&level) {
+                return Err(anyhow::anyhow!(
+                    "Invalid --heading level '{}' (expected 1-6)",
+                    prefix
+                ));
+            }
+            return Ok((Some(level), text.to_lowercase()));
+        }
+    }
+    Ok((None, spec.to_lowercase()))
+}
+
+/// The last resort for a title: the file name without its extension.
+fn file_stem_title(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Yields the targets of the `[text](url)` links and `[[wikilinks]]` in the text,
+/// inline links first (see `Metadata::link_targets`).
+fn link_targets_in(content: &str) -> impl Iterator<Item = &str> {
+    static INLINE_LINK: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"\[[^\]\n]*\]\(\s*(?:<([^>\n]*)>|([^)\s]+))(?:\s+"[^"]*")?\s*\)"#).unwrap()
+    });
+    static WIKILINK: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\[\[([^\]|#\n]+)[^\]\n]*\]\]").unwrap());
+
+    INLINE_LINK
+        .captures_iter(content)
+        .chain(WIKILINK.captures_iter(content))
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .map(|target| target.as_str().trim())
+}
+
+/// Iterates over the lines of `content` that aren't inside a fenced code block.
+///
+/// A fence opens with three or more backticks or tildes and closes with a line
+/// of at least as many of the same character; the fence lines are skipped too.
+/// An unclosed fence runs to the end of the content.
+fn lines_outside_code_fences(content: &str) -> impl Iterator<Item = &str> {
+    let mut fence: Option<(char, usize)> = None;
+    content.lines().filter(move |line| {
+        let trimmed = line.trim_start();
+        let marker = trimmed.chars().next().filter(|&c| c == '`' || c == '~');
+        let run = marker.map_or(0, |c| trimmed.chars().take_while(|&ch| ch == c).count());
+
+        match fence {
+            Some((c, len)) => {
+                if marker == Some(c) && run >= len && trimmed[run..].trim().is_empty() {
+                    fence = None;
+                }
+                false
+            }
+            None if run >= 3 => {
+                fence = marker.map(|c| (c, run));
+                false
+            }
+            None => true,
+        }
+    })
+}
+
+/// Parses an ATX heading (`## Text ##`) into its level and text.
+/// Leading whitespace is allowed, as in `has_title`.
+fn atx_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let text = rest.trim().trim_end_matches('#').trim_end();
+    Some((level, text))
+}
+
+/// Splits an inline `key: value` line at its first colon.
+/// With `line_start`, indented lines are not treated as inline metadata.
+fn split_inline_field(line: &str, line_start: bool) -> Option<(&str, &str)> {
+    if line_start && line.starts_with(char::is_whitespace) {
+        return None;
+    }
+    line.trim_start().split_once(':')
+}
+
 /// Read file content efficiently based on mode
 /// - If full_text: read entire file
 /// - If not full_text: read only first N lines (or until frontmatter end, whichever is longer)
+#[cfg(test)]
 fn read_file_content(path: &Path, head_lines: usize, full_text: bool) -> Result<String> {
-    if full_text {
+    let options = ReadOptions {
+        head_lines,
+        full_text,
+        ..Default::default()
+    };
+    read_file_content_with_options(path, &options)
+}
+
+/// Read file content according to `options`.
+///
+/// Besides the frontmatter-completeness rule, an MDX meta export (with `--mdx`)
+/// that starts inside the scan window is read until its braces balance.
+fn read_file_content_with_options(path: &Path, options: &ReadOptions) -> Result<String> {
+    if let Some(encoding) = options.encoding {
+        // Legacy encodings can't be decoded line by line, so decode the whole file
+        let bytes =
+            fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+        let (decoded, _, had_errors) = encoding.decode(&bytes);
+        if had_errors {
+            return Err(anyhow::anyhow!(
+                "File is not valid {}: {}",
+                encoding.name(),
+                path.display()
+            ));
+        }
+        if options.full_text || options.end_matter {
+            return Ok(decoded.into_owned());
+        }
+        return read_head_window(decoded.as_bytes(), options, path);
+    }
+
+    if options.full_text || options.end_matter {
         // Read entire file
         return fs::read_to_string(path)
             .with_context(|| format!("Failed to read file: {}", path.display()));
@@ -526,24 +2376,142 @@ fn read_file_content(path: &Path, head_lines: usize, full_text: bool) -> Result<
     // Open file with buffered reader for efficient line-by-line reading
     let file =
         fs::File::open(path).with_context(|| format!("Failed to open file: {}", path.display()))?;
-    let reader = BufReader::new(file);
+    read_head_window(buffered_reader(file, options), options, path)
+}
+
+/// Wraps the file in a line reader, sized by --read-buffer-size if given.
+fn buffered_reader(file: fs::File, options: &ReadOptions) -> BufReader<fs::File> {
+    match options.read_buffer_size {
+        Some(capacity) => BufReader::with_capacity(capacity, file),
+        None => BufReader::new(file),
+    }
+}
+
+/// Maps the file into memory when --mmap is on, the file is read as UTF-8 and is
+/// at least [`MMAP_MIN_BYTES`] long. Returns `None` to fall back to normal reading,
+/// including when the file can't be mapped.
+fn map_file(path: &Path, options: &ReadOptions) -> Option<Mmap> {
+    if !options.mmap || options.encoding.is_some() {
+        return None;
+    }
+    let file = fs::File::open(path).ok()?;
+    if file.metadata().ok()?.len() < MMAP_MIN_BYTES {
+        return None;
+    }
+    // SAFETY: the mapping is only read, and never outlives this scan. A file
+    // truncated by another process mid-scan is the caveat of opting into --mmap.
+    unsafe { Mmap::map(&file) }.ok()
+}
 
+/// Validates a mapped file as UTF-8 before any regex runs over it.
+fn mapped_text<'a>(map: &'a Mmap, path: &Path) -> Result<&'a str> {
+    std::str::from_utf8(map).with_context(|| format!("File is not valid UTF-8: {}", path.display()))
+}
+
+/// Collects lines up to the scan window, completing any frontmatter in progress.
+///
+/// With --head-bytes, lines are read with a byte cap, so a huge line past the
+/// budget is never read in full unless it may belong to the frontmatter, an MDX
+/// meta export or the --body-head lines.
+fn read_head_window(
+    mut reader: impl BufRead,
+    options: &ReadOptions,
+    path: &Path,
+) -> Result<String> {
+    let head_lines = options.head_lines;
     let mut lines_vec = Vec::new();
     let mut line_count = 0;
+    let mut preamble_lines = 0;
     let mut in_frontmatter = false;
     let mut frontmatter_ended = false;
+    let mut body_start = 0;
+    let mut fence = "";
+    let mut mdx_depth = 0;
+    let mut mdx_start = 0;
+    let mut bytes_read = 0;
+
+    loop {
+        let mut raw = Vec::new();
+        let cap = options
+            .head_bytes
+            .map(|max_bytes| max_bytes.saturating_sub(bytes_read) + 1);
+        let complete = read_raw_line(&mut reader, cap, &mut raw, path)?;
+        if raw.is_empty() {
+            break;
+        }
+        if let (false, Some(max_bytes)) = (complete, options.head_bytes) {
+            let may_keep_whole = in_frontmatter
+                || mdx_depth > 0
+                || (frontmatter_ended && line_count < body_start + options.body_head_lines)
+                || (line_count <= preamble_lines && could_open_frontmatter(&raw))
+                || (options.mdx && raw.trim_ascii_start().starts_with(b"export"));
+            if !may_keep_whole {
+                if bytes_read < max_bytes {
+                    lines_vec.push(utf8_prefix(&raw[..max_bytes - bytes_read], path)?);
+                }
+                break;
+            }
+            read_raw_line(&mut reader, None, &mut raw, path)?;
+        }
+        let mut line = line_from_bytes(raw, path)?;
+        let trimmed = line.trim();
 
-    for line_result in reader.lines() {
-        let line = line_result
-            .with_context(|| format!("Failed to read line from file: {}", path.display()))?;
+        // Stop before this line if:
+        // 1. We've read enough lines (or bytes, with --head-bytes) AND
+        // 2. We're not in the middle of frontmatter or an MDX meta export AND
+        // 3. The body budget after a closed frontmatter is used up
+        // A preamble or opening fence is always read, so `--head 0` still sees the frontmatter.
+        let opens_frontmatter = (line_count == 0 && is_frontmatter_preamble(trimmed))
+            || (line_count == preamble_lines && FRONTMATTER_FENCES.contains(&trimmed));
+        let budget_spent = match options.head_bytes {
+            Some(max_bytes) => bytes_read >= max_bytes,
+            None => line_count >= head_lines + preamble_lines,
+        };
+        if !opens_frontmatter
+            && budget_spent
+            && !in_frontmatter
+            && mdx_depth <= 0
+            && (!frontmatter_ended || line_count >= body_start + options.body_head_lines)
+        {
+            break;
+        }
 
         // Track frontmatter boundaries
-        let trimmed = line.trim();
-        if line_count == 0 && trimmed == "---" {
+        if line_count == 0 && is_frontmatter_preamble(trimmed) {
+            // A leading shebang or HTML comment doesn't count against the scan window
+            preamble_lines = 1;
+        } else if line_count == preamble_lines && FRONTMATTER_FENCES.contains(&trimmed) {
             in_frontmatter = true;
-        } else if in_frontmatter && trimmed == "---" {
+            fence = if trimmed == "+++" { "+++" } else { "---" };
+        } else if in_frontmatter && trimmed == fence {
             in_frontmatter = false;
             frontmatter_ended = true;
+            body_start = line_count + 1;
+        } else if mdx_depth > 0 {
+            mdx_depth += brace_delta(trimmed);
+        } else if options.mdx && !in_frontmatter {
+            if let Some(literal) = mdx_meta_export_value(trimmed) {
+                mdx_depth = brace_delta(literal);
+                mdx_start = line_count;
+            }
+        }
+
+        // With --head-bytes, the line that crosses the budget is cut short, unless
+        // it belongs to the frontmatter, an MDX meta export or the --body-head lines
+        if let Some(max_bytes) = options.head_bytes {
+            let kept_whole = in_frontmatter
+                || mdx_depth > 0
+                || (frontmatter_ended && line_count < body_start + options.body_head_lines);
+            if !kept_whole && bytes_read + line.len() > max_bytes {
+                let mut end = max_bytes.saturating_sub(bytes_read);
+                while !line.is_char_boundary(end) {
+                    end -= 1;
+                }
+                line.truncate(end);
+                lines_vec.push(line);
+                break;
+            }
+            bytes_read += line.len() + 1;
         }
 
         lines_vec.push(line);
@@ -557,275 +2525,3325 @@ fn read_file_content(path: &Path, head_lines: usize, full_text: bool) -> Result<
                 path.display()
             ));
         }
-
-        // Stop reading if:
-        // 1. We've read enough lines AND
-        // 2. We're not in the middle of frontmatter
-        if line_count >= head_lines && (!in_frontmatter || frontmatter_ended) {
-            break;
+        if mdx_depth > 0 && line_count - mdx_start > MAX_FRONTMATTER_LINES {
+            return Err(anyhow::anyhow!(
+                "MDX meta export exceeds maximum size ({} lines) in: {}",
+                MAX_FRONTMATTER_LINES,
+                path.display()
+            ));
         }
     }
 
     Ok(lines_vec.join("\n"))
 }
 
-/// Extracts YAML frontmatter from markdown content.
+/// Appends the next line, with its line ending, to `buf`, reading at most `cap`
+/// bytes. Returns whether the line was read to its end (or the end of input).
+fn read_raw_line(
+    reader: &mut impl BufRead,
+    cap: Option<usize>,
+    buf: &mut Vec<u8>,
+    path: &Path,
+) -> Result<bool> {
+    let read_error = || format!("Failed to read line from file: {}", path.display());
+    let limit = cap.map_or(u64::MAX, |cap| cap as u64);
+    std::io::Read::take(&mut *reader, limit)
+        .read_until(b'\n', buf)
+        .with_context(read_error)?;
+    Ok(buf.ends_with(b"\n") || reader.fill_buf().with_context(read_error)?.is_empty())
+}
+
+/// Whether the start of a line cut short may be a preamble line or an opening fence.
+fn could_open_frontmatter(raw: &[u8]) -> bool {
+    let start = raw.trim_ascii_start();
+    [&b"#!"[..], b"<!--", b"---", b"+++"]
+        .iter()
+        .any(|prefix| start.starts_with(prefix))
+}
+
+/// Turns a line read by `read_raw_line` into text, without its `\n` or `\r\n`.
+fn line_from_bytes(mut raw: Vec<u8>, path: &Path) -> Result<String> {
+    if raw.ends_with(b"\n") {
+        raw.pop();
+        if raw.ends_with(b"\r") {
+            raw.pop();
+        }
+    }
+    String::from_utf8(raw).map_err(|e| {
+        anyhow::anyhow!(e).context(format!("Failed to read line from file: {}", path.display()))
+    })
+}
+
+/// Decodes the bytes of a line cut short, dropping a character split by the cut.
+fn utf8_prefix(bytes: &[u8], path: &Path) -> Result<String> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(text.to_string()),
+        Err(e) if e.error_len().is_none() => {
+            Ok(String::from_utf8_lossy(&bytes[..e.valid_up_to()]).into_owned())
+        }
+        Err(e) => Err(anyhow::anyhow!(e)
+            .context(format!("Failed to read line from file: {}", path.display()))),
+    }
+}
+
+/// Checks if a line may precede the opening `---` of the frontmatter.
 ///
-/// Frontmatter must be delimited by `---` at the start and end.
-/// Returns `None` if no valid frontmatter is found or if YAML parsing fails.
-/// YAML parsing errors are always logged to stderr as they affect search accuracy.
+/// Generated files sometimes start with a shebang (`#!...`) or a single-line
+/// HTML comment (`<!-- generated -->`) before the frontmatter block.
+fn is_frontmatter_preamble(trimmed: &str) -> bool {
+    trimmed.starts_with("#!") || (trimmed.starts_with("<!--") && trimmed.ends_with("-->"))
+}
+
+/// Converts CRLF (and stray CR) line endings to LF, borrowing when there are none.
+fn normalize_line_endings(content: &str) -> std::borrow::Cow<'_, str> {
+    if content.contains('\r') {
+        std::borrow::Cow::Owned(content.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        std::borrow::Cow::Borrowed(content)
+    }
+}
+
+/// Lines that open and close a frontmatter block
+const FRONTMATTER_FENCES: &[&str] = &["---", "+++"];
+
+/// Returns the content without its fenced frontmatter block (and the preamble
+/// line before it); content without frontmatter is returned unchanged.
+fn strip_frontmatter(content: &str) -> &str {
+    match frontmatter_span(content) {
+        (_, Some(block_end)) => &content[block_end..],
+        (_, None) => content,
+    }
+}
+
+/// Finds the byte offsets where a preamble line ends, and where the fenced
+/// frontmatter block after it ends (if it opens one), with the delimiters of
+/// `extract_frontmatter_as`. An unclosed block runs to the end of the content.
+fn frontmatter_span(content: &str) -> (usize, Option<usize>) {
+    let mut lines = content.split_inclusive('\n').peekable();
+    let mut offset = 0;
+
+    if let Some(line) = lines.next_if(|line| is_frontmatter_preamble(line.trim())) {
+        offset += line.len();
+    }
+    let preamble_end = offset;
+    let Some(open) = lines.next_if(|line| FRONTMATTER_FENCES.contains(&line.trim())) else {
+        return (preamble_end, None);
+    };
+    offset += open.len();
+    for line in lines {
+        offset += line.len();
+        if line.trim() == open.trim() {
+            break;
+        }
+    }
+    (preamble_end, Some(offset))
+}
+
+/// Extracts frontmatter the way `auto` --frontmatter-format does.
+#[cfg(test)]
 fn extract_frontmatter(content: &str, path: &Path) -> Option<Frontmatter> {
+    extract_frontmatter_as(content, path, FrontmatterFormat::Auto)
+}
+
+/// Extracts frontmatter from markdown content.
+///
+/// Frontmatter must be delimited by `---` (or `+++`) at the start and end. A single
+/// shebang or HTML comment line is allowed before the opening delimiter.
+/// With `auto` the fence picks the parser (`---` YAML, `+++` TOML); a forced
+/// format parses the block whatever the fence, e.g. TOML between `---` lines.
+/// With `mdx` there is no fenced block to find.
+/// Returns `None` if no valid frontmatter is found or if parsing fails.
+/// Parsing errors are always logged to stderr as they affect search accuracy.
+/// Windows (CRLF) line endings are normalized before parsing.
+fn extract_frontmatter_as(
+    content: &str,
+    path: &Path,
+    format: FrontmatterFormat,
+) -> Option<Frontmatter> {
+    // The export block is parsed by extract_mdx_meta, as with --mdx
+    if format == FrontmatterFormat::Mdx {
+        return None;
+    }
+    let content = normalize_line_endings(content);
     let mut lines = content.lines();
 
-    // Check if first line is "---"
-    if lines.next()?.trim() != "---" {
+    // Check if first line is a fence, skipping a single preamble line
+    let mut first = lines.next()?.trim();
+    if is_frontmatter_preamble(first) {
+        first = lines.next()?.trim();
+    }
+    if !FRONTMATTER_FENCES.contains(&first) {
         return None;
     }
 
-    // Collect lines until next "---"
-    let mut yaml_lines = Vec::new();
+    // Collect lines until the matching closing fence
+    let mut block_lines = Vec::new();
     for line in lines {
-        if line.trim() == "---" {
+        if line.trim() == first {
             break;
         }
-        yaml_lines.push(line);
+        block_lines.push(line);
     }
 
-    if yaml_lines.is_empty() {
+    if block_lines.is_empty() {
         return None;
     }
 
-    let yaml_content = yaml_lines.join("\n");
-    match serde_yaml::from_str(&yaml_content) {
+    let format = match format {
+        FrontmatterFormat::Auto if first == "+++" => FrontmatterFormat::Toml,
+        format => format,
+    };
+    match parse_frontmatter_block(&block_lines.join("\n"), format) {
         Ok(fm) => Some(fm),
         Err(e) => {
-            eprintln!(
-                "Warning: Failed to parse YAML frontmatter in {}: {}",
-                path.display(),
-                e
+            report_file_warning(
+                path,
+                "frontmatter",
+                &format!("Failed to parse {} frontmatter in", format.label()),
+                &e,
             );
             None
         }
     }
 }
 
-/// Checks if a file path's filename matches a given regex pattern.
-fn matches_filename(path: &Path, regex: &Regex) -> bool {
-    let filename = match path.file_name().and_then(|n| n.to_str()) {
-        Some(name) => name,
-        None => return false,
+/// Parses the text between the fences. TOML and JSON go through a YAML value,
+/// so every format fills `Frontmatter` the same way.
+fn parse_frontmatter_block(block: &str, format: FrontmatterFormat) -> Result<Frontmatter> {
+    let value = match format {
+        FrontmatterFormat::Auto | FrontmatterFormat::Yaml => {
+            return Ok(serde_yaml::from_str(block)?)
+        }
+        FrontmatterFormat::Toml => toml_to_yaml(toml::Value::Table(block.parse()?)),
+        FrontmatterFormat::Json => {
+            serde_yaml::to_value(serde_json::from_str::<serde_json::Value>(block)?)?
+        }
+        FrontmatterFormat::Mdx => {
+            return Err(anyhow::anyhow!("MDX meta exports are not fenced blocks"))
+        }
     };
+    Ok(serde_yaml::from_value(value)?)
+}
 
-    regex.is_match(filename)
+/// Converts a TOML value to YAML. Datetimes become strings, as YAML would read them.
+fn toml_to_yaml(value: toml::Value) -> serde_yaml::Value {
+    match value {
+        toml::Value::String(s) => serde_yaml::Value::String(s),
+        toml::Value::Integer(n) => serde_yaml::Value::Number(n.into()),
+        toml::Value::Float(n) => serde_yaml::Value::Number(n.into()),
+        toml::Value::Boolean(b) => serde_yaml::Value::Bool(b),
+        toml::Value::Datetime(d) => serde_yaml::Value::String(d.to_string()),
+        toml::Value::Array(items) => {
+            serde_yaml::Value::Sequence(items.into_iter().map(toml_to_yaml).collect())
+        }
+        toml::Value::Table(table) => serde_yaml::Value::Mapping(
+            table
+                .into_iter()
+                .map(|(key, value)| (serde_yaml::Value::String(key), toml_to_yaml(value)))
+                .collect(),
+        ),
+    }
 }
 
-/// Determines if a file should be included based on its content metadata.
+/// Extracts metadata from a `---`-fenced block that ends the file (end-matter).
 ///
-/// Applies all content-based filters (tags, titles, fields, dates) with AND logic between filter types
-/// and OR logic within each filter type (e.g., match any of the specified tags).
-fn should_include_file_by_content(metadata: &Metadata, filters: &CompiledFilters) -> bool {
-    // Check tag filters (OR logic: match any tag)
-    if !filters.tag_patterns.is_empty() {
-        let tag_matched = filters
-            .tag_patterns
-            .iter()
-            .any(|(pattern, regex)| metadata.has_tag(pattern, regex));
-        if !tag_matched {
-            return false;
-        }
+/// The closing fence must be the last non-blank line, which tells it apart from
+/// a horizontal rule mid-document. Blocks that aren't a YAML mapping (e.g. prose
+/// between two rules) are ignored without a warning.
+fn extract_end_matter(content: &str) -> Option<Frontmatter> {
+    let content = normalize_line_endings(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let close = lines.iter().rposition(|line| !line.trim().is_empty())?;
+    if lines[close].trim() != "---" {
+        return None;
     }
+    let open = lines[..close]
+        .iter()
+        .rposition(|line| line.trim() == "---")?;
 
-    // Check title filters
-    if !filters.title_patterns.is_empty() {
-        let title_matched = filters
-            .title_patterns
-            .iter()
-            .any(|pattern| metadata.has_title(pattern));
-        if !title_matched {
-            return false;
-        }
+    let yaml_content = lines[open + 1..close].join("\n");
+    if yaml_content.trim().is_empty() {
+        return None;
     }
+    serde_yaml::from_str(&yaml_content).ok()
+}
 
-    // Check author filters (OR logic: match any author)
+/// Merges `over` onto `base`: values in `over` win, keys only in `base` are kept.
+fn overlay_frontmatter(mut over: Frontmatter, base: Frontmatter) -> Frontmatter {
+    over.title = over.title.or(base.title);
+    over.author = over.author.or(base.author);
+    over.tags = over.tags.or(base.tags);
+    for (key, value) in base.extra {
+        over.extra.entry(key).or_insert(value);
+    }
+    over
+}
+
+/// Merges the YAML sidecar of `path` (`path` + `ext`) over its frontmatter.
+///
+/// A missing sidecar leaves the frontmatter as is; one that fails to parse is
+/// reported and ignored.
+fn merge_sidecar(frontmatter: Option<Frontmatter>, path: &Path, ext: &str) -> Option<Frontmatter> {
+    let mut sidecar_name = path.as_os_str().to_owned();
+    sidecar_name.push(ext);
+    let sidecar_path = PathBuf::from(sidecar_name);
+    let Ok(text) = fs::read_to_string(&sidecar_path) else {
+        return frontmatter;
+    };
+
+    match serde_yaml::from_str::<Frontmatter>(&text) {
+        Ok(sidecar) => Some(match frontmatter {
+            Some(fm) => overlay_frontmatter(sidecar, fm),
+            None => sidecar,
+        }),
+        Err(e) => {
+            report_file_warning(&sidecar_path, "sidecar", "Failed to parse sidecar", &e);
+            frontmatter
+        }
+    }
+}
+
+/// Re-reads the frontmatter from the mapping under `root` (e.g. `frontmatter:`
+/// or `fields:` wrappers used by some site pipelines).
+///
+/// Nested values take precedence; top-level keys not present in the wrapper are
+/// kept. If `root` is missing or isn't a mapping, the frontmatter is unchanged.
+fn descend_field_root(mut fm: Frontmatter, root: &str, path: &Path) -> Frontmatter {
+    let Some(value) = fm.extra.remove(root) else {
+        return fm;
+    };
+    if !value.is_mapping() {
+        fm.extra.insert(root.to_string(), value);
+        return fm;
+    }
+
+    match serde_yaml::from_value::<Frontmatter>(value.clone()) {
+        Ok(nested) => overlay_frontmatter(nested, fm),
+        Err(e) => {
+            report_file_warning(
+                path,
+                "frontmatter",
+                &format!("Failed to parse '{}' frontmatter mapping in", root),
+                &e,
+            );
+            fm.extra.insert(root.to_string(), value);
+            fm
+        }
+    }
+}
+
+/// Variable names recognized as MDX metadata exports (`export const meta = {...}`)
+const MDX_META_EXPORTS: &[&str] = &["meta", "frontmatter"];
+
+/// Returns the text after `=` if the line starts an MDX metadata export.
+fn mdx_meta_export_value(trimmed: &str) -> Option<&str> {
+    let rest = trimmed.strip_prefix("export")?.trim_start();
+    let rest = rest.strip_prefix("const")?.trim_start();
+    let (name, value) = rest.split_once('=')?;
+    if MDX_META_EXPORTS.contains(&name.trim()) {
+        Some(value.trim_start())
+    } else {
+        None
+    }
+}
+
+/// Net change in `{`/`}` nesting for a line, ignoring braces inside quoted strings.
+fn brace_delta(line: &str) -> i32 {
+    let mut delta = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for ch in line.chars() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == q {
+                quote = None;
+            }
+            continue;
+        }
+        match ch {
+            '"' | '\'' | '`' => quote = Some(ch),
+            '{' => delta += 1,
+            '}' => delta -= 1,
+            _ => {}
+        }
+    }
+    delta
+}
+
+/// Extracts metadata from an MDX `export const meta = { ... }` block.
+///
+/// The object literal is parsed best-effort: it is converted into a YAML flow
+/// mapping (which already accepts unquoted keys, single quotes and trailing
+/// commas), with `//` comment lines dropped and a space ensured after each key.
+/// Parse failures are logged to stderr like YAML frontmatter errors.
+fn extract_mdx_meta(content: &str, path: &Path) -> Option<Frontmatter> {
+    static KEY_COLON: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"(^|[{,\s])([A-Za-z_$][\w$]*|"[^"]*"|'[^']*')\s*:"#).unwrap()
+    });
+
+    let mut literal = String::new();
+    let mut depth = 0;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if literal.is_empty() {
+            if let Some(value) = mdx_meta_export_value(trimmed) {
+                if !value.starts_with('{') {
+                    return None;
+                }
+                depth = brace_delta(value);
+                literal.push_str(value);
+                literal.push('\n');
+            }
+        } else if !trimmed.starts_with("//") {
+            depth += brace_delta(trimmed);
+            literal.push_str(trimmed);
+            literal.push('\n');
+        }
+        if !literal.is_empty() && depth <= 0 {
+            break;
+        }
+    }
+
+    if literal.is_empty() {
+        return None;
+    }
+
+    let literal = literal.trim_end().trim_end_matches(';');
+    let yaml_content = KEY_COLON.replace_all(literal, "$1$2: ");
+    match serde_yaml::from_str(&yaml_content) {
+        Ok(fm) => Some(fm),
+        Err(e) => {
+            report_file_warning(path, "mdx", "Failed to parse MDX meta export in", &e);
+            None
+        }
+    }
+}
+
+/// Checks if a file's name without its extension matches a given regex pattern.
+fn matches_stem(path: &Path, regex: &Regex) -> bool {
+    path.file_stem()
+        .and_then(|stem| stem.to_str())
+        .is_some_and(|stem| regex.is_match(stem))
+}
+
+/// Checks if a file path's filename matches a given regex pattern.
+fn matches_filename(path: &Path, regex: &Regex) -> bool {
+    let filename = match path.file_name().and_then(|n| n.to_str()) {
+        Some(name) => name,
+        None => return false,
+    };
+
+    regex.is_match(filename)
+}
+
+/// Checks if a file's full path matches a given regex pattern.
+///
+/// Path separators are normalized to `/` so patterns behave the same on every platform.
+fn matches_path(path: &Path, regex: &Regex) -> bool {
+    let normalized = path.to_string_lossy().replace('\\', "/");
+    regex.is_match(&normalized)
+}
+
+/// Determines if a file should be included based on its content metadata alone.
+#[cfg(test)]
+fn should_include_file_by_content(metadata: &Metadata, filters: &CompiledFilters) -> bool {
+    should_include_file(None, metadata, filters)
+}
+
+/// Determines if a file should be included, with no `--grep` patterns to check.
+#[cfg(test)]
+fn should_include_file(
+    path: Option<&Path>,
+    metadata: &Metadata,
+    filters: &CompiledFilters,
+) -> bool {
+    include_file(path, metadata, filters, || true)
+}
+
+/// Determines if a file should be included based on its content metadata.
+///
+/// Applies all content-based filters (tags, titles, fields, dates) with AND logic between filter types
+/// and OR logic within each filter type (e.g., match any of the specified tags).
+/// With `--any`, filter types combine with OR instead and `--name`/`--path` join in;
+/// exclusions (`-tag`, `--missing-field`, `--no-drafts`) apply either way.
+/// The path, when known, feeds path-aware options such as `--filename-as-title`.
+///
+/// The `--grep` check is supplied by the caller and runs last, so the file is only
+/// scanned when the other filters leave the outcome open.
+fn include_file(
+    path: Option<&Path>,
+    metadata: &Metadata,
+    filters: &CompiledFilters,
+    grep: impl FnOnce() -> bool,
+) -> bool {
+    // Checks whether the field, or one of its aliases, is defined at all
+    let defines = |field: &str| {
+        let line_start = filters.field_options.inline_line_start;
+        metadata.has_field_key(field, line_start)
+            || filters
+                .field_aliases
+                .aliases_of(field)
+                .iter()
+                .any(|alias| metadata.has_field_key(alias, line_start))
+    };
+
+    // Exclusions come first: they apply even under --any
+    if filters
+        .excluded_tag_patterns
+        .iter()
+        .any(|(pattern, regex)| metadata.has_tag(pattern, regex, &filters.tag_options))
+    {
+        return false;
+    }
+    if filters.missing_fields.iter().any(|field| defines(field)) {
+        return false;
+    }
+    if filters.exclude_drafts && metadata.is_draft(filters.field_options.inline_line_start) {
+        return false;
+    }
+
+    let mut outcome = FilterOutcome::new(filters.match_any);
+
+    // With --name-or-content, a name match stands in for the positive content filters
+    if filters.name_or_content && !filters.match_any {
+        outcome.positives_met = path.is_some_and(|path| filters.matches_name(path));
+    }
+
+    // With --any, the name and path patterns are one more alternative
+    if filters.match_any {
+        if !filters.name_patterns.is_empty() {
+            let name_matched = path.is_some_and(|path| filters.matches_name(path));
+            if let Some(decided) = outcome.record(name_matched) {
+                return decided;
+            }
+        }
+        if !filters.path_patterns.is_empty() {
+            let path_matched = path.is_some_and(|path| {
+                filters
+                    .path_patterns
+                    .iter()
+                    .any(|regex| matches_path(path, regex))
+            });
+            if let Some(decided) = outcome.record(path_matched) {
+                return decided;
+            }
+        }
+        if !filters.stem_patterns.is_empty() {
+            let stem_matched = path.is_some_and(|path| {
+                filters
+                    .stem_patterns
+                    .iter()
+                    .any(|regex| matches_stem(path, regex))
+            });
+            if let Some(decided) = outcome.record(stem_matched) {
+                return decided;
+            }
+        }
+    }
+
+    // Check frontmatter presence; an empty block parses to no frontmatter
+    if let Some(required) = filters.has_frontmatter {
+        if let Some(decided) = outcome.record(metadata.frontmatter.is_some() == required) {
+            return decided;
+        }
+    }
+
+    // With --filename-as-title, the file stem counts as one more title
+    let stem = path
+        .filter(|_| filters.filename_as_title)
+        .and_then(|path| path.file_stem())
+        .and_then(|stem| stem.to_str());
+
+    // Check tag filters (OR logic: match any tag or tag glob)
+    if !filters.tag_patterns.is_empty() || !filters.tag_glob_patterns.is_empty() {
+        let tag_matched = filters
+            .tag_patterns
+            .iter()
+            .any(|(pattern, regex)| metadata.has_tag(pattern, regex, &filters.tag_options))
+            || filters
+                .tag_glob_patterns
+                .iter()
+                .any(|(yaml_regex, inline_regex)| {
+                    metadata.has_tag_glob(yaml_regex, inline_regex, &filters.tag_options)
+                });
+        if let Some(decided) =
+            outcome.record_invertible(tag_matched, filters.inverts(InvertTarget::Tag))
+        {
+            return decided;
+        }
+    }
+
+    // Check required tags from --tags
+    if !filters.required_tag_patterns.is_empty() {
+        let required_matched = filters
+            .required_tag_patterns
+            .iter()
+            .all(|(pattern, regex)| metadata.has_tag(pattern, regex, &filters.tag_options));
+        if let Some(decided) = outcome.record(required_matched) {
+            return decided;
+        }
+    }
+
+    // Check tag count bounds
+    if filters.min_tags.is_some() || filters.max_tags.is_some() {
+        let count = metadata.all_tags(&filters.tag_options.fields).len();
+        let count_matched = filters.min_tags.is_none_or(|min| count >= min)
+            && filters.max_tags.is_none_or(|max| count <= max);
+        if let Some(decided) = outcome.record(count_matched) {
+            return decided;
+        }
+    }
+
+    // Check title filters
+    if !filters.title_patterns.is_empty() {
+        let stem_lower = stem.map(str::to_lowercase);
+        let title_matched = filters.title_patterns.iter().any(|pattern| {
+            metadata.has_title(pattern)
+                || stem_lower
+                    .as_deref()
+                    .is_some_and(|stem| stem.contains(pattern.as_str()))
+        });
+        if let Some(decided) =
+            outcome.record_invertible(title_matched, filters.inverts(InvertTarget::Title))
+        {
+            return decided;
+        }
+    }
+
+    // Check title regex filters
+    if !filters.title_regexes.is_empty() {
+        let title_matched = filters.title_regexes.iter().any(|regex| {
+            metadata.has_title_regex(regex) || stem.is_some_and(|s| regex.is_match(s))
+        });
+        if let Some(decided) =
+            outcome.record_invertible(title_matched, filters.inverts(InvertTarget::Title))
+        {
+            return decided;
+        }
+    }
+
+    // Check alias filters
+    if !filters.alias_patterns.is_empty() {
+        let alias_matched = filters
+            .alias_patterns
+            .iter()
+            .any(|pattern| metadata.has_alias(pattern));
+        if let Some(decided) = outcome.record(alias_matched) {
+            return decided;
+        }
+    }
+
+    // Check heading filters
+    if !filters.heading_patterns.is_empty() {
+        let heading_matched = filters
+            .heading_patterns
+            .iter()
+            .any(|(level, pattern)| metadata.has_heading(*level, pattern));
+        if let Some(decided) = outcome.record(heading_matched) {
+            return decided;
+        }
+    }
+
+    // Check link target filters
+    if !filters.link_patterns.is_empty() {
+        let targets: Vec<String> = metadata
+            .link_targets()
+            .iter()
+            .map(|target| target.to_lowercase())
+            .collect();
+        let links_to = |pattern: &String| targets.iter().any(|target| target.contains(pattern));
+        if let Some(decided) = outcome.record(filters.link_patterns.iter().any(links_to)) {
+            return decided;
+        }
+    }
+
+    // Check link count bounds
+    if filters.min_links.is_some() || filters.max_links.is_some() {
+        let count = metadata.link_count(filters.skip_code_blocks);
+        let count_matched = filters.min_links.is_none_or(|min| count >= min)
+            && filters.max_links.is_none_or(|max| count <= max);
+        if let Some(decided) = outcome.record(count_matched) {
+            return decided;
+        }
+    }
+
+    // Check task filters
+    if filters.has_tasks || filters.has_open_tasks {
+        let (open, done) = metadata.task_counts();
+        let tasks_matched = if filters.has_open_tasks {
+            open > 0
+        } else {
+            open + done > 0
+        };
+        if let Some(decided) = outcome.record(tasks_matched) {
+            return decided;
+        }
+    }
+
+    // Check description filters
+    if !filters.description_patterns.is_empty() {
+        let description = metadata
+            .first_paragraph()
+            .unwrap_or_default()
+            .to_lowercase();
+        let description_matched = filters
+            .description_patterns
+            .iter()
+            .any(|pattern| description.contains(pattern.as_str()));
+        if let Some(decided) = outcome.record(description_matched) {
+            return decided;
+        }
+    }
+
+    // Check author filters (OR logic by default, AND logic with --author-all)
     if !filters.author_patterns.is_empty() {
-        let author_matched = filters
-            .author_patterns
+        let mut patterns = filters.author_patterns.iter();
+        let author_matched = if filters.author_match_all {
+            patterns.all(|pattern| has_author_or_alias(metadata, pattern, filters))
+        } else {
+            patterns.any(|pattern| has_author_or_alias(metadata, pattern, filters))
+        };
+        if let Some(decided) =
+            outcome.record_invertible(author_matched, filters.inverts(InvertTarget::Author))
+        {
+            return decided;
+        }
+    }
+
+    // Check field filters (OR logic within a field name, --field-logic across names)
+    if !filters.field_patterns.is_empty() {
+        let mut per_field: Vec<(&str, bool)> = Vec::new();
+        for (field, pattern) in &filters.field_patterns {
+            let options = filters.field_options;
+            let matched = metadata.has_field(field, pattern, options)
+                || filters
+                    .field_aliases
+                    .aliases_of(field)
+                    .iter()
+                    .any(|alias| metadata.has_field(alias, pattern, options));
+            match per_field.iter_mut().find(|(name, _)| name == field) {
+                Some((_, field_matched)) => *field_matched |= matched,
+                None => per_field.push((field, matched)),
+            }
+        }
+        let field_matched = match filters.field_logic {
+            FieldLogic::All => per_field.iter().all(|(_, matched)| *matched),
+            FieldLogic::Any => per_field.iter().any(|(_, matched)| *matched),
+        };
+        if let Some(decided) =
+            outcome.record_invertible(field_matched, filters.inverts(InvertTarget::Field))
+        {
+            return decided;
+        }
+    }
+
+    // Check --field-all filters (AND logic: every spec must hold)
+    if !filters.field_all_patterns.is_empty() {
+        let all_matched = filters.field_all_patterns.iter().all(|(field, values)| {
+            let options = filters.field_options;
+            metadata.has_field_all(field, values, options)
+                || filters
+                    .field_aliases
+                    .aliases_of(field)
+                    .iter()
+                    .any(|alias| metadata.has_field_all(alias, values, options))
+        });
+        if let Some(decided) = outcome.record(all_matched) {
+            return decided;
+        }
+    }
+
+    // Check --field-eq filters (AND logic: every spec must hold)
+    if !filters.field_eq_patterns.is_empty() {
+        let all_matched = filters.field_eq_patterns.iter().all(|(field, value)| {
+            let options = filters.field_options;
+            metadata.has_field_eq(field, value, options)
+                || filters
+                    .field_aliases
+                    .aliases_of(field)
+                    .iter()
+                    .any(|alias| metadata.has_field_eq(alias, value, options))
+        });
+        if let Some(decided) = outcome.record(all_matched) {
+            return decided;
+        }
+    }
+
+    // Check field presence filters
+    if !filters.required_fields.is_empty() {
+        let fields_defined = filters.required_fields.iter().all(|field| defines(field));
+        if let Some(decided) = outcome.record(fields_defined) {
+            return decided;
+        }
+    }
+
+    // Check date filters (if any date filter is specified)
+    if filters.date_after.is_some() || filters.date_before.is_some() {
+        let dateless = || metadata.extract_dates(&filters.field_aliases).is_empty();
+        let date_matched = metadata.matches_date_filters(
+            filters.date_after,
+            filters.date_before,
+            filters.dates_exclusive,
+            &filters.field_aliases,
+        ) || (filters.include_dateless && dateless());
+        if let Some(decided) = outcome.record(date_matched) {
+            return decided;
+        }
+    }
+
+    // Check body patterns last, since they may need another read of the file
+    if !filters.grep_patterns.is_empty() && !outcome.positives_met {
+        if let Some(decided) = outcome.record(grep()) {
+            return decided;
+        }
+    }
+
+    outcome.finish()
+}
+
+/// Combines the verdicts of the active filter types: all must match, or one with `--any`.
+struct FilterOutcome {
+    any: bool,
+    active: bool,
+    /// A --name-or-content name match already satisfies the positive filters
+    positives_met: bool,
+}
+
+impl FilterOutcome {
+    fn new(any: bool) -> Self {
+        FilterOutcome {
+            any,
+            active: false,
+            positives_met: false,
+        }
+    }
+
+    /// Records one filter type's verdict, returning the final answer once it is settled.
+    fn record(&mut self, matched: bool) -> Option<bool> {
+        if self.positives_met {
+            return None;
+        }
+        self.active = true;
+        (matched == self.any).then_some(matched)
+    }
+
+    /// Records a filter type --invert may flip. An inverted type excludes files,
+    /// so it still applies after a --name-or-content name match.
+    fn record_invertible(&mut self, matched: bool, inverted: bool) -> Option<bool> {
+        let verdict = matched != inverted;
+        if inverted && self.positives_met {
+            return (!verdict).then_some(false);
+        }
+        self.record(verdict)
+    }
+
+    /// The answer once every filter type agreed, or when none was given.
+    fn finish(self) -> bool {
+        !(self.any && self.active)
+    }
+}
+
+/// Checks the author, then any fields registered as author aliases.
+fn has_author_or_alias(
+    metadata: &Metadata,
+    pattern_lower: &str,
+    filters: &CompiledFilters,
+) -> bool {
+    let options = filters.field_options;
+    metadata.has_author(pattern_lower, options.inline_line_start)
+        || filters
+            .field_aliases
+            .aliases_of("author")
             .iter()
-            .any(|pattern| metadata.has_author(pattern));
-        if !author_matched {
-            return false;
+            .any(|alias| metadata.has_field(alias, pattern_lower, options))
+}
+
+/// Checks if the selected output mode needs each file's metadata,
+/// in which case metadata is read even when no content filter is given.
+fn output_needs_metadata(args: &Args) -> bool {
+    args.print_dates
+        || args.format.is_some()
+        || args.long
+        || args.group_by.is_some()
+        || args.sort_field.is_some()
+        || args.summary
+        || args.print_frontmatter
+        || args.toml
+        || args.show_match
+        || matches!(args.sort, SortKey::Title | SortKey::Date)
+        || args.command.is_some()
+}
+
+/// Buckets matches by each of their tags or authors, sorted by key.
+/// A file with several tags appears under each; files without any are left out.
+fn group_matches(
+    matches: &[FileMatch],
+    group_by: GroupBy,
+    tag_options: &TagOptions,
+    inline_line_start: bool,
+) -> BTreeMap<String, Vec<PathBuf>> {
+    let mut groups: BTreeMap<String, Vec<PathBuf>> = BTreeMap::new();
+    for file_match in matches {
+        let Some(metadata) = &file_match.metadata else {
+            continue;
+        };
+        let keys = match group_by {
+            GroupBy::Tag => metadata.prefixed_tags(tag_options).into_iter().collect(),
+            GroupBy::Author => {
+                let mut authors = metadata.field_values("author", inline_line_start);
+                authors.extend(metadata.field_values("authors", inline_line_start));
+                authors
+            }
+        };
+        for key in keys {
+            let paths = groups.entry(key).or_default();
+            if !paths.contains(&file_match.path) {
+                paths.push(file_match.path.clone());
+            }
+        }
+    }
+    groups
+}
+
+/// Outputs each group as a heading line followed by its indented paths,
+/// or as a JSON object keyed by group with path arrays.
+fn output_groups(groups: &BTreeMap<String, Vec<PathBuf>>, json: bool) -> Result<()> {
+    if json {
+        let object: BTreeMap<&str, Vec<String>> = groups
+            .iter()
+            .map(|(key, paths)| {
+                let paths = paths.iter().map(|p| p.display().to_string()).collect();
+                (key.as_str(), paths)
+            })
+            .collect();
+        let json =
+            serde_json::to_string_pretty(&object).context("Failed to serialize JSON output")?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    for (index, (key, paths)) in groups.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        println!("{}", key);
+        for path in paths {
+            println!("  {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// A single result record in `--json` output.
+/// Optional fields are only present when the corresponding option is given.
+#[derive(Serialize)]
+struct JsonRecord {
+    path: String,
+
+    /// File size, omitted when the file can't be statted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_bytes: Option<u64>,
+
+    /// Modification time in RFC 3339, omitted when it can't be read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<String>,
+
+    /// Extracted dates, present with --print-dates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dates: Option<Vec<String>>,
+
+    /// First prose paragraph, present with --summary (null when there is none)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<Option<String>>,
+}
+
+/// Outputs matches as a JSON array of records.
+fn output_json(matches: &[FileMatch], args: &Args) -> Result<()> {
+    let aliases = FieldAliases::from_args(args)?;
+    let records: Vec<JsonRecord> = matches
+        .iter()
+        .map(|m| {
+            let stat = match fs::metadata(&m.path) {
+                Ok(stat) => Some(stat),
+                Err(e) => {
+                    report_read_warning(&m.path, "Failed to stat", &e, args.verbose);
+                    None
+                }
+            };
+            let modified = stat.as_ref().and_then(|stat| stat.modified().ok());
+
+            JsonRecord {
+                path: m.path.display().to_string(),
+                size_bytes: stat.as_ref().map(|stat| stat.len()),
+                modified: modified.map(|time| DateTime::<Utc>::from(time).to_rfc3339()),
+                dates: args.print_dates.then(|| match_dates(m, &aliases)),
+                summary: args.summary.then(|| match_summary(m, args.summary_length)),
+            }
+        })
+        .collect();
+
+    let json = serde_json::to_string_pretty(&records).context("Failed to serialize JSON output")?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Returns the dates found in a match, formatted as YYYY-MM-DD.
+fn match_dates(file_match: &FileMatch, aliases: &FieldAliases) -> Vec<String> {
+    file_match
+        .metadata
+        .as_ref()
+        .map(|metadata| {
+            metadata
+                .extract_dates(aliases)
+                .iter()
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The `--toml` output document: an array of `[[match]]` tables.
+#[derive(Serialize)]
+struct TomlDocument {
+    #[serde(rename = "match")]
+    matches: Vec<TomlRecord>,
+}
+
+/// A single `[[match]]` table in `--toml` output. TOML has no null, so a
+/// missing title leaves the key out.
+#[derive(Serialize)]
+struct TomlRecord {
+    path: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+
+    /// YAML, --tag-field and inline tags, sorted
+    tags: Vec<String>,
+
+    dates: Vec<String>,
+}
+
+/// Outputs matches as a TOML document.
+fn output_toml(matches: &[FileMatch], args: &Args) -> Result<()> {
+    let aliases = FieldAliases::from_args(args)?;
+    let document = TomlDocument {
+        matches: matches
+            .iter()
+            .map(|m| TomlRecord {
+                path: m.path.display().to_string(),
+                title: m
+                    .metadata
+                    .as_ref()
+                    .and_then(|metadata| metadata.effective_title())
+                    .map(str::to_string),
+                tags: m
+                    .metadata
+                    .as_ref()
+                    .map(|metadata| metadata.all_tags(&args.tag_fields).into_iter().collect())
+                    .unwrap_or_default(),
+                dates: match_dates(m, &aliases),
+            })
+            .collect(),
+    };
+    print!(
+        "{}",
+        toml::to_string(&document).context("Failed to serialize TOML output")?
+    );
+    Ok(())
+}
+
+/// Collects a match's parsed frontmatter into a key-sorted map (empty without frontmatter).
+fn frontmatter_map(file_match: &FileMatch) -> Result<BTreeMap<String, serde_yaml::Value>> {
+    let mut map = BTreeMap::new();
+    let Some(fm) = file_match
+        .metadata
+        .as_ref()
+        .and_then(|m| m.frontmatter.as_ref())
+    else {
+        return Ok(map);
+    };
+
+    for (key, value) in &fm.extra {
+        map.insert(key.clone(), value.clone());
+    }
+    if let Some(title) = &fm.title {
+        map.insert("title".to_string(), serde_yaml::Value::from(title.as_str()));
+    }
+    if let Some(author) = &fm.author {
+        map.insert(
+            "author".to_string(),
+            serde_yaml::Value::from(author.as_str()),
+        );
+    }
+    if let Some(tags) = &fm.tags {
+        map.insert("tags".to_string(), serde_yaml::to_value(tags)?);
+    }
+    Ok(map)
+}
+
+/// Outputs each path followed by its frontmatter, indented, with a blank line between files.
+fn output_frontmatter(matches: &[FileMatch], format: DumpFormat) -> Result<()> {
+    for (index, file_match) in matches.iter().enumerate() {
+        let map = frontmatter_map(file_match)?;
+        let dump = match format {
+            DumpFormat::Yaml if map.is_empty() => "{}".to_string(),
+            DumpFormat::Yaml => serde_yaml::to_string(&map).context("Failed to serialize YAML")?,
+            DumpFormat::Json => serde_json::to_string_pretty(&map).with_context(|| {
+                format!(
+                    "Failed to serialize frontmatter of {} as JSON",
+                    file_match.path.display()
+                )
+            })?,
+        };
+
+        if index > 0 {
+            println!();
+        }
+        println!("{}", file_match.path.display());
+        for line in dump.lines() {
+            println!("  {}", line);
+        }
+    }
+    Ok(())
+}
+
+/// Returns the --summary excerpt of a match, if it has any prose.
+fn match_summary(file_match: &FileMatch, max_chars: usize) -> Option<String> {
+    file_match.metadata.as_ref()?.summary(max_chars)
+}
+
+/// Outputs every --grep hit of each file as `path:line:text`, like ripgrep.
+fn output_grep_lines(
+    files: &[PathBuf],
+    regexes: &[Regex],
+    options: &ReadOptions,
+    separator: Option<&str>,
+    verbose: bool,
+) {
+    for file in files {
+        match grep_lines(file, regexes, options, false) {
+            Ok(hits) => {
+                for (number, line) in hits {
+                    let record = format!("{}:{}:{}", file.display(), number, line);
+                    print_record(&record, separator);
+                }
+            }
+            Err(e) => report_read_error(file, &e, verbose),
+        }
+    }
+}
+
+/// Finds the lines behind a match within the scanned content: inline tags,
+/// inline `author:` lines and --grep hits, as 0-based line indices.
+fn match_lines(metadata: &Metadata, filters: &CompiledFilters) -> Vec<usize> {
+    let tag_regexes: Vec<&Regex> = filters
+        .tag_patterns
+        .iter()
+        .chain(&filters.required_tag_patterns)
+        .map(|(_, regex)| regex)
+        .chain(filters.tag_glob_patterns.iter().map(|(_, inline)| inline))
+        .chain(&filters.grep_patterns)
+        .collect();
+    let line_start = filters.field_options.inline_line_start;
+    let is_author_line = |line: &str| {
+        split_inline_field(line, line_start).is_some_and(|(key, value)| {
+            let value = value.to_lowercase();
+            key.eq_ignore_ascii_case("author")
+                && filters
+                    .author_patterns
+                    .iter()
+                    .any(|pattern| value.contains(pattern.as_str()))
+        })
+    };
+
+    metadata
+        .raw_content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            tag_regexes.iter().any(|regex| regex.is_match(line)) || is_author_line(line)
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Outputs each path followed by its matching lines (`N:line`) and `after`
+/// lines of trailing context (`N-line`), numbered from 1 like grep.
+fn output_match_lines(matches: &[FileMatch], filters: &CompiledFilters, after: usize) {
+    for file_match in matches {
+        println!("{}", file_match.path.display());
+        let Some(metadata) = &file_match.metadata else {
+            continue;
+        };
+
+        let lines: Vec<&str> = metadata.raw_content.lines().collect();
+        let matched = match_lines(metadata, filters);
+        // Next line not yet printed, so overlapping context isn't repeated
+        let mut next = 0;
+        for &index in &matched {
+            let end = (index + after).min(lines.len() - 1);
+            for (offset, line) in lines[..=end].iter().enumerate().skip(next.max(index)) {
+                let mark = if matched.contains(&offset) { ':' } else { '-' };
+                println!("{}{}{}", offset + 1, mark, line);
+            }
+            next = end + 1;
+        }
+    }
+}
+
+/// Outputs the contents of each file (--cat, read with `full_text` options),
+/// optionally without the frontmatter.
+/// Each file gets a `==> path <==` header and a blank line before the next one;
+/// with a separator, the contents are printed bare, each ending with it.
+fn output_contents(
+    files: &[PathBuf],
+    options: &ReadOptions,
+    strip: bool,
+    separator: Option<&str>,
+    verbose: bool,
+) {
+    let mut first = true;
+    for file in files {
+        let content = match read_file_content_with_options(file, options) {
+            Ok(content) => content,
+            Err(e) => {
+                report_read_error(file, &e, verbose);
+                continue;
+            }
+        };
+        let content = if strip {
+            strip_frontmatter(&content)
+        } else {
+            &content
+        };
+        match separator {
+            Some(separator) => print!("{}{}", content, separator),
+            None => {
+                if !first {
+                    println!();
+                }
+                println!("==> {} <==", file.display());
+                print!("{}", content);
+                if !content.is_empty() && !content.ends_with('\n') {
+                    println!();
+                }
+            }
+        }
+        first = false;
+    }
+}
+
+/// Outputs each path followed by a tab and its summary excerpt.
+fn output_summaries(matches: &[FileMatch], max_chars: usize, separator: Option<&str>) {
+    for file_match in matches {
+        let line = format!(
+            "{}\t{}",
+            file_match.path.display(),
+            match_summary(file_match, max_chars).unwrap_or_default()
+        );
+        print_record(&line, separator);
+    }
+}
+
+/// Outputs each path followed by a tab and its comma-separated dates.
+/// Files without dates are still listed, with nothing after the tab.
+fn output_dates(matches: &[FileMatch], aliases: &FieldAliases, separator: Option<&str>) {
+    for file_match in matches {
+        let line = format!(
+            "{}\t{}",
+            file_match.path.display(),
+            match_dates(file_match, aliases).join(",")
+        );
+        print_record(&line, separator);
+    }
+}
+
+/// Template used by --long
+const LONG_FORMAT: &str = "{path}\t{title}\t{tags}";
+
+/// A piece of an output template
+#[derive(Debug, PartialEq)]
+enum TemplateSegment {
+    Literal(String),
+    Path,
+    Title,
+    Tags,
+    Author,
+    Dates,
+    Description,
+}
+
+/// A parsed --format template.
+#[derive(Debug)]
+struct OutputTemplate {
+    segments: Vec<TemplateSegment>,
+}
+
+impl OutputTemplate {
+    /// Parses placeholders like `{path}` and the escapes `\t`, `\n` and `\\`.
+    /// Unknown placeholders are rejected so typos don't silently print nothing.
+    fn parse(template: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '\\' => match chars.next() {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some('\\') => literal.push('\\'),
+                    Some(other) => {
+                        literal.push('\\');
+                        literal.push(other);
+                    }
+                    None => literal.push('\\'),
+                },
+                '{' => {
+                    let name: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                    let segment = match name.as_str() {
+                        "path" => TemplateSegment::Path,
+                        "title" => TemplateSegment::Title,
+                        "tags" => TemplateSegment::Tags,
+                        "author" => TemplateSegment::Author,
+                        "dates" => TemplateSegment::Dates,
+                        "description" => TemplateSegment::Description,
+                        _ => {
+                            return Err(anyhow::anyhow!(
+                                "Unknown placeholder '{{{}}}' in --format template",
+                                name
+                            ))
+                        }
+                    };
+                    if !literal.is_empty() {
+                        segments.push(TemplateSegment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(segment);
+                }
+                _ => literal.push(ch),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(TemplateSegment::Literal(literal));
+        }
+
+        Ok(OutputTemplate { segments })
+    }
+
+    /// Renders one match. Fields missing from the file render as empty strings.
+    fn render(&self, file_match: &FileMatch, highlighter: &Highlighter) -> String {
+        let metadata = file_match.metadata.as_ref();
+        let mut out = String::new();
+
+        for segment in &self.segments {
+            match segment {
+                TemplateSegment::Literal(text) => out.push_str(text),
+                TemplateSegment::Path => out.push_str(&highlighter.path(&file_match.path)),
+                TemplateSegment::Title => {
+                    let title = match metadata {
+                        Some(metadata) => metadata.resolved_title(&file_match.path),
+                        None => file_stem_title(&file_match.path),
+                    };
+                    out.push_str(&highlighter.title(&title));
+                }
+                TemplateSegment::Tags => {
+                    let tags = file_match
+                        .metadata
+                        .as_ref()
+                        .map(Metadata::yaml_tags)
+                        .unwrap_or_default();
+                    let tags: Vec<String> = tags.iter().map(|tag| highlighter.tag(tag)).collect();
+                    out.push_str(&tags.join(","));
+                }
+                TemplateSegment::Author => {
+                    if let Some(metadata) = &file_match.metadata {
+                        out.push_str(&metadata.frontmatter_authors().join(", "));
+                    }
+                }
+                TemplateSegment::Dates => {
+                    out.push_str(&match_dates(file_match, &highlighter.aliases).join(","))
+                }
+                TemplateSegment::Description => {
+                    if let Some(description) = file_match
+                        .metadata
+                        .as_ref()
+                        .and_then(Metadata::first_paragraph)
+                    {
+                        out.push_str(&description);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Highlights the parts of paths, titles and tags matched by the filters.
+struct Highlighter {
+    enabled: bool,
+    title_regexes: Vec<Regex>,
+    tags_lower: Vec<String>,
+    name_regexes: Vec<Regex>,
+    path_regexes: Vec<Regex>,
+    name_full_path: bool,
+    aliases: FieldAliases,
+}
+
+impl Highlighter {
+    fn from_args(args: &Args) -> Result<Self> {
+        let mut title_regexes = args
+            .titles
+            .iter()
+            .map(|title| {
+                RegexBuilder::new(&regex::escape(title))
+                    .case_insensitive(true)
+                    .build()
+                    .with_context(|| format!("Failed to compile title pattern: {}", title))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Reuse the filter's compiled regexes so highlighting matches what was filtered
+        let filters = CompiledFilters::from_args(args)?;
+        title_regexes.extend(filters.title_regexes);
+
+        Ok(Highlighter {
+            enabled: use_color(args),
+            title_regexes,
+            tags_lower: args
+                .tags
+                .iter()
+                .map(|tag| tag.trim_start_matches('#').to_lowercase())
+                .collect(),
+            name_regexes: filters.name_patterns,
+            path_regexes: filters.path_patterns,
+            name_full_path: filters.name_full_path,
+            aliases: FieldAliases::from_args(args)?,
+        })
+    }
+
+    /// Colors every span of the title matched by a --title or --title-regex pattern.
+    fn title(&self, title: &str) -> String {
+        if !self.enabled {
+            return title.to_string();
+        }
+        paint_spans(title, regex_spans(title, &self.title_regexes, 0))
+    }
+
+    /// Colors the parts of a path matched by --name (file name only) or --path.
+    fn path(&self, path: &Path) -> String {
+        let display = path.to_string_lossy();
+        if !self.enabled {
+            return display.into_owned();
+        }
+
+        // Separators are normalized as in `matches_path`; this keeps byte offsets intact
+        let normalized = display.replace('\\', "/");
+        let mut spans = regex_spans(&normalized, &self.path_regexes, 0);
+        if self.name_full_path {
+            spans.extend(regex_spans(&normalized, &self.name_regexes, 0));
+        } else if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            let offset = display.len() - name.len();
+            spans.extend(regex_spans(name, &self.name_regexes, offset));
+        }
+        paint_spans(&display, spans)
+    }
+
+    /// Colors a tag that equals one of the --tag values.
+    fn tag(&self, tag: &str) -> String {
+        if self.enabled && self.tags_lower.contains(&tag.to_lowercase()) {
+            paint_match(tag)
+        } else {
+            tag.to_string()
+        }
+    }
+}
+
+/// Collects the non-empty spans matched by any of the regexes, shifted by `offset`.
+fn regex_spans(text: &str, regexes: &[Regex], offset: usize) -> Vec<(usize, usize)> {
+    regexes
+        .iter()
+        .flat_map(|regex| regex.find_iter(text))
+        .filter(|m| !m.is_empty())
+        .map(|m| (m.start() + offset, m.end() + offset))
+        .collect()
+}
+
+/// Paints the given byte spans of `text`, merging any overlaps.
+fn paint_spans(text: &str, mut spans: Vec<(usize, usize)>) -> String {
+    spans.sort();
+
+    let mut out = String::new();
+    let mut pos = 0;
+    for (start, end) in spans {
+        let start = start.max(pos);
+        if start >= end {
+            continue;
+        }
+        out.push_str(&text[pos..start]);
+        out.push_str(&paint_match(&text[start..end]));
+        pos = end;
+    }
+    out.push_str(&text[pos..]);
+    out
+}
+
+/// Wraps matched text in the highlight style (bold red, like grep).
+fn paint_match(text: &str) -> String {
+    Style::new().red().bold().style(text).to_string()
+}
+
+/// Decides whether to emit color codes. Machine-readable output is never colored.
+fn use_color(args: &Args) -> bool {
+    if args.json || args.toml || args.nul || args.separator.is_some() {
+        return false;
+    }
+    match args.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::io::stdout().is_terminal()
+                && std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        }
+    }
+}
+
+/// Outputs each match rendered through the template.
+fn output_formatted(
+    matches: &[FileMatch],
+    template: &OutputTemplate,
+    args: &Args,
+    separator: Option<&str>,
+) -> Result<()> {
+    let highlighter = Highlighter::from_args(args)?;
+    for file_match in matches {
+        print_record(&template.render(file_match, &highlighter), separator);
+    }
+    Ok(())
+}
+
+/// Prints one line of output, ended by the --separator (or `\0` with -0) instead
+/// of a newline when one is given.
+fn print_record(record: &str, separator: Option<&str>) {
+    match separator {
+        Some(separator) => print!("{}{}", record, separator),
+        None => println!("{}", record),
+    }
+}
+
+/// Outputs file paths to stdout, either newline-delimited or NUL-delimited.
+///
+/// Parts matched by --name or --path are highlighted when color is enabled.
+fn output_files(files: &[PathBuf], highlighter: &Highlighter, separator: Option<&str>) {
+    for file in files {
+        match separator {
+            Some(separator) => print!("{}{}", file.display(), separator),
+            None => println!("{}", highlighter.path(file)),
+        }
+    }
+}
+
+/// Returns the separator printed after each path: `\0` with `-0`, the parsed
+/// --separator, or `None` for the default newline.
+fn path_separator(args: &Args) -> Result<Option<String>> {
+    if args.nul {
+        return Ok(Some("\0".to_string()));
+    }
+    args.separator.as_deref().map(parse_separator).transpose()
+}
+
+/// Parses a --separator value, expanding the escapes `\t`, `\n`, `\0` and `\\`.
+fn parse_separator(spec: &str) -> Result<String> {
+    let mut separator = String::new();
+    let mut chars = spec.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            separator.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => separator.push('\t'),
+            Some('n') => separator.push('\n'),
+            Some('0') => separator.push('\0'),
+            Some('\\') => separator.push('\\'),
+            Some(other) => {
+                return Err(anyhow::anyhow!(
+                    "Invalid --separator '{}': unknown escape '\\{}'",
+                    spec,
+                    other
+                ))
+            }
+            None => {
+                return Err(anyhow::anyhow!(
+                    "Invalid --separator '{}': trailing backslash",
+                    spec
+                ))
+            }
+        }
+    }
+    if separator.is_empty() {
+        return Err(anyhow::anyhow!("Invalid --separator: it cannot be empty"));
+    }
+    Ok(separator)
+}
+
+/// Outputs each unreadable file as `path: error`, or only the paths when a
+/// --separator (or -0) makes the list machine-readable.
+fn output_file_errors(errors: &[FileError], separator: Option<&str>) {
+    for error in errors {
+        match separator {
+            Some(separator) => print!("{}{}", error.path.display(), separator),
+            None => println!("{}: {}", error.path.display(), error.message),
+        }
+    }
+}
+
+/// Outputs files under a `dir/:` header per parent directory, indented.
+///
+/// Directories appear in the order of their first file, so the sort order is kept.
+fn output_dir_groups(files: &[PathBuf], highlighter: &Highlighter) {
+    let mut groups: Vec<(&Path, Vec<&PathBuf>)> = Vec::new();
+    let mut positions: HashMap<&Path, usize> = HashMap::new();
+    for file in files {
+        let parent = file.parent().unwrap_or(Path::new(""));
+        let index = *positions.entry(parent).or_insert_with(|| {
+            groups.push((parent, Vec::new()));
+            groups.len() - 1
+        });
+        groups[index].1.push(file);
+    }
+
+    for (index, (dir, members)) in groups.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        let dir = if dir.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            dir.display().to_string()
+        };
+        let separator = if dir.ends_with(std::path::MAIN_SEPARATOR) {
+            ""
+        } else {
+            std::path::MAIN_SEPARATOR_STR
+        };
+        println!("{}{}:", dir, separator);
+        for file in members {
+            println!("  {}", highlighter.path(file));
+        }
+    }
+}
+
+/// Counts the distinct values of a field across the given files.
+///
+/// Files that can't be read are skipped (with a warning under `--verbose`).
+fn collect_field_values(
+    files: &[PathBuf],
+    field_name: &str,
+    read_options: &ReadOptions,
+    inline_line_start: bool,
+    verbose: bool,
+) -> BTreeMap<String, usize> {
+    let per_file: Vec<Vec<String>> = files
+        .par_iter()
+        .filter_map(
+            |path| match Metadata::from_file_with_options(path, read_options) {
+                Ok(metadata) => Some(metadata.field_values(field_name, inline_line_start)),
+                Err(e) => {
+                    report_read_error(path, &e, verbose);
+                    None
+                }
+            },
+        )
+        .collect();
+
+    let mut counts = BTreeMap::new();
+    for value in per_file.into_iter().flatten() {
+        *counts.entry(value).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Collects the distinct tags of the matches: YAML tags, --tag-field values and
+/// inline `#tags` in the scanned lines, sorted.
+fn collect_tags(matches: &[FileMatch], tag_fields: &[String]) -> BTreeSet<String> {
+    let mut tags = BTreeSet::new();
+    for metadata in matches.iter().filter_map(|m| m.metadata.as_ref()) {
+        tags.extend(metadata.all_tags(tag_fields));
+    }
+    tags
+}
+
+/// Outputs distinct field values sorted by value, each followed by a tab and its count.
+fn output_field_values(counts: &BTreeMap<String, usize>, separator: Option<&str>) {
+    for (value, count) in counts {
+        print_record(&format!("{}\t{}", value, count), separator);
+    }
+}
+
+/// Drops the `.` components of a path before glob matching, so a dot-glob
+/// like `**/.*.md` doesn't match the `./` of a relative search directory.
+fn without_cur_dir(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| *component != std::path::Component::CurDir)
+        .collect()
+}
+
+/// Enumerates all files matching the glob pattern in the specified directories.
+///
+/// Respects .gitignore, .ignore files, and skips hidden files (unless `--hidden`) and
+/// common build/cache directories.
+/// With `--cache`, the walk is skipped when the cached list is still current.
+/// With `--stdin`, the paths listed on stdin are used instead of walking.
+fn enumerate_files(args: &Args) -> Result<Vec<PathBuf>> {
+    if args.stdin {
+        let mut input = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)
+            .context("Failed to read paths from stdin")?;
+        return Ok(parse_path_list(&input, args.nul)
+            .into_iter()
+            .filter(|path| {
+                let is_file = path.is_file();
+                if !is_file && args.verbose {
+                    eprintln!("Warning: Not a file: {}", path.display());
+                }
+                is_file
+            })
+            .collect());
+    }
+
+    // Build glob matcher from --glob and --ext (case-insensitive with -i)
+    let glob_matcher = build_glob_set(args)?;
+
+    let dirs = existing_search_dirs(args)?;
+    let mut files = match &args.cache {
+        Some(cache_path) => cached_walk(cache_path, &dirs, args, &glob_matcher)?,
+        None => walk_search_dirs(&dirs, args, &glob_matcher, None),
+    };
+
+    // Intersect with the files git reports as changed
+    if let Some(rev) = &args.since_commit {
+        let mut changed = HashSet::new();
+        for dir in &dirs {
+            changed.extend(git_changed_files(dir, rev)?);
+        }
+        files.retain(|path| {
+            path.canonicalize()
+                .is_ok_and(|canonical| changed.contains(&canonical))
+        });
+    }
+
+    Ok(files)
+}
+
+/// Walks the search directories and returns the files matching the glob.
+///
+/// When `stamps` is given, the mtime of every visited directory (and of any
+/// ignore file inside it) is recorded so a cached list can be validated later.
+fn walk_search_dirs(
+    dirs: &[PathBuf],
+    args: &Args,
+    glob_matcher: &GlobSet,
+    mut stamps: Option<&mut Vec<CacheStamp>>,
+) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+
+    for dir in dirs {
+        // Use ignore crate's WalkBuilder for better performance and .gitignore support
+        let mut walker = WalkBuilder::new(dir);
+
+        // Respect .gitignore files
+        walker.git_ignore(true);
+
+        // Respect global gitignore
+        walker.git_global(true);
+
+        // Respect .ignore files
+        walker.ignore(true);
+
+        // Filter hidden files/directories (like .git, .obsidian) unless --hidden
+        walker.hidden(!args.hidden);
+
+        // Don't follow symbolic links to avoid infinite loops
+        walker.follow_links(false);
+
+        // Set max depth if specified
+        if let Some(depth) = args.depth {
+            walker.max_depth(Some(depth));
+        }
+
+        // Stay on the search directory's device (Unix and Windows)
+        walker.same_file_system(args.one_file_system);
+
+        for entry in walker.build().filter_map(|e| e.ok()) {
+            let path = entry.path();
+
+            // Additional filtering for specific directories we always want to skip
+            // (in case they're not hidden or not in .gitignore)
+            let should_skip = path.components().any(|component| {
+                if let std::path::Component::Normal(os_str) = component {
+                    if let Some(dir_name) = os_str.to_str() {
+                        EXCLUDED_DIRS.contains(&dir_name)
+                    } else {
+                        false
+                    }
+                } else {
+                    false
+                }
+            });
+
+            if should_skip {
+                continue;
+            }
+
+            // Check if it's a file and matches the glob pattern
+            // Match against the full path to support patterns like "**/*.md"
+            // as well as simple filename patterns like "*.md"
+            // Files above --depth-min are skipped (directories are still descended)
+            let too_shallow = args.depth_min.is_some_and(|min| entry.depth() < min);
+
+            if path.is_file() && !too_shallow && glob_matcher.is_match(without_cur_dir(path)) {
+                files.push(path.to_path_buf());
+            } else if let Some(stamps) = stamps.as_deref_mut() {
+                if path.is_dir() {
+                    stamps.extend(CacheStamp::of(path));
+                    for ignore_file in IGNORE_FILES {
+                        stamps.extend(CacheStamp::of(&path.join(ignore_file)));
+                    }
+                }
+            }
+        }
+    }
+
+    files
+}
+
+/// Splits a path list read from stdin.
+///
+/// Paths are NUL-delimited with `-0` or whenever the input contains a NUL byte
+/// (as from `find -print0`), and newline-delimited otherwise. Empty entries,
+/// such as a trailing delimiter, are skipped.
+fn parse_path_list(input: &str, nul: bool) -> Vec<PathBuf> {
+    if nul || input.contains('\0') {
+        input
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    } else {
+        input
+            .lines()
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect()
+    }
+}
+
+/// Ignore files whose edits change what the walk returns without touching the directory mtime.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+/// A path and its modification time at the time the cache was written.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CacheStamp {
+    path: PathBuf,
+    modified: SystemTime,
+}
+
+impl CacheStamp {
+    /// Stats `path`, returning `None` if it doesn't exist or has no mtime.
+    fn of(path: &Path) -> Option<Self> {
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        Some(CacheStamp {
+            path: path.to_path_buf(),
+            modified,
+        })
+    }
+
+    /// Whether the path still exists with the same mtime.
+    fn is_current(&self) -> bool {
+        CacheStamp::of(&self.path).is_some_and(|now| now.modified == self.modified)
+    }
+}
+
+/// The on-disk file list written by `--cache`.
+#[derive(Debug, Serialize, Deserialize)]
+struct FileListCache {
+    /// The enumeration options the list was built with
+    key: String,
+    /// Directories and ignore files seen during the walk
+    dirs: Vec<CacheStamp>,
+    /// Files matching the glob
+    files: Vec<CacheStamp>,
+}
+
+/// Describes every option that affects which files the walk returns.
+fn cache_key(dirs: &[PathBuf], args: &Args) -> String {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    format!(
+        "{:?}",
+        (
+            cwd,
+            dirs,
+            &args.glob,
+            &args.extensions,
+            args.depth,
+            args.depth_min,
+            args.ignore_case,
+            args.one_file_system,
+            args.hidden,
+        )
+    )
+}
+
+/// Returns the file list from the cache if no directory changed since it was
+/// written, and otherwise walks the tree and rewrites the cache.
+///
+/// Files are restatted on reuse; deleted ones are dropped from the list.
+fn cached_walk(
+    cache_path: &Path,
+    dirs: &[PathBuf],
+    args: &Args,
+    glob_matcher: &GlobSet,
+) -> Result<Vec<PathBuf>> {
+    let key = cache_key(dirs, args);
+
+    let cached = fs::read_to_string(cache_path).ok().and_then(|text| {
+        match serde_json::from_str::<FileListCache>(&text) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                if args.verbose {
+                    eprintln!(
+                        "Warning: Ignoring unreadable cache {}: {}",
+                        cache_path.display(),
+                        e
+                    );
+                }
+                None
+            }
+        }
+    });
+
+    if let Some(cache) = cached {
+        if cache.key == key && cache.dirs.iter().all(CacheStamp::is_current) {
+            let files: Vec<CacheStamp> = cache
+                .files
+                .iter()
+                .filter_map(|entry| CacheStamp::of(&entry.path))
+                .collect();
+            if files != cache.files {
+                write_cache(
+                    cache_path,
+                    &FileListCache {
+                        files: files.clone(),
+                        ..cache
+                    },
+                )?;
+            }
+            return Ok(files.into_iter().map(|entry| entry.path).collect());
+        }
+    }
+
+    // Create the cache before walking so a cache inside a search directory
+    // doesn't invalidate itself by changing that directory's mtime
+    if !cache_path.exists() {
+        write_cache(
+            cache_path,
+            &FileListCache {
+                key: String::new(),
+                dirs: Vec::new(),
+                files: Vec::new(),
+            },
+        )?;
+    }
+
+    let mut dir_stamps = Vec::new();
+    let files = walk_search_dirs(dirs, args, glob_matcher, Some(&mut dir_stamps));
+    write_cache(
+        cache_path,
+        &FileListCache {
+            key,
+            dirs: dir_stamps,
+            files: files
+                .iter()
+                .filter_map(|path| CacheStamp::of(path))
+                .collect(),
+        },
+    )?;
+    Ok(files)
+}
+
+/// Serializes the cache to `path`.
+fn write_cache(path: &Path, cache: &FileListCache) -> Result<()> {
+    let text = serde_json::to_string(cache)?;
+    fs::write(path, text).with_context(|| format!("Failed to write cache: {}", path.display()))
+}
+
+/// Lets the user pick a match with fzf (skipped for a single match) and opens it
+/// in the editor. Cancelling the selection opens nothing.
+fn open_selected_file(files: &[PathBuf]) -> Result<()> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let chosen = if let [file] = files {
+        file.clone()
+    } else {
+        let mut fzf = Command::new("fzf")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .context("--open needs fzf on PATH to choose between several matches")?;
+        if let Some(mut stdin) = fzf.stdin.take() {
+            for file in files {
+                writeln!(stdin, "{}", file.display()).context("Failed to write to fzf")?;
+            }
+        }
+        let output = fzf.wait_with_output().context("Failed to run fzf")?;
+        let selection = String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string();
+        if !output.status.success() || selection.is_empty() {
+            return Ok(());
+        }
+        PathBuf::from(selection)
+    };
+
+    let editor = editor_command(std::env::var("VISUAL").ok(), std::env::var("EDITOR").ok());
+    let status = Command::new(&editor[0])
+        .args(&editor[1..])
+        .arg(&chosen)
+        .status()
+        .with_context(|| format!("Failed to start editor: {}", editor.join(" ")))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!("Editor exited with {}", status));
+    }
+    Ok(())
+}
+
+/// Splits the editor setting into program and arguments (e.g. `code -w`):
+/// `$VISUAL`, else `$EDITOR`, else `vi`.
+fn editor_command(visual: Option<String>, editor: Option<String>) -> Vec<String> {
+    [visual, editor]
+        .into_iter()
+        .flatten()
+        .map(|command| {
+            command
+                .split_whitespace()
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        })
+        .find(|words| !words.is_empty())
+        .unwrap_or_else(|| vec!["vi".to_string()])
+}
+
+/// Runs git in `dir` and returns its stdout, failing with git's own message.
+fn run_git(dir: &Path, git_args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(git_args)
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "git {} failed in {}: {}",
+            git_args.join(" "),
+            dir.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Returns the canonical paths of files changed since `rev` in the repository
+/// containing `dir`. Files deleted since then are skipped.
+fn git_changed_files(dir: &Path, rev: &str) -> Result<HashSet<PathBuf>> {
+    let toplevel = run_git(dir, &["rev-parse", "--show-toplevel"]).with_context(|| {
+        format!(
+            "--since-commit requires a git repository: {}",
+            dir.display()
+        )
+    })?;
+    let toplevel = PathBuf::from(toplevel.trim());
+
+    // The revision must never be read as a git option, e.g. --output=FILE
+    if rev.starts_with('-') {
+        return Err(anyhow::anyhow!(
+            "Invalid --since-commit revision '{}': it must not start with '-'",
+            rev
+        ));
+    }
+    // -z keeps paths with spaces or non-ASCII characters unquoted
+    let changed = run_git(
+        dir,
+        &["diff", "--name-only", "-z", "--end-of-options", rev, "--"],
+    )?;
+    Ok(changed
+        .split('\0')
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| toplevel.join(line).canonicalize().ok())
+        .collect())
+}
+
+/// Checks if a path component contains glob metacharacters.
+fn has_glob_meta(component: &str) -> bool {
+    component.contains(['*', '?', '[', '{'])
+}
+
+/// Expands directory arguments containing glob metacharacters (e.g. `vault/*/notes`)
+/// into the matching directories, one path component at a time. Arguments without
+/// metacharacters pass through unchanged.
+fn expand_dir_args(dirs: &[PathBuf], verbose: bool) -> Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
+
+    for dir in dirs {
+        let is_pattern = dir
+            .components()
+            .any(|c| c.as_os_str().to_str().is_some_and(has_glob_meta));
+        if !is_pattern {
+            expanded.push(dir.clone());
+            continue;
+        }
+
+        let mut candidates = vec![PathBuf::new()];
+        for component in dir.components() {
+            let name = component.as_os_str();
+            let pattern = name.to_str().filter(|s| has_glob_meta(s));
+            let Some(pattern) = pattern else {
+                for candidate in &mut candidates {
+                    candidate.push(name);
+                }
+                continue;
+            };
+
+            let matcher = GlobBuilder::new(pattern)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid directory pattern: {}", dir.display()))?
+                .compile_matcher();
+
+            let mut next = Vec::new();
+            for candidate in &candidates {
+                let base = if candidate.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    candidate.as_path()
+                };
+                let Ok(entries) = fs::read_dir(base) else {
+                    continue;
+                };
+                for entry in entries.filter_map(|e| e.ok()) {
+                    if entry.path().is_dir() && matcher.is_match(entry.file_name()) {
+                        next.push(candidate.join(entry.file_name()));
+                    }
+                }
+            }
+            candidates = next;
+        }
+
+        // Literal components after a wildcard may not exist under every match
+        candidates.retain(|candidate| candidate.is_dir());
+        if candidates.is_empty() && verbose {
+            eprintln!(
+                "Warning: Directory pattern matched no directories: {}",
+                dir.display()
+            );
+        }
+        candidates.sort();
+        expanded.extend(candidates);
+    }
+
+    Ok(expanded)
+}
+
+/// Expands the directory arguments and drops the ones that don't exist.
+///
+/// It is an error when none of them exist (unless --allow-missing-dirs),
+/// so scripts can tell a typo'd path from an empty result. When only some
+/// are missing, each one is reported with a warning.
+fn existing_search_dirs(args: &Args) -> Result<Vec<PathBuf>> {
+    let (existing, missing): (Vec<PathBuf>, Vec<PathBuf>) =
+        expand_dir_args(&args.dirs, args.verbose)?
+            .into_iter()
+            .partition(|dir| dir.exists());
+
+    if existing.is_empty() && !args.allow_missing_dirs {
+        let names: Vec<String> = args.dirs.iter().map(|d| d.display().to_string()).collect();
+        return Err(anyhow::anyhow!("No such directory: {}", names.join(", ")));
+    }
+
+    if !args.allow_missing_dirs || args.verbose {
+        for dir in &missing {
+            eprintln!("Warning: Directory not found: {}", dir.display());
+        }
+    }
+
+    Ok(existing)
+}
+
+/// Glob used when neither --glob nor --ext is given
+const DEFAULT_GLOB: &str = "**/*.md";
+
+/// Builds the file matcher as the union of --glob and one `**/*.{ext}`
+/// pattern per --ext, falling back to `**/*.md` when neither is given.
+fn build_glob_set(args: &Args) -> Result<GlobSet> {
+    let mut patterns: Vec<String> = args.glob.iter().cloned().collect();
+    for ext in &args.extensions {
+        let ext = ext.trim().trim_start_matches('.');
+        if ext.is_empty() || ext.contains(['/', '\\']) {
+            return Err(anyhow::anyhow!(
+                "Invalid extension '{}': expected a bare extension like 'md'",
+                ext
+            ));
+        }
+        patterns.push(format!("**/*.{}", ext));
+    }
+    if patterns.is_empty() {
+        patterns.push(DEFAULT_GLOB.to_string());
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in &patterns {
+        let glob = GlobBuilder::new(pattern)
+            .case_insensitive(args.ignore_case)
+            .build()
+            .with_context(|| format!("Invalid glob pattern: {}", pattern))?;
+        builder.add(glob);
+    }
+    builder.build().context("Failed to build glob set")
+}
+
+/// Sorts the result list according to `--sort` (or `--sort-field`) and `--reverse`.
+///
+/// For `--sort mtime` and `--sort ctime`, each file is statted exactly once. `--sort date` uses the
+/// latest date of a file, falling back to its mtime with --date-fallback-mtime. Files whose
+/// time can't be read, and files without a usable `--sort-field`
+/// value, always sort last, regardless of `--reverse`.
+fn sort_matches(matches: &mut Vec<FileMatch>, args: &Args) {
+    if let Some(field) = &args.sort_field {
+        let kind = args.sort_field_kind;
+        sort_keyed(
+            matches,
+            args.reverse,
+            args.sort_order,
+            |file_match| {
+                let metadata = file_match.metadata.as_ref()?;
+                let values = metadata.field_values(field, args.inline_metadata_prefix);
+                FieldSortKey::parse(values.first()?, kind)
+            },
+            FieldSortKey::compare,
+        );
+        return;
+    }
+
+    match args.sort {
+        SortKey::Path => {
+            matches.sort_by(|a, b| compare_paths(&a.path, &b.path, args.sort_order));
+            if args.reverse {
+                matches.reverse();
+            }
+        }
+        SortKey::Mtime | SortKey::Ctime => sort_keyed(
+            matches,
+            args.reverse,
+            args.sort_order,
+            |file_match| {
+                let (time, what) = match args.sort {
+                    SortKey::Ctime => (
+                        fs::metadata(&file_match.path).and_then(|m| change_time(&m)),
+                        "change time",
+                    ),
+                    _ => (
+                        fs::metadata(&file_match.path).and_then(|m| m.modified()),
+                        "modification time",
+                    ),
+                };
+                match time {
+                    Ok(time) => Some(time),
+                    Err(e) => {
+                        let summary = format!("Failed to read {} of", what);
+                        report_read_warning(&file_match.path, &summary, &e, args.verbose);
+                        None
+                    }
+                }
+            },
+            SystemTime::cmp,
+        ),
+        SortKey::Title => sort_keyed(
+            matches,
+            args.reverse,
+            args.sort_order,
+            |file_match| {
+                let metadata = file_match.metadata.as_ref()?;
+                Some(metadata.effective_title()?.to_lowercase())
+            },
+            String::cmp,
+        ),
+        SortKey::Date => {
+            // Alias errors were already reported while compiling the filters
+            let aliases = FieldAliases::from_args(args).unwrap_or_default();
+            sort_keyed(
+                matches,
+                args.reverse,
+                args.sort_order,
+                |file_match| {
+                    let latest = file_match
+                        .metadata
+                        .as_ref()
+                        .and_then(|metadata| metadata.extract_dates(&aliases).into_iter().max());
+                    if latest.is_some() || !args.date_fallback_mtime {
+                        return latest;
+                    }
+                    match fs::metadata(&file_match.path).and_then(|m| m.modified()) {
+                        Ok(mtime) => Some(DateTime::<Utc>::from(mtime).date_naive()),
+                        Err(e) => {
+                            let summary = "Failed to read modification time of";
+                            report_read_warning(&file_match.path, summary, &e, args.verbose);
+                            None
+                        }
+                    }
+                },
+                NaiveDate::cmp,
+            )
+        }
+    }
+}
+
+/// Returns the inode change time (ctime) of a file. Platforms without one
+/// fall back to the creation time.
+#[cfg(unix)]
+fn change_time(metadata: &fs::Metadata) -> std::io::Result<SystemTime> {
+    use std::os::unix::fs::MetadataExt;
+
+    let since_epoch = Duration::new(
+        u64::try_from(metadata.ctime()).map_err(std::io::Error::other)?,
+        u32::try_from(metadata.ctime_nsec()).map_err(std::io::Error::other)?,
+    );
+    Ok(SystemTime::UNIX_EPOCH + since_epoch)
+}
+
+#[cfg(not(unix))]
+fn change_time(metadata: &fs::Metadata) -> std::io::Result<SystemTime> {
+    metadata.created()
+}
+
+/// Sorts by a key computed once per match, breaking ties by path.
+/// Matches without a key go last (by path), regardless of `reverse`.
+fn sort_keyed<K>(
+    matches: &mut Vec<FileMatch>,
+    reverse: bool,
+    order: PathOrder,
+    key: impl Fn(&FileMatch) -> Option<K>,
+    compare: impl Fn(&K, &K) -> std::cmp::Ordering,
+) {
+    let mut keyed: Vec<(Option<K>, FileMatch)> = matches
+        .drain(..)
+        .map(|file_match| (key(&file_match), file_match))
+        .collect();
+
+    keyed.sort_by(|(a_key, a), (b_key, b)| match (a_key, b_key) {
+        (Some(a_key), Some(b_key)) => {
+            let ordering =
+                compare(a_key, b_key).then_with(|| compare_paths(&a.path, &b.path, order));
+            if reverse {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        }
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => compare_paths(&a.path, &b.path, order),
+    });
+
+    matches.extend(keyed.into_iter().map(|(_, file_match)| file_match));
+}
+
+/// Compares two paths according to --sort-order. Paths that only differ in case
+/// (or leading zeros) fall back to byte order, so the order is always total.
+fn compare_paths(a: &Path, b: &Path, order: PathOrder) -> std::cmp::Ordering {
+    let folded = |path: &Path| path.to_string_lossy().to_lowercase();
+    let ordering = match order {
+        PathOrder::Byte => std::cmp::Ordering::Equal,
+        PathOrder::Ci => folded(a).cmp(&folded(b)),
+        PathOrder::Natural => natural_cmp(&folded(a), &folded(b)),
+    };
+    ordering.then_with(|| a.cmp(b))
+}
+
+/// Compares strings with runs of ASCII digits ordered by their numeric value.
+fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(a_first), Some(b_first)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+        if a_first.is_ascii_digit() && b_first.is_ascii_digit() {
+            let a_end = a.find(|c: char| !c.is_ascii_digit()).unwrap_or(a.len());
+            let b_end = b.find(|c: char| !c.is_ascii_digit()).unwrap_or(b.len());
+            let a_digits = a[..a_end].trim_start_matches('0');
+            let b_digits = b[..b_end].trim_start_matches('0');
+            let ordering = a_digits
+                .len()
+                .cmp(&b_digits.len())
+                .then_with(|| a_digits.cmp(b_digits));
+            if ordering.is_ne() {
+                return ordering;
+            }
+            (a, b) = (&a[a_end..], &b[b_end..]);
+        } else if a_first != b_first {
+            return a_first.cmp(&b_first);
+        } else {
+            (a, b) = (&a[a_first.len_utf8()..], &b[b_first.len_utf8()..]);
+        }
+    }
+}
+
+/// A --sort-field value parsed according to --sort-field-kind
+#[derive(Debug, Clone, PartialEq)]
+enum FieldSortKey {
+    Text(String),
+    Number(f64),
+    Version(semver::Version),
+}
+
+impl FieldSortKey {
+    /// Parses a field value, returning `None` if it isn't valid for `kind`.
+    fn parse(value: &str, kind: SortFieldKind) -> Option<Self> {
+        let value = value.trim();
+        match kind {
+            SortFieldKind::Lexical => Some(FieldSortKey::Text(value.to_string())),
+            SortFieldKind::Numeric => value.parse().ok().map(FieldSortKey::Number),
+            SortFieldKind::Semver => parse_version(value).map(FieldSortKey::Version),
+        }
+    }
+
+    fn compare(&self, other: &Self) -> std::cmp::Ordering {
+        match (self, other) {
+            (FieldSortKey::Text(a), FieldSortKey::Text(b)) => a.cmp(b),
+            (FieldSortKey::Number(a), FieldSortKey::Number(b)) => a.total_cmp(b),
+            (FieldSortKey::Version(a), FieldSortKey::Version(b)) => a.cmp(b),
+            // All keys in one sort share a kind
+            _ => std::cmp::Ordering::Equal,
+        }
+    }
+}
+
+/// Parses a semantic version, allowing a leading `v` and missing minor/patch
+/// components (YAML turns an unquoted `1.10` into the number 1.1, so short
+/// versions are common).
+fn parse_version(value: &str) -> Option<semver::Version> {
+    let value = value.strip_prefix(['v', 'V']).unwrap_or(value);
+    if let Ok(version) = semver::Version::parse(value) {
+        return Some(version);
+    }
+
+    let components = value.split('.').count();
+    if components < 3 && value.split('.').all(|c| c.parse::<u64>().is_ok()) {
+        let padded = format!("{}{}", value, ".0".repeat(3 - components));
+        return semver::Version::parse(&padded).ok();
+    }
+    None
+}
+
+/// A file that failed to read or parse during the scan
+#[derive(Debug)]
+struct FileError {
+    path: PathBuf,
+    message: String,
+}
+
+/// Finds the matching files, recording those that can't be read in `errors`.
+fn find_matching_files(args: &Args, errors: &Mutex<Vec<FileError>>) -> Result<Vec<FileMatch>> {
+    // Validate read options (e.g. the encoding name) before touching the filesystem
+    let read_options = ReadOptions::from_args(args)?;
+
+    // Enumerate all markdown files
+    let mut files = enumerate_files(args)?;
+    let enumerated = files.len();
+
+    // With --any, --name and --path join the content filters in one OR instead of pre-filtering
+    let content_filters = has_metadata_filters(args) || !args.greps.is_empty();
+    let names_in_content = args.any && !args.list_only && content_filters;
+    // With --name-or-content, --name alone moves into the content check
+    let name_or_content = args.name_or_content && !args.list_only && content_filters;
+
+    // Whether anything beyond --grep needs the parsed metadata (--files-with-errors reads every file)
+    let needs_metadata = output_needs_metadata(args)
+        || has_metadata_filters(args)
+        || names_in_content
+        || name_or_content
+        || args.files_with_errors;
+
+    // If no filters and the output doesn't need metadata, return all files sorted
+    if !needs_metadata
+        && !args.list_only
+        && args.names.is_empty()
+        && args.paths.is_empty()
+        && args.stems.is_empty()
+        && args.greps.is_empty()
+    {
+        let mut matches: Vec<FileMatch> = files
+            .into_iter()
+            .map(|path| FileMatch {
+                path,
+                metadata: None,
+            })
+            .collect();
+        sort_matches(&mut matches, args);
+        return Ok(matches);
+    }
+
+    // Compile filters once before parallel processing
+    let filters = CompiledFilters::from_args(args)?;
+
+    // With --any and nothing else to join, --name, --path and --stem are ORed among themselves
+    let name_filter_kinds = [
+        &filters.name_patterns,
+        &filters.path_patterns,
+        &filters.stem_patterns,
+    ]
+    .iter()
+    .filter(|patterns| !patterns.is_empty())
+    .count();
+    let names_any = args.any && !names_in_content && name_filter_kinds > 1;
+    if names_any {
+        files.retain(|path| {
+            filters.matches_name(path)
+                || filters
+                    .path_patterns
+                    .iter()
+                    .any(|regex| matches_path(path, regex))
+                || filters
+                    .stem_patterns
+                    .iter()
+                    .any(|regex| matches_stem(path, regex))
+        });
+    }
+    let skip_name_prefilters = names_in_content || names_any;
+
+    // Early filtering: check filename patterns first (no I/O required)
+    if !skip_name_prefilters && !name_or_content && !filters.name_patterns.is_empty() {
+        files.retain(|path| filters.matches_name(path));
+    }
+    if !skip_name_prefilters && !filters.path_patterns.is_empty() {
+        files.retain(|path| {
+            filters
+                .path_patterns
+                .iter()
+                .any(|regex| matches_path(path, regex))
+        });
+    }
+    if !skip_name_prefilters && !filters.stem_patterns.is_empty() {
+        files.retain(|path| {
+            filters
+                .stem_patterns
+                .iter()
+                .any(|regex| matches_stem(path, regex))
+        });
+    }
+
+    // --list-only stops before any content is read, and so do --name/--path on their own
+    let content_filtered = needs_metadata || !filters.grep_patterns.is_empty();
+    if args.list_only || !content_filtered {
+        if args.list_only && args.verbose {
+            eprintln!(
+                "{} files enumerated, {} after --name/--path filters",
+                enumerated,
+                files.len()
+            );
         }
+        let mut matches: Vec<FileMatch> = files
+            .into_iter()
+            .map(|path| FileMatch {
+                path,
+                metadata: None,
+            })
+            .collect();
+        sort_matches(&mut matches, args);
+        return Ok(matches);
     }
 
-    // Check field filters
-    if !filters.field_patterns.is_empty() {
-        let field_matched = filters
-            .field_patterns
-            .iter()
-            .any(|(field, pattern)| metadata.has_field(field, pattern));
-        if !field_matched {
-            return false;
+    // Filter files in parallel (only for content-based filters)
+    let verbose = args.verbose;
+    let progress = ScanProgress::new(args, files.len());
+
+    // With --grep as the only content filter, stream each file without parsing it
+    if !needs_metadata && !filters.grep_patterns.is_empty() {
+        let mut matches: Vec<FileMatch> = files
+            .into_par_iter()
+            .filter_map(|path| {
+                progress.tick();
+                match grep_file(&path, &filters.grep_patterns, &read_options) {
+                    Ok(true) => Some(FileMatch {
+                        path,
+                        metadata: None,
+                    }),
+                    Ok(false) => None,
+                    Err(e) => {
+                        report_read_error(&path, &e, verbose);
+                        record_error(errors, path, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        progress.finish();
+        sort_matches(&mut matches, args);
+        return Ok(matches);
+    }
+
+    let mut matches: Vec<FileMatch> = files
+        .into_par_iter()
+        .filter_map(|path| {
+            progress.tick();
+            // Extract metadata (only if we need to check content-based filters)
+            match Metadata::from_file_with_options(&path, &read_options) {
+                Ok(metadata) => {
+                    // Check content-based filters, keeping metadata for the output stage
+                    if include_file(Some(&path), &metadata, &filters, || {
+                        matches_grep(&path, &metadata, &filters, &read_options, errors, verbose)
+                    }) {
+                        Some(FileMatch {
+                            path,
+                            metadata: Some(metadata),
+                        })
+                    } else {
+                        None
+                    }
+                }
+                Err(e) => {
+                    report_read_error(&path, &e, verbose);
+                    record_error(errors, path, e);
+                    None
+                }
+            }
+        })
+        .collect();
+    progress.finish();
+
+    // Sort results (alphabetically by default, like ls)
+    sort_matches(&mut matches, args);
+
+    Ok(matches)
+}
+
+/// Records a file the scan couldn't read; called from the worker threads.
+fn record_error(errors: &Mutex<Vec<FileError>>, path: PathBuf, error: anyhow::Error) {
+    let message = format!("{:#}", error);
+    if let Ok(mut errors) = errors.lock() {
+        errors.push(FileError { path, message });
+    }
+}
+
+/// Set by --errors-json; read wherever a file warning is reported, including
+/// the frontmatter parsers that don't see `Args`.
+static ERRORS_JSON: AtomicBool = AtomicBool::new(false);
+
+/// One --errors-json line
+#[derive(Serialize)]
+struct ErrorReport<'a> {
+    path: String,
+    kind: &'a str,
+    message: String,
+}
+
+/// Reports a problem with one file on stderr: `Warning: {summary} {path}: {error}`,
+/// or a JSON line with --errors-json.
+fn report_file_warning(path: &Path, kind: &str, summary: &str, error: &dyn std::fmt::Display) {
+    if ERRORS_JSON.load(Ordering::Relaxed) {
+        let report = ErrorReport {
+            path: path.display().to_string(),
+            kind,
+            message: error.to_string(),
+        };
+        if let Ok(line) = serde_json::to_string(&report) {
+            eprintln!("{}", line);
         }
+    } else {
+        eprintln!("Warning: {} {}: {}", summary, path.display(), error);
     }
+}
 
-    // Check date filters (if any date filter is specified)
-    if (filters.date_after.is_some() || filters.date_before.is_some())
-        && !metadata.matches_date_filters(filters.date_after, filters.date_before)
-    {
-        return false;
+/// Reports a file that couldn't be read; shown with --verbose, and always with --errors-json.
+fn report_read_error(path: &Path, error: &anyhow::Error, verbose: bool) {
+    report_read_warning(path, "Failed to read", error, verbose);
+}
+
+/// Reports a failed read of part of a file, such as its stat, gated like `report_read_error`.
+fn report_read_warning(path: &Path, summary: &str, error: &dyn std::fmt::Display, verbose: bool) {
+    if verbose || ERRORS_JSON.load(Ordering::Relaxed) {
+        report_file_warning(path, "read", summary, error);
     }
+}
 
-    true
+/// The `--progress` bar for the parallel scan, cleared before any result is printed.
+struct ScanProgress {
+    bar: Option<ProgressBar>,
+
+    /// Files processed so far, bumped from the worker threads
+    processed: AtomicUsize,
 }
 
-/// Outputs file paths to stdout, either newline-delimited or NUL-delimited.
-fn output_files(files: &[PathBuf], use_nul: bool) {
-    for file in files {
-        if use_nul {
-            print!("{}\0", file.display());
-        } else {
-            println!("{}", file.display());
+impl ScanProgress {
+    /// Sets up progress for `total` files. The bar stays off unless --progress is
+    /// given and both stdout and stderr are terminals; it only ever writes to stderr.
+    fn new(args: &Args, total: usize) -> Self {
+        let bar = (args.progress
+            && std::io::stdout().is_terminal()
+            && std::io::stderr().is_terminal())
+        .then(|| {
+            let bar =
+                ProgressBar::with_draw_target(Some(total as u64), ProgressDrawTarget::stderr());
+            bar.set_style(
+                ProgressStyle::with_template("{bar:40} {pos}/{len} files ({elapsed})")
+                    .expect("progress template is valid"),
+            );
+            bar
+        });
+        ScanProgress {
+            bar,
+            processed: AtomicUsize::new(0),
+        }
+    }
+
+    /// Counts one more processed file.
+    fn tick(&self) {
+        let processed = self.processed.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(bar) = &self.bar {
+            bar.set_position(processed as u64);
+        }
+    }
+
+    /// Clears the bar so the results print on a clean line.
+    fn finish(&self) {
+        if let Some(bar) = &self.bar {
+            bar.finish_and_clear();
         }
     }
 }
 
-/// Enumerates all files matching the glob pattern in the specified directories.
+/// Whether any filter needs the file's parsed metadata (everything but --name, --path and --grep).
+fn has_metadata_filters(args: &Args) -> bool {
+    !(args.tags.is_empty()
+        && args.tags_file.is_none()
+        && args.tag_specs.is_empty()
+        && args.min_tags.is_none()
+        && args.max_tags.is_none()
+        && args.titles.is_empty()
+        && args.aliases.is_empty()
+        && args.title_regexes.is_empty()
+        && args.headings.is_empty()
+        && args.links_to.is_empty()
+        && !args.has_tasks
+        && !args.has_open_tasks
+        && args.min_links.is_none()
+        && args.max_links.is_none()
+        && args.descriptions.is_empty()
+        && args.authors.is_empty()
+        && args.fields.is_empty()
+        && args.field_all.is_empty()
+        && args.field_eq.is_empty()
+        && args.has_fields.is_empty()
+        && !args.require_frontmatter
+        && !args.no_frontmatter
+        && args.missing_fields.is_empty()
+        && !args.no_drafts
+        && args.date_after.is_none()
+        && args.date_before.is_none())
+}
+
+/// Applies the --grep patterns to a file whose metadata is already loaded.
 ///
-/// Respects .gitignore, .ignore files, and skips hidden files and common build/cache directories.
-fn enumerate_files(args: &Args) -> Result<Vec<PathBuf>> {
-    let mut files = Vec::new();
+/// In --full-text mode the whole file is in memory already; otherwise it is streamed.
+fn matches_grep(
+    path: &Path,
+    metadata: &Metadata,
+    filters: &CompiledFilters,
+    read_options: &ReadOptions,
+    errors: &Mutex<Vec<FileError>>,
+    verbose: bool,
+) -> bool {
+    if filters.grep_patterns.is_empty() {
+        return true;
+    }
+    if read_options.full_text {
+        return metadata.raw_content.lines().any(|line| {
+            filters
+                .grep_patterns
+                .iter()
+                .any(|regex| regex.is_match(line))
+        });
+    }
 
-    // Build glob matcher from the glob pattern
-    let glob_matcher = Glob::new(&args.glob)
-        .with_context(|| format!("Invalid glob pattern: {}", args.glob))?
-        .compile_matcher();
+    grep_file(path, &filters.grep_patterns, read_options).unwrap_or_else(|e| {
+        report_read_error(path, &e, verbose);
+        record_error(errors, path.to_path_buf(), e);
+        false
+    })
+}
 
-    for dir in &args.dirs {
-        // Use ignore crate's WalkBuilder for better performance and .gitignore support
-        let mut walker = WalkBuilder::new(dir);
+/// Scans a file line by line for any of the patterns, stopping at the first match
+/// so large files are never loaded whole.
+fn grep_file(path: &Path, regexes: &[Regex], options: &ReadOptions) -> Result<bool> {
+    Ok(!grep_lines(path, regexes, options, true)?.is_empty())
+}
 
-        // Respect .gitignore files
-        walker.git_ignore(true);
+/// Returns the lines matching any of the patterns as (1-based line number, text).
+/// With `first_only`, reading stops at the first hit.
+fn grep_lines(
+    path: &Path,
+    regexes: &[Regex],
+    options: &ReadOptions,
+    first_only: bool,
+) -> Result<Vec<(usize, String)>> {
+    let mut hits = Vec::new();
+    // Records a hit and tells whether to stop reading
+    let mut check = |index: usize, line: &str| {
+        let is_match = regexes.iter().any(|regex| regex.is_match(line));
+        if is_match {
+            hits.push((index + 1, line.to_string()));
+        }
+        is_match && first_only
+    };
 
-        // Respect global gitignore
-        walker.git_global(true);
+    if let Some(map) = map_file(path, options) {
+        for (index, line) in mapped_text(&map, path)?.lines().enumerate() {
+            if check(index, line) {
+                break;
+            }
+        }
+    } else if let Some(encoding) = options.encoding {
+        // Legacy encodings can't be decoded line by line, so decode the whole file
+        let bytes =
+            fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+        let (decoded, _, had_errors) = encoding.decode(&bytes);
+        if had_errors {
+            return Err(anyhow::anyhow!(
+                "File is not valid {}: {}",
+                encoding.name(),
+                path.display()
+            ));
+        }
+        for (index, line) in decoded.lines().enumerate() {
+            if check(index, line) {
+                break;
+            }
+        }
+    } else {
+        let file = fs::File::open(path)
+            .with_context(|| format!("Failed to open file: {}", path.display()))?;
+        for (index, line) in buffered_reader(file, options).lines().enumerate() {
+            let line =
+                line.with_context(|| format!("Failed to read line from file: {}", path.display()))?;
+            if check(index, &line) {
+                break;
+            }
+        }
+    }
+    Ok(hits)
+}
 
-        // Respect .ignore files
-        walker.ignore(true);
+/// Exits with 0 when files matched, 1 when nothing matched, and 2 on errors.
+/// `--no-status` keeps the exit status at 0 for empty results.
+fn main() -> ExitCode {
+    let mut args = match parse_args_with_config(std::env::args_os().collect()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    if let Some(Commands::Tags { dirs }) = &mut args.command {
+        args.dirs.append(dirs);
+    }
+    if let Some(path) = &args.dirs_from {
+        match read_dirs_file(path, args.verbose) {
+            Ok(dirs) if dirs.is_empty() && args.dirs.is_empty() => {
+                eprintln!(
+                    "Error: No existing directories listed in {}",
+                    path.display()
+                );
+                return ExitCode::from(EXIT_ERROR);
+            }
+            Ok(dirs) => args.dirs.extend(dirs),
+            Err(e) => {
+                eprintln!("Error: {:?}", e);
+                return ExitCode::from(EXIT_ERROR);
+            }
+        }
+    }
+    // --newest/--oldest are shorthands for a date sort with a limit
+    if let Some(count) = args.newest.or(args.oldest) {
+        args.sort = SortKey::Date;
+        args.reverse = args.newest.is_some();
+        args.limit = Some(count);
+    }
+    if args.dirs.is_empty() {
+        args.dirs = default_dirs(std::env::var_os(DIRS_ENV_VAR));
+    }
+    if let Some(threads) = args.threads {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads);
+        if let Err(e) = pool.build_global() {
+            eprintln!("Error: Failed to start {} threads: {}", threads, e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    }
+    let found = match if args.watch { watch(&args) } else { run(&args) } {
+        Ok(found) => found,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
 
-        // Filter hidden files/directories (like .git, .obsidian)
-        walker.hidden(true);
+    if !found && !args.no_status {
+        ExitCode::from(EXIT_NO_MATCH)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
 
-        // Don't follow symbolic links to avoid infinite loops
-        walker.follow_links(false);
+/// Environment variable with search roots used when no directory is given
+const DIRS_ENV_VAR: &str = "FMD_DIRS";
 
-        // Set max depth if specified
-        if let Some(depth) = args.depth {
-            walker.max_depth(Some(depth));
-        }
+/// Returns the search roots when no positional directory is given: the
+/// entries of `FMD_DIRS` if set and non-empty, else `.`. Entries are
+/// colon-separated (semicolon on Windows), like `PATH`.
+fn default_dirs(env_value: Option<OsString>) -> Vec<PathBuf> {
+    let dirs: Vec<PathBuf> = env_value
+        .map(|value| {
+            std::env::split_paths(&value)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
 
-        for entry in walker.build().filter_map(|e| e.ok()) {
-            let path = entry.path();
+    if dirs.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        dirs
+    }
+}
 
-            // Additional filtering for specific directories we always want to skip
-            // (in case they're not hidden or not in .gitignore)
-            let should_skip = path.components().any(|component| {
-                if let std::path::Component::Normal(os_str) = component {
-                    if let Some(dir_name) = os_str.to_str() {
-                        EXCLUDED_DIRS.contains(&dir_name)
-                    } else {
-                        false
-                    }
-                } else {
-                    false
-                }
-            });
+/// How long --watch waits for changes to settle before searching again
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
 
-            if should_skip {
+/// Runs the search, then again after every change to a file matching the glob
+/// under the search dirs, until interrupted. Each new result set follows a
+/// cleared screen on a terminal, or a blank line otherwise. Only errors in the
+/// first search are fatal.
+fn watch(args: &Args) -> Result<bool> {
+    let glob_matcher = build_glob_set(args)?;
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(sender).context("Failed to start the file watcher")?;
+    // Watch the dirs the search scans: globs expanded, missing dirs skipped
+    for dir in &existing_search_dirs(args)? {
+        watcher
+            .watch(dir, RecursiveMode::Recursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+    }
+
+    run(args)?;
+    let relevant = |event: &notify::Event| {
+        !matches!(event.kind, EventKind::Access(_))
+            && event
+                .paths
+                .iter()
+                .any(|path| glob_matcher.is_match(without_cur_dir(path)))
+    };
+    for event in &receiver {
+        match event {
+            Ok(event) if relevant(&event) => {}
+            Ok(_) => continue,
+            Err(e) => {
+                if args.verbose {
+                    eprintln!("Warning: File watcher error: {}", e);
+                }
                 continue;
             }
+        }
+        // Let a burst of changes (e.g. an editor saving) settle first
+        while receiver.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
 
-            // Check if it's a file and matches the glob pattern
-            // Match against the full path to support patterns like "**/*.md"
-            // as well as simple filename patterns like "*.md"
-            if path.is_file() && glob_matcher.is_match(path) {
-                files.push(path.to_path_buf());
-            }
+        if std::io::stdout().is_terminal() {
+            print!("\x1b[2J\x1b[H");
+        } else {
+            println!();
+        }
+        if let Err(e) = run(args) {
+            eprintln!("Error: {:?}", e);
+        }
+    }
+    Ok(true)
+}
+
+/// Runs the search and prints the results. Returns whether anything was found.
+fn run(args: &Args) -> Result<bool> {
+    ERRORS_JSON.store(args.errors_json, Ordering::Relaxed);
+    let separator = path_separator(args)?;
+    if args.open && !std::io::stdout().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "--open needs a terminal: stdout is not a TTY"
+        ));
+    }
+    if (args.min_links.is_some() || args.max_links.is_some()) && !args.full_text {
+        eprintln!(
+            "Warning: --min-links and --max-links only count links in the scanned lines; add --full-text to count the whole file"
+        );
+    }
+    let errors = Mutex::new(Vec::new());
+    let mut matches = find_matching_files(args, &errors)?;
+    if let Some(limit) = args.limit {
+        matches.truncate(limit);
+    }
+    let matching_files: Vec<PathBuf> = matches.iter().map(|m| m.path.clone()).collect();
+
+    if let Some(Commands::Tags { .. }) = args.command {
+        let tags = collect_tags(&matches, &args.tag_fields);
+        for tag in &tags {
+            print_record(tag, separator.as_deref());
         }
+        return Ok(!tags.is_empty());
     }
 
-    Ok(files)
+    if args.files_with_errors {
+        let mut errors = errors.into_inner().unwrap_or_default();
+        errors.sort_by(|a, b| a.path.cmp(&b.path));
+        output_file_errors(&errors, separator.as_deref());
+        return Ok(!errors.is_empty());
+    }
+
+    if args.open {
+        if matching_files.is_empty() {
+            return Ok(false);
+        }
+        open_selected_file(&matching_files)?;
+        return Ok(true);
+    }
+
+    if args.list_only {
+        if args.json {
+            output_json(&matches, args)?;
+        } else {
+            output_files(
+                &matching_files,
+                &Highlighter::from_args(args)?,
+                separator.as_deref(),
+            );
+        }
+        return Ok(!matches.is_empty());
+    }
+
+    if let Some(field_name) = &args.field_values {
+        let read_options = ReadOptions::from_args(args)?;
+        let counts = collect_field_values(
+            &matching_files,
+            field_name,
+            &read_options,
+            args.inline_metadata_prefix,
+            args.verbose,
+        );
+        output_field_values(&counts, separator.as_deref());
+        return Ok(!counts.is_empty());
+    }
+
+    if let Some(group_by) = args.group_by {
+        let tag_options = CompiledFilters::from_args(args)?.tag_options;
+        let groups = group_matches(
+            &matches,
+            group_by,
+            &tag_options,
+            args.inline_metadata_prefix,
+        );
+        output_groups(&groups, args.json)?;
+    } else if args.json {
+        output_json(&matches, args)?;
+    } else if args.toml {
+        output_toml(&matches, args)?;
+    } else if let Some(template) = &args.format {
+        output_formatted(
+            &matches,
+            &OutputTemplate::parse(template)?,
+            args,
+            separator.as_deref(),
+        )?;
+    } else if args.long {
+        output_formatted(
+            &matches,
+            &OutputTemplate::parse(LONG_FORMAT)?,
+            args,
+            separator.as_deref(),
+        )?;
+    } else if args.print_frontmatter {
+        output_frontmatter(&matches, args.print_frontmatter_format)?;
+    } else if args.cat {
+        let read_options = ReadOptions {
+            full_text: true,
+            ..ReadOptions::from_args(args)?
+        };
+        output_contents(
+            &matching_files,
+            &read_options,
+            args.strip_frontmatter,
+            separator.as_deref(),
+            args.verbose,
+        );
+    } else if args.grep_lines {
+        let filters = CompiledFilters::from_args(args)?;
+        let read_options = ReadOptions::from_args(args)?;
+        output_grep_lines(
+            &matching_files,
+            &filters.grep_patterns,
+            &read_options,
+            separator.as_deref(),
+            args.verbose,
+        );
+    } else if args.show_match {
+        output_match_lines(
+            &matches,
+            &CompiledFilters::from_args(args)?,
+            args.after_context,
+        );
+    } else if args.summary {
+        output_summaries(&matches, args.summary_length, separator.as_deref());
+    } else if args.print_dates {
+        output_dates(
+            &matches,
+            &FieldAliases::from_args(args)?,
+            separator.as_deref(),
+        );
+    } else if args.group_by_dir {
+        output_dir_groups(&matching_files, &Highlighter::from_args(args)?);
+    } else {
+        output_files(
+            &matching_files,
+            &Highlighter::from_args(args)?,
+            separator.as_deref(),
+        );
+    }
+    Ok(!matches.is_empty())
 }
 
-/// Core logic for finding matching markdown files based on filters
-fn find_matching_files(args: &Args) -> Result<Vec<PathBuf>> {
-    // Enumerate all markdown files
-    let mut files = enumerate_files(args)?;
+/// Environment variable naming the global config file (empty disables it)
+const CONFIG_ENV_VAR: &str = "FMD_CONFIG";
 
-    // If no filters, return all files sorted
-    if args.tags.is_empty()
-        && args.titles.is_empty()
-        && args.authors.is_empty()
-        && args.names.is_empty()
-        && args.fields.is_empty()
-        && args.date_after.is_none()
-        && args.date_before.is_none()
-    {
-        files.sort();
-        return Ok(files);
+/// Per-directory config file, found in the first search dir or its ancestors
+const DIR_CONFIG_FILE: &str = ".fmdrc";
+
+/// A config file setting: a flag, or the value(s) of an option
+#[derive(Debug, Clone, PartialEq)]
+enum ConfigValue {
+    Flag(bool),
+    Values(Vec<String>),
+}
+
+/// Parses the command line with defaults from config files merged in.
+///
+/// Precedence: command line > nearest `.fmdrc` > global `fmd.toml` > built-in
+/// defaults. Config keys are long option names (`sort = "mtime"`,
+/// `field-alias = ["date=published"]`); a key given on the command line is
+/// ignored in config files entirely, so list options are replaced, not extended.
+fn parse_args_with_config(cli: Vec<OsString>) -> Result<Args> {
+    let command = Args::command();
+    let Ok(mut matches) = command.clone().try_get_matches_from(&cli) else {
+        // Let clap report the usage error (or print --help) and exit
+        return Ok(Args::parse_from(cli));
+    };
+
+    // A saved query goes right after the program name, so the command line overrides it
+    let mut cli = cli;
+    if let Some(path) = matches.get_one::<PathBuf>("query_file") {
+        let tokens = read_query_file(path)?;
+        let after_program = cli.len().min(1);
+        cli.splice(after_program..after_program, tokens);
+        matches = match command.clone().try_get_matches_from(&cli) {
+            Ok(matches) => matches,
+            Err(_) => return Ok(Args::parse_from(cli)),
+        };
     }
 
-    // Compile filters once before parallel processing
-    let filters = CompiledFilters::from_args(args)?;
+    // The search roots may also follow a subcommand (`fmd tags vault`)
+    let subcommand = matches.subcommand().map(|(_, sub)| sub);
+    let mut dirs: Vec<PathBuf> = [Some(&matches), subcommand]
+        .into_iter()
+        .flatten()
+        .filter_map(|matches| matches.get_many::<PathBuf>("dirs"))
+        .flat_map(|dirs| dirs.cloned())
+        .collect();
+    if let Some(path) = matches.get_one::<PathBuf>("dirs_from") {
+        // An unreadable list is reported once the arguments are parsed
+        dirs.extend(read_dirs_file(path, false).unwrap_or_default());
+    }
+    if dirs.is_empty() {
+        dirs = default_dirs(std::env::var_os(DIRS_ENV_VAR));
+    }
+    // The .fmdrc is looked up from the first directory the search will scan
+    let first_dir = expand_dir_args(&dirs, false)
+        .ok()
+        .and_then(|dirs| dirs.into_iter().find(|dir| dir.exists()));
 
-    // Early filtering: check filename patterns first (no I/O required)
-    if !filters.name_patterns.is_empty() {
-        files.retain(|path| {
-            filters
-                .name_patterns
-                .iter()
-                .any(|regex| matches_filename(path, regex))
-        });
+    // Later files override earlier ones key by key
+    let mut settings = BTreeMap::new();
+    let config_files = [
+        global_config_path(),
+        first_dir.and_then(|dir| nearest_dir_config(&dir)),
+    ];
+    for path in config_files.into_iter().flatten() {
+        settings.extend(read_config(&path, &command)?);
     }
 
-    // Filter files in parallel (only for content-based filters)
-    let verbose = args.verbose;
-    let head_lines = args.head_lines;
-    let full_text = args.full_text;
-    let mut matching_files: Vec<PathBuf> = files
-        .par_iter()
-        .filter_map(|path| {
-            // Extract metadata (only if we need to check content-based filters)
-            match Metadata::from_file(path, head_lines, full_text, verbose) {
-                Ok(metadata) => {
-                    // Check content-based filters
-                    if should_include_file_by_content(&metadata, &filters) {
-                        Some(path.clone())
-                    } else {
-                        None
-                    }
+    let mut argv = vec![cli.first().cloned().unwrap_or_else(|| "fmd".into())];
+    for (key, value) in settings {
+        let given_on_cli = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(key.as_str()))
+            .is_some_and(|arg| {
+                matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+            });
+        if given_on_cli {
+            continue;
+        }
+        match value {
+            ConfigValue::Flag(true) => argv.push(format!("--{}", key).into()),
+            ConfigValue::Flag(false) => {}
+            ConfigValue::Values(values) => {
+                argv.extend(values.iter().map(|v| format!("--{}={}", key, v).into()))
+            }
+        }
+    }
+    argv.extend(cli.into_iter().skip(1));
+
+    Ok(Args::parse_from(argv))
+}
+
+/// Reads the tokens of a --query-file: blank lines and `#` comment lines are
+/// skipped, and each line is split at whitespace outside double quotes.
+fn read_query_file(path: &Path) -> Result<Vec<OsString>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read query file: {}", path.display()))?;
+
+    let mut tokens = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut token = String::new();
+        let mut in_quotes = false;
+        let mut has_token = false;
+        for c in line.chars() {
+            match c {
+                '"' => {
+                    in_quotes = !in_quotes;
+                    has_token = true;
                 }
-                Err(e) => {
-                    if verbose {
-                        eprintln!("Warning: Failed to read {}: {}", path.display(), e);
+                c if c.is_whitespace() && !in_quotes => {
+                    if has_token {
+                        tokens.push(std::mem::take(&mut token).into());
+                        has_token = false;
                     }
-                    None
+                }
+                c => {
+                    token.push(c);
+                    has_token = true;
                 }
             }
-        })
-        .collect();
+        }
+        if in_quotes {
+            return Err(anyhow::anyhow!(
+                "Unclosed quote in query file {}: {}",
+                path.display(),
+                line
+            ));
+        }
+        if has_token {
+            tokens.push(token.into());
+        }
+    }
+    Ok(tokens)
+}
+
+/// Returns the global config file: `$FMD_CONFIG` if set, else `fmd/fmd.toml`
+/// under `$XDG_CONFIG_HOME`, `~/.config` or `%APPDATA%` when it exists.
+fn global_config_path() -> Option<PathBuf> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV_VAR) {
+        return (!path.is_empty()).then(|| PathBuf::from(path));
+    }
 
-    // Sort results alphabetically (like ls)
-    matching_files.sort();
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))?;
+    let path = base.join("fmd").join("fmd.toml");
+    path.is_file().then_some(path)
+}
 
-    Ok(matching_files)
+/// Finds the `.fmdrc` closest to `dir`, looking in `dir` and then its ancestors.
+fn nearest_dir_config(dir: &Path) -> Option<PathBuf> {
+    let dir = dir.canonicalize().ok()?;
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(DIR_CONFIG_FILE))
+        .find(|path| path.is_file())
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
-    let matching_files = find_matching_files(&args)?;
-    output_files(&matching_files, args.nul);
-    Ok(())
+/// Reads a TOML config file, checking each key against the options of `command`.
+fn read_config(path: &Path, command: &clap::Command) -> Result<BTreeMap<String, ConfigValue>> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let table: toml::Table = text
+        .parse()
+        .with_context(|| format!("Invalid TOML in config file: {}", path.display()))?;
+
+    let mut settings = BTreeMap::new();
+    for (key, value) in table {
+        let invalid = |reason: &str| {
+            anyhow::anyhow!(
+                "Invalid setting '{}' in {}: {}",
+                key,
+                path.display(),
+                reason
+            )
+        };
+
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_long() == Some(key.as_str()))
+            .ok_or_else(|| invalid("not an fmd option"))?;
+        let takes_value = arg.get_action().takes_values();
+
+        let setting = match value {
+            toml::Value::Boolean(flag) if !takes_value => ConfigValue::Flag(flag),
+            toml::Value::Array(items) if takes_value => ConfigValue::Values(
+                items
+                    .iter()
+                    .map(|item| toml_scalar(item).ok_or_else(|| invalid("expected scalar values")))
+                    .collect::<Result<_>>()?,
+            ),
+            value if takes_value => ConfigValue::Values(vec![
+                toml_scalar(&value).ok_or_else(|| invalid("expected a value"))?
+            ]),
+            _ => return Err(invalid("expected true or false")),
+        };
+        settings.insert(key, setting);
+    }
+    Ok(settings)
+}
+
+/// Renders a TOML string, number or boolean as an option value.
+fn toml_scalar(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(s) => Some(s.clone()),
+        toml::Value::Integer(n) => Some(n.to_string()),
+        toml::Value::Float(n) => Some(n.to_string()),
+        toml::Value::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,4 @@
+use super::default_args;
 use crate::*;
 use std::fs;
 use std::io::Write;
@@ -24,8 +25,21 @@ fn test_output_files_with_multiple_files() {
 
     // Test both newline and NUL delimiters
     // These just ensure the function runs without panicking
-    output_files(&files, false);
-    output_files(&files, true);
+    let highlighter = Highlighter::from_args(&default_args()).unwrap();
+    output_files(&files, &highlighter, None);
+    output_files(&files, &highlighter, Some("\0"));
+}
+
+#[test]
+fn test_parse_separator_escapes() {
+    assert_eq!(parse_separator("\\t").unwrap(), "\t");
+    assert_eq!(parse_separator("\\0").unwrap(), "\0");
+    assert_eq!(parse_separator(", ").unwrap(), ", ");
+    assert_eq!(parse_separator("\\\\|\\n").unwrap(), "\\|\n");
+
+    assert!(parse_separator("\\x").is_err());
+    assert!(parse_separator("end\\").is_err());
+    assert!(parse_separator("").is_err());
 }
 
 #[test]
@@ -40,13 +54,14 @@ fn test_args_parsing_and_filter_creation() {
         nul: true,
         ignore_case: true,
         depth: Some(2),
-        glob: "**/*.md".to_string(),
+        glob: Some("**/*.md".to_string()),
         head_lines: 20,
         full_text: true,
         verbose: true,
         date_after: Some("2025-01-01".to_string()),
         date_before: Some("2025-12-31".to_string()),
         dirs: vec![PathBuf::from(".")],
+        ..default_args()
     };
 
     // Compile filters from args
@@ -77,8 +92,8 @@ fn test_metadata_extraction_from_file() {
 
     // Verify metadata was extracted correctly
     let tag_regex = regex::Regex::new(r"(?i)\b#?rust\b").unwrap();
-    assert!(metadata.has_tag("rust", &tag_regex));
-    assert!(metadata.has_author("test"));
+    assert!(metadata.has_tag("rust", &tag_regex, &TagOptions::default()));
+    assert!(metadata.has_author("test", false));
 }
 
 #[test]
@@ -93,13 +108,14 @@ fn test_compiled_filters_integration() {
         nul: false,
         ignore_case: false,
         depth: None,
-        glob: "**/*.md".to_string(),
+        glob: Some("**/*.md".to_string()),
         head_lines: 10,
         full_text: false,
         verbose: false,
         date_after: None,
         date_before: None,
         dirs: vec![PathBuf::from(".")],
+        ..default_args()
     };
 
     // Should compile filters successfully
@@ -126,3 +142,135 @@ fn test_read_file_content_with_metadata() {
     let content_full = read_file_content(&file_path, 3, true).unwrap();
     assert!(content_full.contains("Line 2"));
 }
+
+#[test]
+fn test_collect_field_values_counts() {
+    let temp_dir = TempDir::new().unwrap();
+    let files = vec![
+        create_test_file(&temp_dir, "a.md", "---\nstatus: active\n---\n"),
+        create_test_file(&temp_dir, "b.md", "---\nstatus: draft\n---\n"),
+        create_test_file(&temp_dir, "c.md", "---\nstatus: active\n---\n"),
+        create_test_file(&temp_dir, "d.md", "# No status"),
+    ];
+    let options = ReadOptions {
+        head_lines: 10,
+        ..Default::default()
+    };
+
+    let counts = collect_field_values(&files, "status", &options, false, false);
+    let counts: Vec<(&str, usize)> = counts.iter().map(|(v, c)| (v.as_str(), *c)).collect();
+    assert_eq!(counts, vec![("active", 2), ("draft", 1)]);
+}
+
+#[test]
+fn test_match_dates() {
+    let metadata = Metadata {
+        frontmatter: extract_frontmatter("---\ndate: 2025-01-15\n---\n", &PathBuf::from("a.md")),
+        raw_content: String::new().into(),
+    };
+    let dated = FileMatch {
+        path: PathBuf::from("a.md"),
+        metadata: Some(metadata),
+    };
+    assert_eq!(
+        match_dates(&dated, &FieldAliases::default()),
+        vec!["2025-01-15"]
+    );
+
+    let unread = FileMatch {
+        path: PathBuf::from("b.md"),
+        metadata: None,
+    };
+    assert!(match_dates(&unread, &FieldAliases::default()).is_empty());
+}
+
+#[test]
+fn test_default_dirs() {
+    assert_eq!(default_dirs(None), vec![PathBuf::from(".")]);
+    assert_eq!(default_dirs(Some("".into())), vec![PathBuf::from(".")]);
+
+    // Entries use the platform's path list separator (':' on Unix)
+    let dirs = vec![PathBuf::from("notes"), PathBuf::from("work")];
+    let value = std::env::join_paths(&dirs).unwrap();
+    assert_eq!(default_dirs(Some(value)), dirs);
+}
+
+#[test]
+fn test_read_config_settings() {
+    let temp_dir = TempDir::new().unwrap();
+    let command = Args::command();
+
+    let path = create_test_file(
+        &temp_dir,
+        "fmd.toml",
+        "sort = \"mtime\"\nhead = 20\nno-drafts = true\ntag = [\"work\", \"home\"]\n",
+    );
+    let settings = read_config(&path, &command).unwrap();
+    assert_eq!(
+        settings["sort"],
+        ConfigValue::Values(vec!["mtime".to_string()])
+    );
+    assert_eq!(
+        settings["head"],
+        ConfigValue::Values(vec!["20".to_string()])
+    );
+    assert_eq!(settings["no-drafts"], ConfigValue::Flag(true));
+    assert_eq!(
+        settings["tag"],
+        ConfigValue::Values(vec!["work".to_string(), "home".to_string()])
+    );
+
+    let unknown = create_test_file(&temp_dir, "unknown.toml", "colour = \"never\"\n");
+    let err = read_config(&unknown, &command).unwrap_err();
+    assert!(err.to_string().contains("not an fmd option"));
+
+    let flag_with_value = create_test_file(&temp_dir, "flag.toml", "no-drafts = \"yes\"\n");
+    assert!(read_config(&flag_with_value, &command).is_err());
+}
+
+#[test]
+fn test_read_query_file_tokens() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = create_test_file(
+        &temp_dir,
+        "review.fmdq",
+        "# Weekly review
+--tag rust
+
+--title \"release notes\"
+--no-drafts
+",
+    );
+    let tokens = read_query_file(&path).unwrap();
+    assert_eq!(
+        tokens,
+        ["--tag", "rust", "--title", "release notes", "--no-drafts"]
+            .map(OsString::from)
+            .to_vec()
+    );
+
+    let unclosed = create_test_file(&temp_dir, "bad.fmdq", "--title \"oops\n");
+    assert!(read_query_file(&unclosed).is_err());
+}
+
+#[test]
+fn test_editor_command() {
+    let some = |s: &str| Some(s.to_string());
+    assert_eq!(editor_command(some("code -w"), some("vim")), ["code", "-w"]);
+    assert_eq!(editor_command(None, some("nano")), ["nano"]);
+    // Blank settings are skipped
+    assert_eq!(editor_command(some("  "), None), ["vi"]);
+}
+
+#[test]
+fn test_scan_progress_counts_from_threads() {
+    use rayon::prelude::*;
+    use std::sync::atomic::Ordering;
+
+    // Without --progress there is no bar, but the counter still runs
+    let progress = ScanProgress::new(&default_args(), 100);
+    assert!(progress.bar.is_none());
+    (0..100).into_par_iter().for_each(|_| progress.tick());
+    progress.finish();
+    assert_eq!(progress.processed.load(Ordering::Relaxed), 100);
+}
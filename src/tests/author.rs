@@ -44,12 +44,12 @@ Content"#,
         let path = PathBuf::from("test.md");
         let metadata = Metadata {
             frontmatter: extract_frontmatter(content, &path),
-            raw_content: content.to_string(),
+            raw_content: content.to_string().into(),
         };
 
         for (pattern, should_match) in expectations {
             assert_eq!(
-                metadata.has_author(pattern),
+                metadata.has_author(pattern, false),
                 should_match,
                 "Pattern '{}' in content '{}'",
                 pattern,
@@ -92,12 +92,12 @@ More content"#,
         let path = PathBuf::from("test.md");
         let metadata = Metadata {
             frontmatter: extract_frontmatter(content, &path),
-            raw_content: content.to_string(),
+            raw_content: content.to_string().into(),
         };
 
         for (pattern, should_match) in expectations {
             assert_eq!(
-                metadata.has_author(pattern),
+                metadata.has_author(pattern, false),
                 should_match,
                 "Pattern '{}' in content",
                 pattern
@@ -151,12 +151,12 @@ author: "  Padded Name  "
         let path = PathBuf::from("test.md");
         let metadata = Metadata {
             frontmatter: extract_frontmatter(content, &path),
-            raw_content: content.to_string(),
+            raw_content: content.to_string().into(),
         };
 
         for (pattern, should_match) in expectations {
             assert_eq!(
-                metadata.has_author(pattern),
+                metadata.has_author(pattern, false),
                 should_match,
                 "Pattern '{}' failed",
                 pattern
@@ -164,3 +164,39 @@ author: "  Padded Name  "
         }
     }
 }
+
+#[test]
+fn has_author_inline_line_start_only() {
+    let content = "Intro\n    author: David Brown\nauthor: Erin Green\n";
+    let path = PathBuf::from("test.md");
+    let metadata = Metadata {
+        frontmatter: extract_frontmatter(content, &path),
+        raw_content: content.to_string().into(),
+    };
+
+    // Indented keys count by default but not with the line-start requirement
+    assert!(metadata.has_author("david", false));
+    assert!(!metadata.has_author("david", true));
+
+    // Column-0 keys match either way
+    assert!(metadata.has_author("erin", false));
+    assert!(metadata.has_author("erin", true));
+}
+
+#[test]
+fn has_author_matches_authors_list() {
+    let content = "---\nauthors: [Alice Smith, Bob Jones]\n---\n# Co-authored";
+    let path = PathBuf::from("test.md");
+    let metadata = Metadata {
+        frontmatter: extract_frontmatter(content, &path),
+        raw_content: content.to_string().into(),
+    };
+
+    assert!(metadata.has_author("alice", false));
+    assert!(metadata.has_author("bob jones", false));
+    assert!(!metadata.has_author("carol", false));
+    assert_eq!(
+        metadata.frontmatter_authors(),
+        vec!["Alice Smith", "Bob Jones"]
+    );
+}
@@ -1,4 +1,8 @@
-use crate::{extract_frontmatter, should_include_file_by_content, CompiledFilters, Metadata};
+use super::default_args;
+use crate::{
+    extract_frontmatter, should_include_file, should_include_file_by_content, Args,
+    CompiledFilters, InvertTarget, Metadata,
+};
 use chrono::NaiveDate;
 use regex::Regex;
 use std::path::PathBuf;
@@ -7,7 +11,7 @@ fn create_test_metadata(content: &str) -> Metadata {
     let path = PathBuf::from("test.md");
     Metadata {
         frontmatter: extract_frontmatter(content, &path),
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     }
 }
 
@@ -28,6 +32,7 @@ tags: [rust, programming]
                 field_patterns: vec![],
                 date_after: None,
                 date_before: None,
+                ..Default::default()
             },
             true,
         ),
@@ -43,6 +48,7 @@ tags: [python, java]
                 field_patterns: vec![],
                 date_after: None,
                 date_before: None,
+                ..Default::default()
             },
             false,
         ),
@@ -59,6 +65,7 @@ title: My Rust Guide
                 field_patterns: vec![],
                 date_after: None,
                 date_before: None,
+                ..Default::default()
             },
             true,
         ),
@@ -74,6 +81,7 @@ title: Python Tutorial
                 field_patterns: vec![],
                 date_after: None,
                 date_before: None,
+                ..Default::default()
             },
             false,
         ),
@@ -90,6 +98,7 @@ author: John Doe
                 field_patterns: vec![],
                 date_after: None,
                 date_before: None,
+                ..Default::default()
             },
             true,
         ),
@@ -105,6 +114,7 @@ author: Jane Smith
                 field_patterns: vec![],
                 date_after: None,
                 date_before: None,
+                ..Default::default()
             },
             false,
         ),
@@ -121,6 +131,7 @@ status: active
                 field_patterns: vec![("status".to_string(), "active".to_string())],
                 date_after: None,
                 date_before: None,
+                ..Default::default()
             },
             true,
         ),
@@ -136,6 +147,7 @@ status: draft
                 field_patterns: vec![("status".to_string(), "active".to_string())],
                 date_after: None,
                 date_before: None,
+                ..Default::default()
             },
             false,
         ),
@@ -170,6 +182,7 @@ tags: [rust]
         field_patterns: vec![],
         date_after: None,
         date_before: None,
+        ..Default::default()
     };
 
     // OR logic: should match if ANY tag matches
@@ -192,6 +205,7 @@ date: 2024-01-15
                 field_patterns: vec![],
                 date_after: Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
                 date_before: None,
+                ..Default::default()
             },
             true,
         ),
@@ -208,6 +222,7 @@ date: 2023-12-15
                 field_patterns: vec![],
                 date_after: Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
                 date_before: None,
+                ..Default::default()
             },
             false,
         ),
@@ -224,6 +239,7 @@ date: 2023-12-15
                 field_patterns: vec![],
                 date_after: None,
                 date_before: Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+                ..Default::default()
             },
             true,
         ),
@@ -240,6 +256,7 @@ date: 2024-02-15
                 field_patterns: vec![],
                 date_after: None,
                 date_before: Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+                ..Default::default()
             },
             false,
         ),
@@ -285,6 +302,7 @@ date: 2024-03-15
             field_patterns: vec![],
             date_after: Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
             date_before: Some(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()),
+            ..Default::default()
         };
 
         assert_eq!(
@@ -312,6 +330,7 @@ date: 2024-01-15
         field_patterns: vec![("status".to_string(), "active".to_string())],
         date_after: Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
         date_before: Some(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()),
+        ..Default::default()
     };
 
     // AND logic: all filters must match
@@ -336,6 +355,7 @@ date: 2024-01-15
         field_patterns: vec![("status".to_string(), "active".to_string())],
         date_after: Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
         date_before: Some(NaiveDate::from_ymd_opt(2024, 2, 1).unwrap()),
+        ..Default::default()
     };
 
     // Should fail because status doesn't match (draft != active)
@@ -356,6 +376,7 @@ title: Test
         field_patterns: vec![],
         date_after: Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
         date_before: None,
+        ..Default::default()
     };
 
     // Should not match if date filter is specified but no date in content
@@ -374,6 +395,7 @@ fn empty_frontmatter_no_filters() {
         field_patterns: vec![],
         date_after: None,
         date_before: None,
+        ..Default::default()
     };
 
     // No filters should include everything
@@ -392,6 +414,7 @@ fn empty_frontmatter_with_tag_filter() {
         field_patterns: vec![],
         date_after: None,
         date_before: None,
+        ..Default::default()
     };
 
     // Should not match without tags
@@ -410,6 +433,7 @@ fn inline_tag_match() {
         field_patterns: vec![],
         date_after: None,
         date_before: None,
+        ..Default::default()
     };
 
     assert!(should_include_file_by_content(&metadata, &filters));
@@ -427,7 +451,594 @@ fn markdown_heading_title_match() {
         field_patterns: vec![],
         date_after: None,
         date_before: None,
+        ..Default::default()
     };
 
     assert!(should_include_file_by_content(&metadata, &filters));
 }
+
+#[test]
+fn author_all_requires_every_author() {
+    let filters = CompiledFilters {
+        author_patterns: vec!["alice".to_string(), "bob".to_string()],
+        author_match_all: true,
+        ..Default::default()
+    };
+
+    let both = create_test_metadata("---\nauthor: Alice Smith and Bob Jones\n---\n# Paper");
+    let only_alice = create_test_metadata("---\nauthor: Alice Smith\n---\n# Paper");
+    let inline_both = create_test_metadata("# Paper\n\nauthor: Alice Smith\nauthor: Bob Jones");
+
+    assert!(should_include_file_by_content(&both, &filters));
+    assert!(!should_include_file_by_content(&only_alice, &filters));
+    assert!(should_include_file_by_content(&inline_both, &filters));
+}
+
+#[test]
+fn author_any_is_default() {
+    let filters = CompiledFilters {
+        author_patterns: vec!["alice".to_string(), "bob".to_string()],
+        ..Default::default()
+    };
+
+    let only_alice = create_test_metadata("---\nauthor: Alice Smith\n---\n# Paper");
+    assert!(should_include_file_by_content(&only_alice, &filters));
+}
+
+#[test]
+fn tag_spec_required_optional_and_excluded() {
+    let args = Args {
+        tag_specs: vec!["+rust -draft cli tui".to_string()],
+        ..default_args()
+    };
+    let filters = CompiledFilters::from_args(&args).unwrap();
+
+    let cases = vec![
+        // Required and one optional tag present
+        ("---\ntags: [rust, cli]\n---", true),
+        // Required tag missing
+        ("---\ntags: [cli, tui]\n---", false),
+        // Required present but no optional tag
+        ("---\ntags: [rust]\n---", false),
+        // Excluded tag present
+        ("---\ntags: [rust, tui, draft]\n---", false),
+        // Inline tags count too
+        ("# Note\n\n#rust #tui", true),
+    ];
+
+    for (content, expected) in cases {
+        let metadata = create_test_metadata(content);
+        assert_eq!(
+            should_include_file_by_content(&metadata, &filters),
+            expected,
+            "Unexpected result for: {}",
+            content
+        );
+    }
+}
+
+#[test]
+fn field_logic_all_and_any() {
+    let field_patterns = vec![
+        ("status".to_string(), "done".to_string()),
+        ("priority".to_string(), "high".to_string()),
+    ];
+    let all = CompiledFilters {
+        field_patterns: field_patterns.clone(),
+        ..Default::default()
+    };
+    let any = CompiledFilters {
+        field_patterns,
+        field_logic: crate::FieldLogic::Any,
+        ..Default::default()
+    };
+
+    let both = create_test_metadata("---\nstatus: done\npriority: high\n---");
+    let status_only = create_test_metadata("---\nstatus: done\npriority: low\n---");
+    let neither = create_test_metadata("---\nstatus: open\npriority: low\n---");
+
+    assert!(should_include_file_by_content(&both, &all));
+    assert!(!should_include_file_by_content(&status_only, &all));
+    assert!(!should_include_file_by_content(&neither, &all));
+
+    assert!(should_include_file_by_content(&both, &any));
+    assert!(should_include_file_by_content(&status_only, &any));
+    assert!(!should_include_file_by_content(&neither, &any));
+}
+
+#[test]
+fn field_logic_repeated_field_stays_or() {
+    let filters = CompiledFilters {
+        field_patterns: vec![
+            ("status".to_string(), "done".to_string()),
+            ("status".to_string(), "review".to_string()),
+            ("priority".to_string(), "high".to_string()),
+        ],
+        ..Default::default()
+    };
+
+    let review = create_test_metadata("---\nstatus: review\npriority: high\n---");
+    let open = create_test_metadata("---\nstatus: open\npriority: high\n---");
+
+    assert!(should_include_file_by_content(&review, &filters));
+    assert!(!should_include_file_by_content(&open, &filters));
+}
+
+#[test]
+fn author_filter_matches_alias_field() {
+    let args = Args {
+        authors: vec!["Jane".to_string()],
+        resolve_aliases: true,
+        ..default_args()
+    };
+    let filters = CompiledFilters::from_args(&args).unwrap();
+
+    let by = create_test_metadata("---\nby: Jane Doe\n---");
+    let authors = create_test_metadata("---\nauthors: [Bob, Jane Roe]\n---");
+    let other = create_test_metadata("---\nby: Bob\n---");
+
+    assert!(should_include_file_by_content(&by, &filters));
+    assert!(should_include_file_by_content(&authors, &filters));
+    assert!(!should_include_file_by_content(&other, &filters));
+
+    // Aliases are only consulted when enabled
+    let plain = CompiledFilters {
+        author_patterns: vec!["jane".to_string()],
+        ..Default::default()
+    };
+    assert!(!should_include_file_by_content(&by, &plain));
+}
+
+#[test]
+fn field_filter_matches_alias_field() {
+    let args = Args {
+        fields: vec!["status:done".to_string()],
+        field_aliases: vec!["status=state".to_string()],
+        ..default_args()
+    };
+    let filters = CompiledFilters::from_args(&args).unwrap();
+
+    assert!(should_include_file_by_content(
+        &create_test_metadata("---\nstate: done\n---"),
+        &filters
+    ));
+}
+
+#[test]
+fn field_presence_filters() {
+    let has_status = CompiledFilters {
+        required_fields: vec!["status".to_string()],
+        ..Default::default()
+    };
+    let missing_date = CompiledFilters {
+        missing_fields: vec!["date".to_string()],
+        ..Default::default()
+    };
+
+    let statused = create_test_metadata("---\nstatus: draft\ndate: 2025-01-15\n---");
+    let dateless = create_test_metadata("---\ntitle: Undated\n---");
+
+    assert!(should_include_file_by_content(&statused, &has_status));
+    assert!(!should_include_file_by_content(&dateless, &has_status));
+
+    assert!(!should_include_file_by_content(&statused, &missing_date));
+    assert!(should_include_file_by_content(&dateless, &missing_date));
+}
+
+#[test]
+fn missing_field_treats_empty_values_as_present() {
+    let missing_status = CompiledFilters {
+        missing_fields: vec!["status".to_string()],
+        ..Default::default()
+    };
+
+    let empty_yaml = create_test_metadata("---\nstatus:\n---\n");
+    let empty_inline = create_test_metadata("# Note\n\nstatus:\n");
+    let no_frontmatter = create_test_metadata("# Note\n\nJust text.\n");
+    let other_fields = create_test_metadata("---\ntitle: Note\n---\n");
+
+    assert!(!should_include_file_by_content(
+        &empty_yaml,
+        &missing_status
+    ));
+    assert!(!should_include_file_by_content(
+        &empty_inline,
+        &missing_status
+    ));
+    assert!(should_include_file_by_content(
+        &no_frontmatter,
+        &missing_status
+    ));
+    assert!(should_include_file_by_content(
+        &other_fields,
+        &missing_status
+    ));
+}
+
+#[test]
+fn description_filter_matches_first_paragraph() {
+    let filters = CompiledFilters {
+        description_patterns: vec!["release notes".to_string()],
+        ..Default::default()
+    };
+
+    let matching = create_test_metadata("# v1.2\n\nRelease notes for the spring update.");
+    let later = create_test_metadata("# v1.2\n\nIntro.\n\nRelease notes below.");
+
+    assert!(should_include_file_by_content(&matching, &filters));
+    assert!(!should_include_file_by_content(&later, &filters));
+}
+
+#[test]
+fn no_drafts_filter() {
+    let filters = CompiledFilters {
+        exclude_drafts: true,
+        ..Default::default()
+    };
+
+    let boolean = create_test_metadata("---\ntitle: WIP\ndraft: true\n---");
+    let string = create_test_metadata("---\ndraft: \"true\"\n---");
+    let published = create_test_metadata("---\ndraft: false\n---");
+    let undeclared = create_test_metadata("---\ntitle: Post\n---");
+    let inline = create_test_metadata("# Idea\ndraft: true");
+    let plain = create_test_metadata("# Idea\nJust notes");
+
+    assert!(boolean.is_draft(false));
+    assert!(string.is_draft(false));
+    assert!(inline.is_draft(false));
+    assert!(!published.is_draft(false));
+    assert!(!undeclared.is_draft(false));
+    assert!(!plain.is_draft(false));
+
+    assert!(!should_include_file_by_content(&boolean, &filters));
+    assert!(!should_include_file_by_content(&inline, &filters));
+    assert!(should_include_file_by_content(&undeclared, &filters));
+    // Drafts are only hidden when asked
+    assert!(should_include_file_by_content(
+        &boolean,
+        &CompiledFilters::default()
+    ));
+}
+
+#[test]
+fn filename_as_title_filter() {
+    let metadata = create_test_metadata("---\ntags: [notes]\n---\n# Agenda\n");
+    let path = PathBuf::from("journal/2024-06-15 Meeting with Bob.md");
+    let filters = CompiledFilters {
+        title_patterns: vec!["meeting".to_string()],
+        filename_as_title: true,
+        ..Default::default()
+    };
+
+    assert!(should_include_file(Some(&path), &metadata, &filters));
+    // Opt-in only, and the directory name doesn't count
+    let off = CompiledFilters {
+        filename_as_title: false,
+        ..filters
+    };
+    assert!(!should_include_file(Some(&path), &metadata, &off));
+    let in_dir = PathBuf::from("meeting/notes.md");
+    let on = CompiledFilters {
+        filename_as_title: true,
+        ..off
+    };
+    assert!(!should_include_file(Some(&in_dir), &metadata, &on));
+
+    let regex = CompiledFilters {
+        title_regexes: vec![Regex::new(r"^\d{4}-\d{2}-\d{2} ").unwrap()],
+        filename_as_title: true,
+        ..Default::default()
+    };
+    assert!(should_include_file(Some(&path), &metadata, &regex));
+}
+
+#[test]
+fn any_combines_filter_types_with_or() {
+    let tagged = create_test_metadata("---\ntags: [important]\n---\n# Plan\n");
+    let untagged = create_test_metadata("---\ntags: [misc]\n---\n# Plan\n");
+    let dated = PathBuf::from("notes/2024-review.md");
+    let other = PathBuf::from("notes/review.md");
+    let filters = CompiledFilters {
+        tag_patterns: vec![("important".to_string(), Regex::new("#important").unwrap())],
+        name_patterns: vec![Regex::new("2024").unwrap()],
+        match_any: true,
+        ..Default::default()
+    };
+
+    // Either the name or the tag is enough
+    assert!(should_include_file(Some(&dated), &untagged, &filters));
+    assert!(should_include_file(Some(&other), &tagged, &filters));
+    assert!(!should_include_file(Some(&other), &untagged, &filters));
+
+    // Exclusions still rule a file out
+    let excluding = CompiledFilters {
+        missing_fields: vec!["tags".to_string()],
+        ..filters
+    };
+    assert!(!should_include_file(Some(&dated), &untagged, &excluding));
+}
+
+#[test]
+fn invert_flips_each_filter_type() {
+    let rust_draft = create_test_metadata(
+        "---\ntitle: Draft notes\nauthor: Alice\nstatus: wip\ntags: [rust]\n---\n",
+    );
+    let rust_final = create_test_metadata(
+        "---\ntitle: Final notes\nauthor: Bob\nstatus: done\ntags: [rust]\n---\n",
+    );
+    let rust = ("rust".to_string(), Regex::new("#rust").unwrap());
+
+    let cases = vec![
+        (
+            InvertTarget::Title,
+            CompiledFilters {
+                title_patterns: vec!["draft".to_string()],
+                ..Default::default()
+            },
+        ),
+        (
+            InvertTarget::Author,
+            CompiledFilters {
+                author_patterns: vec!["alice".to_string()],
+                ..Default::default()
+            },
+        ),
+        (
+            InvertTarget::Field,
+            CompiledFilters {
+                field_patterns: vec![("status".to_string(), "wip".to_string())],
+                ..Default::default()
+            },
+        ),
+    ];
+    for (target, filters) in cases {
+        assert!(should_include_file_by_content(&rust_draft, &filters));
+        assert!(!should_include_file_by_content(&rust_final, &filters));
+
+        // Inverted, and still ANDed with the positive tag filter
+        let inverted = CompiledFilters {
+            tag_patterns: vec![rust.clone()],
+            inverts: vec![target],
+            ..filters
+        };
+        assert!(
+            !should_include_file_by_content(&rust_draft, &inverted),
+            "{:?}",
+            target
+        );
+        assert!(
+            should_include_file_by_content(&rust_final, &inverted),
+            "{:?}",
+            target
+        );
+    }
+
+    let untagged = create_test_metadata("---\ntitle: Other\n---\n");
+    let not_rust = CompiledFilters {
+        tag_patterns: vec![rust],
+        inverts: vec![InvertTarget::Tag],
+        ..Default::default()
+    };
+    assert!(!should_include_file_by_content(&rust_final, &not_rust));
+    assert!(should_include_file_by_content(&untagged, &not_rust));
+}
+
+#[test]
+fn include_dateless_lets_undated_files_pass_date_filters() {
+    let dated = create_test_metadata("---\ndate: 2023-06-01\n---\n");
+    let recent = create_test_metadata("---\ndate: 2024-06-01\n---\n");
+    let undated = create_test_metadata("---\ntitle: Someday\n---\n");
+    let filters = CompiledFilters {
+        date_before: Some(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap()),
+        ..Default::default()
+    };
+
+    assert!(should_include_file_by_content(&dated, &filters));
+    assert!(!should_include_file_by_content(&undated, &filters));
+
+    let filters = CompiledFilters {
+        include_dateless: true,
+        ..filters
+    };
+    assert!(should_include_file_by_content(&dated, &filters));
+    assert!(should_include_file_by_content(&undated, &filters));
+    // A date outside the range still fails
+    assert!(!should_include_file_by_content(&recent, &filters));
+}
+
+#[test]
+fn tag_count_bounds_count_distinct_yaml_and_inline_tags() {
+    let untagged = create_test_metadata("# Plain note\n");
+    let three = create_test_metadata("---\ntags: [rust, cli]\n---\n#rust #linux\n");
+
+    let untagged_only = CompiledFilters {
+        max_tags: Some(0),
+        ..Default::default()
+    };
+    assert!(should_include_file_by_content(&untagged, &untagged_only));
+    assert!(!should_include_file_by_content(&three, &untagged_only));
+
+    let well_tagged = CompiledFilters {
+        min_tags: Some(3),
+        ..Default::default()
+    };
+    assert!(should_include_file_by_content(&three, &well_tagged));
+    assert!(!should_include_file_by_content(&untagged, &well_tagged));
+
+    let exactly_two = CompiledFilters {
+        min_tags: Some(2),
+        max_tags: Some(2),
+        ..Default::default()
+    };
+    assert!(!should_include_file_by_content(&three, &exactly_two));
+}
+
+#[test]
+fn name_or_content_accepts_either_side() {
+    let filters = CompiledFilters {
+        name_patterns: vec![Regex::new("^todo").unwrap()],
+        name_or_content: true,
+        tag_patterns: vec![("rust".to_string(), Regex::new("rust").unwrap())],
+        ..Default::default()
+    };
+    let rust = create_test_metadata("---\ntags: [rust]\n---\n");
+    let plain = create_test_metadata("# Plain\n");
+
+    let by_name = PathBuf::from("todo-list.md");
+    let other = PathBuf::from("notes.md");
+    assert!(should_include_file(Some(&by_name), &plain, &filters));
+    assert!(should_include_file(Some(&other), &rust, &filters));
+    assert!(!should_include_file(Some(&other), &plain, &filters));
+}
+
+#[test]
+fn field_all_requires_every_value() {
+    let filters = CompiledFilters {
+        field_all_patterns: vec![(
+            "categories".to_string(),
+            vec!["a".to_string(), "b".to_string()],
+        )],
+        ..Default::default()
+    };
+    let abc = create_test_metadata("---\ncategories: [a, b, c]\n---\n");
+    let only_a = create_test_metadata("---\ncategories: [a]\n---\n");
+    let scalar = create_test_metadata("---\ncategories: a b\n---\n");
+
+    assert!(should_include_file_by_content(&abc, &filters));
+    assert!(!should_include_file_by_content(&only_a, &filters));
+    // A scalar only satisfies a single-value requirement
+    assert!(!should_include_file_by_content(&scalar, &filters));
+
+    let single = CompiledFilters {
+        field_all_patterns: vec![("categories".to_string(), vec!["a".to_string()])],
+        ..Default::default()
+    };
+    assert!(should_include_file_by_content(&scalar, &single));
+}
+
+#[test]
+fn field_eq_requires_exact_value() {
+    let filters = CompiledFilters {
+        field_eq_patterns: vec![("status".to_string(), "done".to_string())],
+        ..Default::default()
+    };
+    let done = create_test_metadata("---\nstatus: Done \n---\n");
+    let not_done = create_test_metadata("---\nstatus: not-done\n---\n");
+    let inline = create_test_metadata("status: done\n");
+
+    assert!(should_include_file_by_content(&done, &filters));
+    assert!(!should_include_file_by_content(&not_done, &filters));
+    assert!(should_include_file_by_content(&inline, &filters));
+
+    // Arrays need an element equal to the value
+    let tags = CompiledFilters {
+        field_eq_patterns: vec![("labels".to_string(), "rust".to_string())],
+        ..Default::default()
+    };
+    let exact = create_test_metadata("---\nlabels: [go, rust]\n---\n");
+    let partial = create_test_metadata("---\nlabels: [rustacean]\n---\n");
+    assert!(should_include_file_by_content(&exact, &tags));
+    assert!(!should_include_file_by_content(&partial, &tags));
+}
+
+#[test]
+fn link_count_bounds_count_links_and_wikilinks() {
+    let hub = create_test_metadata("See [a](a.md), [[B]] and [[C|see c]].\n![img](x.png)\n");
+    let orphan = create_test_metadata("# Alone\nNo links here.\n");
+
+    let min = CompiledFilters {
+        min_links: Some(4),
+        ..Default::default()
+    };
+    assert!(should_include_file_by_content(&hub, &min));
+    assert!(!should_include_file_by_content(&orphan, &min));
+
+    let max = CompiledFilters {
+        max_links: Some(0),
+        ..Default::default()
+    };
+    assert!(!should_include_file_by_content(&hub, &max));
+    assert!(should_include_file_by_content(&orphan, &max));
+}
+
+#[test]
+fn link_count_skip_code_blocks() {
+    let metadata =
+        create_test_metadata("[real](a.md) and `[span](b.md)`\n```\n[[fenced]]\n```\n[[Real]]\n");
+    let counting = |skip_code_blocks| CompiledFilters {
+        min_links: Some(4),
+        skip_code_blocks,
+        ..Default::default()
+    };
+    assert!(should_include_file_by_content(&metadata, &counting(false)));
+    assert!(!should_include_file_by_content(&metadata, &counting(true)));
+
+    let exactly_two = CompiledFilters {
+        min_links: Some(2),
+        max_links: Some(2),
+        skip_code_blocks: true,
+        ..Default::default()
+    };
+    assert!(should_include_file_by_content(&metadata, &exactly_two));
+}
+
+#[test]
+fn alias_filter_matches_string_and_list_aliases() {
+    let filters = CompiledFilters {
+        alias_patterns: vec!["old title".to_string()],
+        ..Default::default()
+    };
+    let listed = create_test_metadata("---\ntitle: New Title\naliases: [Old Title, OT]\n---\n");
+    let single = create_test_metadata("---\naliases: The Old Title\n---\n");
+    let titled = create_test_metadata("---\ntitle: Old Title\n---\n");
+
+    assert!(should_include_file_by_content(&listed, &filters));
+    assert!(should_include_file_by_content(&single, &filters));
+    // Titles are not aliases
+    assert!(!should_include_file_by_content(&titled, &filters));
+}
+
+#[test]
+fn task_filters_distinguish_open_and_done() {
+    let open = create_test_metadata("# Todo\n- [ ] call Bob\n- [x] email Ann\n");
+    let done = create_test_metadata("# Done\n- [x] call Bob\n");
+    let none = create_test_metadata("# Notes\n- call Bob\n");
+
+    let has_open = CompiledFilters {
+        has_open_tasks: true,
+        ..Default::default()
+    };
+    assert!(should_include_file_by_content(&open, &has_open));
+    assert!(!should_include_file_by_content(&done, &has_open));
+
+    let has_tasks = CompiledFilters {
+        has_tasks: true,
+        ..Default::default()
+    };
+    assert!(should_include_file_by_content(&done, &has_tasks));
+    assert!(!should_include_file_by_content(&none, &has_tasks));
+}
+
+#[test]
+fn frontmatter_presence_filters() {
+    let with_frontmatter = create_test_metadata("---\ntitle: Note\n---\n# Note\n");
+    let empty_block = create_test_metadata("---\n---\n# Note\n");
+    let plain = create_test_metadata("# Note\n");
+
+    let require = CompiledFilters {
+        has_frontmatter: Some(true),
+        ..Default::default()
+    };
+    assert!(should_include_file_by_content(&with_frontmatter, &require));
+    assert!(!should_include_file_by_content(&plain, &require));
+    // An empty block has nothing to parse, so it counts as no frontmatter
+    assert!(!should_include_file_by_content(&empty_block, &require));
+
+    let forbid = CompiledFilters {
+        has_frontmatter: Some(false),
+        ..Default::default()
+    };
+    assert!(!should_include_file_by_content(&with_frontmatter, &forbid));
+    assert!(should_include_file_by_content(&plain, &forbid));
+    assert!(should_include_file_by_content(&empty_block, &forbid));
+}
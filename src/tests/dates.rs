@@ -1,3 +1,4 @@
+use super::default_args;
 use crate::*;
 use chrono::NaiveDate;
 use std::path::PathBuf;
@@ -9,10 +10,10 @@ fn test_extract_dates_from_frontmatter() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
-    let dates = metadata.extract_dates();
+    let dates = metadata.extract_dates(&FieldAliases::default());
     assert_eq!(dates.len(), 2);
     assert!(dates.contains(&NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()));
     assert!(dates.contains(&NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()));
@@ -23,10 +24,10 @@ fn test_extract_dates_from_inline() {
     let content = "# Title\n\ndate: 2025-01-15\nupdated: 2025-01-20";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
-    let dates = metadata.extract_dates();
+    let dates = metadata.extract_dates(&FieldAliases::default());
     assert_eq!(dates.len(), 2);
     assert!(dates.contains(&NaiveDate::from_ymd_opt(2025, 1, 15).unwrap()));
     assert!(dates.contains(&NaiveDate::from_ymd_opt(2025, 1, 20).unwrap()));
@@ -39,18 +40,24 @@ fn test_matches_date_filters_after() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     // Should match: date is after 2025-01-10
-    assert!(
-        metadata.matches_date_filters(Some(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()), None)
-    );
+    assert!(metadata.matches_date_filters(
+        Some(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()),
+        None,
+        false,
+        &FieldAliases::default()
+    ));
 
     // Should not match: date is before 2025-01-20
-    assert!(
-        !metadata.matches_date_filters(Some(NaiveDate::from_ymd_opt(2025, 1, 20).unwrap()), None)
-    );
+    assert!(!metadata.matches_date_filters(
+        Some(NaiveDate::from_ymd_opt(2025, 1, 20).unwrap()),
+        None,
+        false,
+        &FieldAliases::default()
+    ));
 }
 
 #[test]
@@ -60,18 +67,24 @@ fn test_matches_date_filters_before() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     // Should match: date is before 2025-01-20
-    assert!(
-        metadata.matches_date_filters(None, Some(NaiveDate::from_ymd_opt(2025, 1, 20).unwrap()))
-    );
+    assert!(metadata.matches_date_filters(
+        None,
+        Some(NaiveDate::from_ymd_opt(2025, 1, 20).unwrap()),
+        false,
+        &FieldAliases::default()
+    ));
 
     // Should not match: date is after 2025-01-10
-    assert!(
-        !metadata.matches_date_filters(None, Some(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()))
-    );
+    assert!(!metadata.matches_date_filters(
+        None,
+        Some(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()),
+        false,
+        &FieldAliases::default()
+    ));
 }
 
 #[test]
@@ -81,19 +94,23 @@ fn test_matches_date_filters_range() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     // Should match: date is in range [2025-01-10, 2025-01-20]
     assert!(metadata.matches_date_filters(
         Some(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()),
-        Some(NaiveDate::from_ymd_opt(2025, 1, 20).unwrap())
+        Some(NaiveDate::from_ymd_opt(2025, 1, 20).unwrap()),
+        false,
+        &FieldAliases::default()
     ));
 
     // Should not match: date is outside range [2025-01-01, 2025-01-10]
     assert!(!metadata.matches_date_filters(
         Some(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()),
-        Some(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap())
+        Some(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()),
+        false,
+        &FieldAliases::default()
     ));
 }
 
@@ -104,16 +121,23 @@ fn test_matches_date_filters_multiple_dates() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     // Should match: at least one date (created: 2025-01-05) is after 2025-01-01
-    assert!(metadata.matches_date_filters(Some(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()), None));
+    assert!(metadata.matches_date_filters(
+        Some(NaiveDate::from_ymd_opt(2025, 1, 1).unwrap()),
+        None,
+        false,
+        &FieldAliases::default()
+    ));
 
     // Should match: at least one date (date: 2025-01-15) is in range
     assert!(metadata.matches_date_filters(
         Some(NaiveDate::from_ymd_opt(2025, 1, 10).unwrap()),
-        Some(NaiveDate::from_ymd_opt(2025, 1, 20).unwrap())
+        Some(NaiveDate::from_ymd_opt(2025, 1, 20).unwrap()),
+        false,
+        &FieldAliases::default()
     ));
 }
 
@@ -143,13 +167,14 @@ fn test_date_filter_args_parsing() {
         nul: false,
         ignore_case: false,
         depth: None,
-        glob: "**/*.md".to_string(),
+        glob: Some("**/*.md".to_string()),
         head_lines: 10,
         full_text: false,
         verbose: false,
         date_after: Some("2025-01-01".to_string()),
         date_before: Some("2025-12-31".to_string()),
         dirs: vec![PathBuf::from(".")],
+        ..default_args()
     };
 
     let filters = CompiledFilters::from_args(&args).unwrap();
@@ -175,13 +200,14 @@ fn test_date_filter_invalid_format() {
         nul: false,
         ignore_case: false,
         depth: None,
-        glob: "**/*.md".to_string(),
+        glob: Some("**/*.md".to_string()),
         head_lines: 10,
         full_text: false,
         verbose: false,
         date_after: Some("2025/01/01".to_string()), // Invalid format
         date_before: None,
         dirs: vec![PathBuf::from(".")],
+        ..default_args()
     };
 
     let result = CompiledFilters::from_args(&args_after);
@@ -201,13 +227,14 @@ fn test_date_filter_invalid_format() {
         nul: false,
         ignore_case: false,
         depth: None,
-        glob: "**/*.md".to_string(),
+        glob: Some("**/*.md".to_string()),
         head_lines: 10,
         full_text: false,
         verbose: false,
         date_after: None,
         date_before: Some("invalid-date".to_string()), // Invalid format
         dirs: vec![PathBuf::from(".")],
+        ..default_args()
     };
 
     let result = CompiledFilters::from_args(&args_before);
@@ -227,13 +254,14 @@ fn test_date_filter_invalid_format() {
         nul: false,
         ignore_case: false,
         depth: None,
-        glob: "**/*.md".to_string(),
+        glob: Some("**/*.md".to_string()),
         head_lines: 10,
         full_text: false,
         verbose: false,
         date_after: None,
         date_before: Some("".to_string()), // Empty
         dirs: vec![PathBuf::from(".")],
+        ..default_args()
     };
 
     let result = CompiledFilters::from_args(&args_empty);
@@ -242,3 +270,51 @@ fn test_date_filter_invalid_format() {
         assert!(e.to_string().contains("Invalid date format"));
     }
 }
+
+#[test]
+fn test_extract_dates_with_aliases() {
+    let content = "---\npublished: 2025-03-01\n---\n# Content";
+    let path = PathBuf::from("test.md");
+    let metadata = Metadata {
+        frontmatter: extract_frontmatter(content, &path),
+        raw_content: content.to_string().into(),
+    };
+
+    assert!(metadata.extract_dates(&FieldAliases::default()).is_empty());
+
+    let args = Args {
+        resolve_aliases: true,
+        ..default_args()
+    };
+    let aliases = FieldAliases::from_args(&args).unwrap();
+    assert_eq!(
+        metadata.extract_dates(&aliases),
+        vec![NaiveDate::from_ymd_opt(2025, 3, 1).unwrap()]
+    );
+}
+
+#[test]
+fn test_matches_date_filters_exclusive_bounds() {
+    let content = "---\ndate: 2025-01-15\n---\n# Content";
+    let path = PathBuf::from("test.md");
+    let metadata = Metadata {
+        frontmatter: extract_frontmatter(content, &path),
+        raw_content: content.to_string().into(),
+    };
+    let bound = Some(NaiveDate::from_ymd_opt(2025, 1, 15).unwrap());
+    let aliases = FieldAliases::default();
+
+    // Inclusive by default: a date on the bound matches
+    assert!(metadata.matches_date_filters(bound, None, false, &aliases));
+    assert!(metadata.matches_date_filters(None, bound, false, &aliases));
+
+    // Exclusive: the same date is excluded on either bound
+    assert!(!metadata.matches_date_filters(bound, None, true, &aliases));
+    assert!(!metadata.matches_date_filters(None, bound, true, &aliases));
+    assert!(metadata.matches_date_filters(
+        Some(NaiveDate::from_ymd_opt(2025, 1, 14).unwrap()),
+        Some(NaiveDate::from_ymd_opt(2025, 1, 16).unwrap()),
+        true,
+        &aliases
+    ));
+}
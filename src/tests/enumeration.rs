@@ -1,4 +1,5 @@
-use crate::{enumerate_files, Args};
+use super::default_args;
+use crate::{cache_key, enumerate_files, expand_dir_args, parse_path_list, read_dirs_file, Args};
 use std::fs;
 use std::path::PathBuf;
 use tempfile::TempDir;
@@ -17,7 +18,7 @@ fn create_temp_test_dir() -> (TempDir, PathBuf) {
 fn create_test_args(dirs: Vec<PathBuf>, glob: String, depth: Option<usize>) -> Args {
     Args {
         dirs,
-        glob,
+        glob: Some(glob),
         tags: vec![],
         titles: vec![],
         authors: vec![],
@@ -31,6 +32,7 @@ fn create_test_args(dirs: Vec<PathBuf>, glob: String, depth: Option<usize>) -> A
         depth,
         verbose: false,
         head_lines: 10,
+        ..default_args()
     }
 }
 
@@ -206,3 +208,306 @@ fn enumerate_files_empty_directory() {
     let files = enumerate_files(&args).unwrap();
     assert_eq!(files.len(), 0);
 }
+
+#[test]
+fn enumerate_files_glob_case_sensitivity() {
+    let (_temp, temp_path) = create_temp_test_dir();
+
+    fs::write(temp_path.join("lower.md"), "content").unwrap();
+    fs::write(temp_path.join("UPPER.MD"), "content").unwrap();
+
+    // Case-sensitive by default
+    let args = create_test_args(vec![temp_path.clone()], "*.MD".to_string(), None);
+    let files = enumerate_files(&args).unwrap();
+    assert_eq!(files.len(), 1);
+    assert!(files[0].ends_with("UPPER.MD"));
+
+    // -i makes the glob case-insensitive
+    let args = Args {
+        ignore_case: true,
+        ..create_test_args(vec![temp_path.clone()], "*.MD".to_string(), None)
+    };
+    let files = enumerate_files(&args).unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(files.iter().any(|f| f.ends_with("lower.md")));
+}
+
+#[test]
+fn enumerate_files_by_extensions() {
+    let (_temp, temp_path) = create_temp_test_dir();
+
+    fs::write(temp_path.join("note.md"), "content").unwrap();
+    fs::write(temp_path.join("post.mdx"), "content").unwrap();
+    fs::write(temp_path.join("todo.txt"), "content").unwrap();
+    fs::create_dir(temp_path.join("sub")).unwrap();
+    fs::write(temp_path.join("sub/deep.mdx"), "content").unwrap();
+
+    let args = Args {
+        dirs: vec![temp_path.clone()],
+        extensions: vec!["md".to_string(), ".mdx".to_string()],
+        ..default_args()
+    };
+    let files = enumerate_files(&args).unwrap();
+    assert_eq!(files.len(), 3);
+    assert!(!files.iter().any(|f| f.ends_with("todo.txt")));
+
+    // Extensions are unioned with an explicit --glob
+    let args = Args {
+        dirs: vec![temp_path.clone()],
+        glob: Some("*.txt".to_string()),
+        extensions: vec!["mdx".to_string()],
+        ..default_args()
+    };
+    let files = enumerate_files(&args).unwrap();
+    assert_eq!(files.len(), 3);
+    assert!(!files.iter().any(|f| f.ends_with("note.md")));
+}
+
+#[test]
+fn enumerate_files_extension_with_separator_is_error() {
+    let (_temp, temp_path) = create_temp_test_dir();
+
+    for ext in ["sub/md", "", "."] {
+        let args = Args {
+            dirs: vec![temp_path.clone()],
+            extensions: vec![ext.to_string()],
+            ..default_args()
+        };
+        assert!(
+            enumerate_files(&args).is_err(),
+            "Expected error for '{}'",
+            ext
+        );
+    }
+}
+
+#[test]
+fn read_dirs_file_skips_comments_and_missing_dirs() {
+    let (_temp, temp_path) = create_temp_test_dir();
+    fs::create_dir_all(temp_path.join("work")).unwrap();
+    fs::create_dir_all(temp_path.join("home")).unwrap();
+
+    let roots = temp_path.join("roots.txt");
+    let listed = |name: &str| temp_path.join(name).display().to_string();
+    fs::write(
+        &roots,
+        format!(
+            "# Vaults\n{}\n\n  {}  \n{}\n",
+            listed("work"),
+            listed("home"),
+            listed("gone")
+        ),
+    )
+    .unwrap();
+
+    let dirs = read_dirs_file(&roots, false).unwrap();
+    assert_eq!(dirs, vec![temp_path.join("work"), temp_path.join("home")]);
+
+    assert!(read_dirs_file(&temp_path.join("missing.txt"), false).is_err());
+}
+
+#[test]
+fn expand_dir_args_with_wildcard() {
+    let (_temp, temp_path) = create_temp_test_dir();
+
+    for dir in ["vault/a/notes", "vault/b/notes", "vault/c"] {
+        fs::create_dir_all(temp_path.join(dir)).unwrap();
+    }
+    fs::write(temp_path.join("vault/a/notes/one.md"), "content").unwrap();
+    fs::write(temp_path.join("vault/b/notes/two.md"), "content").unwrap();
+    fs::write(temp_path.join("vault/c/three.md"), "content").unwrap();
+
+    let pattern = temp_path.join("vault/*/notes");
+    let expanded = expand_dir_args(std::slice::from_ref(&pattern), false).unwrap();
+    assert_eq!(
+        expanded,
+        vec![
+            temp_path.join("vault/a/notes"),
+            temp_path.join("vault/b/notes")
+        ]
+    );
+
+    let args = Args {
+        dirs: vec![pattern],
+        ..default_args()
+    };
+    let files = enumerate_files(&args).unwrap();
+    assert_eq!(files.len(), 2);
+    assert!(!files.iter().any(|f| f.ends_with("three.md")));
+}
+
+#[test]
+fn expand_dir_args_passes_plain_paths_through() {
+    let dirs = vec![PathBuf::from("does/not/exist"), PathBuf::from(".")];
+    assert_eq!(expand_dir_args(&dirs, false).unwrap(), dirs);
+
+    let unmatched = expand_dir_args(&[PathBuf::from("no_such_*_dir")], false).unwrap();
+    assert!(unmatched.is_empty());
+}
+
+#[test]
+fn enumerate_files_missing_directories() {
+    let (_temp, temp_path) = create_temp_test_dir();
+    fs::write(temp_path.join("note.md"), "content").unwrap();
+    let missing = temp_path.join("missing");
+
+    // Only missing directories is an error
+    let args = create_test_args(vec![missing.clone()], "**/*.md".to_string(), None);
+    assert!(enumerate_files(&args).is_err());
+
+    let args = Args {
+        allow_missing_dirs: true,
+        ..create_test_args(vec![missing.clone()], "**/*.md".to_string(), None)
+    };
+    assert!(enumerate_files(&args).unwrap().is_empty());
+
+    // A mix searches the existing ones
+    let args = create_test_args(
+        vec![temp_path.clone(), missing],
+        "**/*.md".to_string(),
+        None,
+    );
+    assert_eq!(enumerate_files(&args).unwrap().len(), 1);
+}
+
+#[test]
+fn enumerate_files_cache_reuse_and_invalidation() {
+    let (_temp_dir, path) = create_temp_test_dir();
+    fs::write(path.join("a.md"), "# A").unwrap();
+    fs::write(path.join("b.md"), "# B").unwrap();
+    let cache_path = path.join("files.json");
+
+    let args = Args {
+        cache: Some(cache_path.clone()),
+        ..create_test_args(vec![path.clone()], "**/*.md".to_string(), None)
+    };
+    let mut files = enumerate_files(&args).unwrap();
+    files.sort();
+    assert_eq!(files, vec![path.join("a.md"), path.join("b.md")]);
+
+    // While no directory changed, the cached list is returned as-is
+    let mut cache: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&cache_path).unwrap()).unwrap();
+    cache["files"]
+        .as_array_mut()
+        .unwrap()
+        .retain(|entry| entry["path"].as_str().is_some_and(|p| p.ends_with("a.md")));
+    fs::write(&cache_path, cache.to_string()).unwrap();
+    assert_eq!(enumerate_files(&args).unwrap(), vec![path.join("a.md")]);
+
+    // Deleting a file changes the directory, so the tree is walked again
+    fs::remove_file(path.join("a.md")).unwrap();
+    assert_eq!(enumerate_files(&args).unwrap(), vec![path.join("b.md")]);
+}
+
+#[test]
+fn parse_path_list_delimiters() {
+    let expected = vec![PathBuf::from("my notes/a b.md"), PathBuf::from("c.md")];
+
+    // NUL-delimited with -0, including a trailing NUL
+    assert_eq!(parse_path_list("my notes/a b.md\0c.md\0", true), expected);
+    // NUL bytes are detected without -0
+    assert_eq!(parse_path_list("my notes/a b.md\0c.md\0", false), expected);
+    // Otherwise one path per line, skipping blank lines
+    assert_eq!(
+        parse_path_list("my notes/a b.md\n\nc.md\n", false),
+        expected
+    );
+}
+
+#[test]
+fn enumerate_files_depth_min() {
+    let (_temp, temp_path) = create_temp_test_dir();
+
+    let level2 = temp_path.join("level1").join("level2");
+    fs::create_dir_all(level2.join("level3")).unwrap();
+    fs::write(temp_path.join("index.md"), "content").unwrap();
+    fs::write(temp_path.join("level1").join("l1.md"), "content").unwrap();
+    fs::write(level2.join("l2.md"), "content").unwrap();
+    fs::write(level2.join("level3").join("l3.md"), "content").unwrap();
+
+    // --depth-min 2 --depth 3 keeps only the middle levels
+    let args = Args {
+        depth_min: Some(2),
+        ..create_test_args(vec![temp_path.clone()], "**/*.md".to_string(), Some(3))
+    };
+    let mut names: Vec<String> = enumerate_files(&args)
+        .unwrap()
+        .iter()
+        .map(|f| f.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["l1.md", "l2.md"]);
+}
+
+#[test]
+fn enumerate_files_one_file_system() {
+    let (_temp, temp_path) = create_temp_test_dir();
+    fs::create_dir_all(temp_path.join("sub").join("deep")).unwrap();
+    fs::write(temp_path.join("top.md"), "content").unwrap();
+    fs::write(temp_path.join("sub").join("a.md"), "content").unwrap();
+    fs::write(temp_path.join("sub").join("deep").join("b.md"), "content").unwrap();
+    fs::write(temp_path.join("sub").join("notes.txt"), "content").unwrap();
+
+    // Subdirectories on the same device are still walked, at every depth
+    let args = Args {
+        one_file_system: true,
+        ..create_test_args(vec![temp_path.clone()], "**/*.md".to_string(), None)
+    };
+    let mut files = enumerate_files(&args).unwrap();
+    files.sort();
+    assert_eq!(
+        files,
+        vec![
+            temp_path.join("sub").join("a.md"),
+            temp_path.join("sub").join("deep").join("b.md"),
+            temp_path.join("top.md"),
+        ]
+    );
+
+    // On a single device the walk finds the same files as without the flag
+    let plain = create_test_args(vec![temp_path.clone()], "**/*.md".to_string(), None);
+    let mut plain_files = enumerate_files(&plain).unwrap();
+    plain_files.sort();
+    assert_eq!(files, plain_files);
+
+    // A cached list from the unrestricted walk isn't reused
+    assert_ne!(
+        cache_key(std::slice::from_ref(&temp_path), &args),
+        cache_key(std::slice::from_ref(&temp_path), &plain)
+    );
+}
+
+#[test]
+fn enumerate_files_hidden() {
+    let (_temp, temp_path) = create_temp_test_dir();
+    fs::write(temp_path.join("visible.md"), "content").unwrap();
+    fs::write(temp_path.join(".secret.md"), "content").unwrap();
+    fs::create_dir(temp_path.join(".git")).unwrap();
+    fs::write(temp_path.join(".git").join("notes.md"), "content").unwrap();
+
+    let dot_glob = create_test_args(vec![temp_path.clone()], "**/.*.md".to_string(), None);
+    assert_eq!(enumerate_files(&dot_glob).unwrap(), Vec::<PathBuf>::new());
+
+    let args = Args {
+        hidden: true,
+        ..dot_glob
+    };
+    assert_eq!(
+        enumerate_files(&args).unwrap(),
+        vec![temp_path.join(".secret.md")]
+    );
+
+    // .git stays excluded even with --hidden
+    let args = Args {
+        hidden: true,
+        ..create_test_args(vec![temp_path.clone()], "**/*.md".to_string(), None)
+    };
+    let mut names: Vec<String> = enumerate_files(&args)
+        .unwrap()
+        .iter()
+        .map(|f| f.file_name().unwrap().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    assert_eq!(names, vec![".secret.md", "visible.md"]);
+}
@@ -101,9 +101,55 @@ fn test_read_file_content_head_lines_zero() {
     temp_file.flush().unwrap();
 
     let content = read_file_content(temp_file.path(), 0, false).unwrap();
-    // Current implementation reads one line before checking head_lines
-    // So with head_lines=0, it returns the first line
-    assert_eq!(content, "Line 1");
+    assert_eq!(content, "");
+}
+
+#[test]
+fn test_read_file_content_head_lines_zero_keeps_frontmatter() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(temp_file, "---").unwrap();
+    writeln!(temp_file, "title: Test").unwrap();
+    writeln!(temp_file, "---").unwrap();
+    writeln!(temp_file, "Line 1").unwrap();
+    temp_file.flush().unwrap();
+
+    let options = ReadOptions {
+        head_lines: 0,
+        body_head_lines: 0,
+        ..Default::default()
+    };
+    let content = read_file_content_with_options(temp_file.path(), &options).unwrap();
+    assert_eq!(content, "---\ntitle: Test\n---");
+}
+
+#[test]
+fn test_read_options_head_zero_skips_default_body_head() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(temp_file, "---").unwrap();
+    writeln!(temp_file, "title: Test").unwrap();
+    writeln!(temp_file, "---").unwrap();
+    writeln!(temp_file, "# Note").unwrap();
+    writeln!(temp_file, "author: bob").unwrap();
+    temp_file.flush().unwrap();
+
+    let read_with = |body_head_lines| {
+        let args = Args {
+            head_lines: 0,
+            body_head_lines,
+            ..super::default_args()
+        };
+        let options = ReadOptions::from_args(&args).unwrap();
+        read_file_content_with_options(temp_file.path(), &options).unwrap()
+    };
+    // Without --body-head, --head 0 reads only the frontmatter
+    assert_eq!(read_with(None), "---\ntitle: Test\n---");
+    assert_eq!(
+        read_with(Some(5)),
+        "---\ntitle: Test\n---\n# Note\nauthor: bob"
+    );
+
+    let defaults = ReadOptions::from_args(&super::default_args()).unwrap();
+    assert_eq!(defaults.body_head_lines, DEFAULT_BODY_HEAD_LINES);
 }
 
 #[test]
@@ -281,3 +327,275 @@ fn test_read_file_content_excessive_frontmatter() {
         assert!(e.to_string().contains("1000"));
     }
 }
+
+#[test]
+fn test_read_file_content_frontmatter_after_preamble() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(temp_file, "<!-- generated -->").unwrap();
+    writeln!(temp_file, "---").unwrap();
+    for i in 0..5 {
+        writeln!(temp_file, "field{}: value{}", i, i).unwrap();
+    }
+    writeln!(temp_file, "title: Last Field").unwrap();
+    writeln!(temp_file, "---").unwrap();
+    writeln!(temp_file, "Content").unwrap();
+    temp_file.flush().unwrap();
+
+    // The frontmatter is read to its end even though it exceeds head_lines
+    let content = read_file_content(temp_file.path(), 3, false).unwrap();
+    assert!(content.contains("title: Last Field"));
+    assert!(!content.contains("Content"));
+}
+
+#[test]
+fn test_read_file_content_shift_jis() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    // "# 会議メモ" encoded as Shift_JIS
+    temp_file
+        .write_all(&[35, 32, 137, 239, 139, 99, 131, 129, 131, 130, b'\n'])
+        .unwrap();
+    temp_file.flush().unwrap();
+
+    // Strict UTF-8 reading rejects the file
+    assert!(read_file_content(temp_file.path(), 10, false).is_err());
+
+    let options = ReadOptions {
+        head_lines: 10,
+        encoding: encoding_rs::Encoding::for_label(b"shift_jis"),
+        ..Default::default()
+    };
+    let metadata = Metadata::from_file_with_options(temp_file.path(), &options).unwrap();
+    assert!(metadata.has_title("会議"));
+}
+
+#[test]
+fn test_read_options_unknown_encoding() {
+    let args = Args {
+        encoding: Some("klingon".to_string()),
+        ..super::default_args()
+    };
+    let result = ReadOptions::from_args(&args);
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("Unknown encoding"));
+}
+
+#[test]
+fn test_read_file_content_custom_buffer_size() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(temp_file, "---").unwrap();
+    writeln!(temp_file, "title: Buffered 読み込み").unwrap();
+    writeln!(temp_file, "---").unwrap();
+    for i in 0..20 {
+        writeln!(temp_file, "Line {} with some text", i).unwrap();
+    }
+    temp_file.flush().unwrap();
+
+    let read_with = |read_buffer_size| {
+        let options = ReadOptions {
+            head_lines: 10,
+            read_buffer_size,
+            ..Default::default()
+        };
+        read_file_content_with_options(temp_file.path(), &options).unwrap()
+    };
+    let expected = read_with(None);
+    for size in [1, 3, 64, 1 << 20] {
+        assert_eq!(read_with(Some(size)), expected, "buffer of {} bytes", size);
+    }
+
+    let args = Args {
+        read_buffer_size: Some(0),
+        ..super::default_args()
+    };
+    assert!(ReadOptions::from_args(&args).is_err());
+}
+
+#[test]
+fn test_read_file_content_body_head_after_long_frontmatter() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(temp_file, "---").unwrap();
+    for i in 0..30 {
+        writeln!(temp_file, "key{}: value", i).unwrap();
+    }
+    writeln!(temp_file, "---").unwrap();
+    writeln!(temp_file, "# Heading").unwrap();
+    writeln!(temp_file, "author: Jane Doe").unwrap();
+    writeln!(temp_file, "Body").unwrap();
+    temp_file.flush().unwrap();
+
+    // Without a body budget, reading stops right after the closing ---
+    let no_body = ReadOptions {
+        head_lines: 10,
+        ..Default::default()
+    };
+    let metadata = Metadata::from_file_with_options(temp_file.path(), &no_body).unwrap();
+    assert!(!metadata.has_author("jane", false));
+
+    let with_body = ReadOptions {
+        head_lines: 10,
+        body_head_lines: 2,
+        ..Default::default()
+    };
+    let metadata = Metadata::from_file_with_options(temp_file.path(), &with_body).unwrap();
+    assert!(metadata.has_author("jane", false));
+    assert!(!metadata.raw_content.contains("Body"));
+}
+
+#[test]
+fn test_grep_file_stops_at_first_match() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file.write_all(b"# Notes\nTODO: ship it\n").unwrap();
+    // Invalid UTF-8 after the match is never read
+    temp_file.write_all(&[0xff, 0xfe, b'\n']).unwrap();
+    temp_file.flush().unwrap();
+
+    let todo = Regex::new("^TODO").unwrap();
+    assert!(grep_file(
+        temp_file.path(),
+        std::slice::from_ref(&todo),
+        &ReadOptions::default()
+    )
+    .unwrap());
+
+    let missing = Regex::new("FIXME").unwrap();
+    assert!(grep_file(temp_file.path(), &[missing], &ReadOptions::default()).is_err());
+}
+
+#[test]
+fn test_mmap_reads_match_normal_reads() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(temp_file, "---\ntitle: Big\ntags: [archive]\n---").unwrap();
+    for i in 0..4000 {
+        writeln!(temp_file, "Filler line {} of a long note.", i).unwrap();
+    }
+    writeln!(temp_file, "Closing #footer tag").unwrap();
+    temp_file.flush().unwrap();
+    assert!(temp_file.as_file().metadata().unwrap().len() >= MMAP_MIN_BYTES);
+
+    for (full_text, end_matter) in [(true, false), (false, true)] {
+        let options = |mmap| ReadOptions {
+            head_lines: DEFAULT_HEAD_LINES,
+            full_text,
+            end_matter,
+            mmap,
+            ..Default::default()
+        };
+        let (normal, mapped) = (options(false), options(true));
+        assert!(map_file(temp_file.path(), &mapped).is_some());
+
+        let expected = Metadata::from_file_with_options(temp_file.path(), &normal).unwrap();
+        let actual = Metadata::from_file_with_options(temp_file.path(), &mapped).unwrap();
+        assert_eq!(*actual.raw_content, *expected.raw_content);
+        // --full-text searches the mapping itself rather than a copy
+        assert_eq!(
+            matches!(actual.raw_content, RawContent::Mapped(_)),
+            full_text
+        );
+        assert_eq!(
+            actual.frontmatter.and_then(|fm| fm.title),
+            expected.frontmatter.and_then(|fm| fm.title)
+        );
+    }
+
+    let footer = Regex::new("#footer").unwrap();
+    let mapped = ReadOptions {
+        mmap: true,
+        ..Default::default()
+    };
+    assert!(grep_file(temp_file.path(), &[footer], &mapped).unwrap());
+}
+
+#[test]
+fn test_grep_lines_reports_every_hit() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    temp_file
+        .write_all(b"# Notes\nTODO: ship it\nDone\nTODO: docs\n")
+        .unwrap();
+    temp_file.flush().unwrap();
+
+    let todo = Regex::new("^TODO").unwrap();
+    let options = ReadOptions::default();
+    assert_eq!(
+        grep_lines(
+            temp_file.path(),
+            std::slice::from_ref(&todo),
+            &options,
+            false
+        )
+        .unwrap(),
+        vec![
+            (2, "TODO: ship it".to_string()),
+            (4, "TODO: docs".to_string())
+        ]
+    );
+    assert_eq!(
+        grep_lines(temp_file.path(), &[todo], &options, true).unwrap(),
+        vec![(2, "TODO: ship it".to_string())]
+    );
+}
+
+#[test]
+fn test_read_file_content_head_bytes_truncates_long_line() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(temp_file, "{}", "x".repeat(5000)).unwrap();
+    writeln!(temp_file, "tags: #late").unwrap();
+    temp_file.flush().unwrap();
+
+    let options = ReadOptions {
+        head_bytes: Some(100),
+        ..Default::default()
+    };
+    let content = read_file_content_with_options(temp_file.path(), &options).unwrap();
+    assert_eq!(content, "x".repeat(100));
+
+    // Multi-byte characters are never split
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(temp_file, "{}", "é".repeat(100)).unwrap();
+    temp_file.flush().unwrap();
+    let options = ReadOptions {
+        head_bytes: Some(101),
+        ..Default::default()
+    };
+    let content = read_file_content_with_options(temp_file.path(), &options).unwrap();
+    assert_eq!(content, "é".repeat(50));
+}
+
+#[test]
+fn test_read_head_window_head_bytes_stops_reading_long_line() {
+    let options = ReadOptions {
+        head_bytes: Some(100),
+        ..Default::default()
+    };
+    for before in ["", "# Title\n"] {
+        let text = format!("{}{}\nafter\n", before, "x".repeat(1 << 20));
+        let mut reader = std::io::Cursor::new(text.into_bytes());
+        let content = read_head_window(&mut reader, &options, &PathBuf::from("t.md")).unwrap();
+
+        assert_eq!(
+            content,
+            format!("{}{}", before, "x".repeat(100 - before.len()))
+        );
+        // Only the budget (and the byte that shows the line goes on) was consumed
+        assert!(reader.position() <= 102, "read {} bytes", reader.position());
+    }
+}
+
+#[test]
+fn test_read_file_content_head_bytes_completes_frontmatter() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(temp_file, "---").unwrap();
+    writeln!(temp_file, "title: {}", "Long ".repeat(20)).unwrap();
+    writeln!(temp_file, "---").unwrap();
+    writeln!(temp_file, "Body line").unwrap();
+    temp_file.flush().unwrap();
+
+    // The frontmatter is read to its end even past the budget
+    let options = ReadOptions {
+        head_bytes: Some(10),
+        body_head_lines: 0,
+        ..Default::default()
+    };
+    let content = read_file_content_with_options(temp_file.path(), &options).unwrap();
+    assert!(content.starts_with("---\ntitle: Long"));
+    assert!(content.ends_with("---"));
+}
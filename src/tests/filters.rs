@@ -1,5 +1,8 @@
+use super::default_args;
 use crate::*;
+use std::io::Write;
 use std::path::PathBuf;
+use tempfile::NamedTempFile;
 
 #[test]
 fn test_compiled_filters_tag_regex() {
@@ -12,13 +15,14 @@ fn test_compiled_filters_tag_regex() {
         nul: false,
         ignore_case: false,
         depth: None,
-        glob: "**/*.md".to_string(),
+        glob: Some("**/*.md".to_string()),
         head_lines: 10,
         full_text: false,
         verbose: false,
         date_after: None,
         date_before: None,
         dirs: vec![PathBuf::from(".")],
+        ..default_args()
     };
 
     let filters = CompiledFilters::from_args(&args).unwrap();
@@ -43,13 +47,14 @@ fn test_compiled_filters_invalid_regex_returns_error() {
         nul: false,
         ignore_case: false,
         depth: None,
-        glob: "**/*.md".to_string(),
+        glob: Some("**/*.md".to_string()),
         head_lines: 10,
         full_text: false,
         verbose: false,
         date_after: None,
         date_before: None,
         dirs: vec![PathBuf::from(".")],
+        ..default_args()
     };
 
     let result = CompiledFilters::from_args(&args);
@@ -79,13 +84,14 @@ fn test_compiled_filters_field_validation_errors() {
             nul: false,
             ignore_case: false,
             depth: None,
-            glob: "**/*.md".to_string(),
+            glob: Some("**/*.md".to_string()),
             head_lines: 10,
             full_text: false,
             verbose: false,
             date_after: None,
             date_before: None,
             dirs: vec![PathBuf::from(".")],
+            ..default_args()
         };
 
         let result = CompiledFilters::from_args(&args);
@@ -101,3 +107,143 @@ fn test_compiled_filters_field_validation_errors() {
         }
     }
 }
+
+#[test]
+fn test_compiled_filters_tag_spec_prefixes() {
+    let args = Args {
+        tag_specs: vec!["+rust +cli -draft".to_string(), "tool #util".to_string()],
+        ..default_args()
+    };
+
+    let filters = CompiledFilters::from_args(&args).unwrap();
+    let names = |patterns: &[(String, regex::Regex)]| {
+        patterns.iter().map(|(p, _)| p.clone()).collect::<Vec<_>>()
+    };
+    assert_eq!(names(&filters.required_tag_patterns), vec!["rust", "cli"]);
+    assert_eq!(names(&filters.excluded_tag_patterns), vec!["draft"]);
+    assert_eq!(names(&filters.tag_patterns), vec!["tool", "util"]);
+}
+
+#[test]
+fn test_compiled_filters_tag_spec_empty_tag_error() {
+    let args = Args {
+        tag_specs: vec!["+rust -".to_string()],
+        ..default_args()
+    };
+
+    let result = CompiledFilters::from_args(&args);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_field_aliases_from_args() {
+    let args = Args {
+        resolve_aliases: true,
+        field_aliases: vec!["author=writer, by".to_string(), "status=state".to_string()],
+        ..default_args()
+    };
+
+    let aliases = FieldAliases::from_args(&args).unwrap();
+    assert_eq!(aliases.aliases_of("author"), ["by", "authors", "writer"]);
+    assert_eq!(aliases.aliases_of("date"), ["published", "pubdate"]);
+    assert_eq!(aliases.aliases_of("status"), ["state"]);
+    assert!(aliases.aliases_of("title").is_empty());
+
+    // Without the flag only explicit aliases apply
+    let explicit = FieldAliases::from_args(&Args {
+        field_aliases: vec!["status=state".to_string()],
+        ..default_args()
+    })
+    .unwrap();
+    assert!(explicit.aliases_of("author").is_empty());
+
+    let invalid = Args {
+        field_aliases: vec!["author".to_string()],
+        ..default_args()
+    };
+    assert!(FieldAliases::from_args(&invalid).is_err());
+}
+
+#[test]
+fn test_read_tags_file_skips_comments_and_blanks() {
+    let mut file = NamedTempFile::new().unwrap();
+    file.write_all(b"# Project tags\nrust\n\n  cli  \n# old\nweb\n")
+        .unwrap();
+
+    let tags = read_tags_file(file.path()).unwrap();
+    assert_eq!(tags, vec!["rust", "cli", "web"]);
+
+    let args = Args {
+        tags: vec!["extra".to_string()],
+        tags_file: Some(file.path().to_path_buf()),
+        ..default_args()
+    };
+    let filters = CompiledFilters::from_args(&args).unwrap();
+    assert_eq!(filters.tag_patterns.len(), 4);
+}
+
+#[test]
+fn test_read_tags_file_missing() {
+    let result = read_tags_file(&PathBuf::from("/nonexistent/tags.txt"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_compiled_filters_field_separator() {
+    let args = Args {
+        fields: vec!["url=https://example.com".to_string()],
+        field_separator: '=',
+        ..default_args()
+    };
+    let filters = CompiledFilters::from_args(&args).unwrap();
+    assert_eq!(
+        filters.field_patterns,
+        vec![("url".to_string(), "https://example.com".to_string())]
+    );
+
+    // The default colon is then just part of the pattern
+    let args = Args {
+        fields: vec!["url:https://example.com".to_string()],
+        field_separator: '=',
+        ..default_args()
+    };
+    let error = CompiledFilters::from_args(&args).err().unwrap();
+    assert!(error.to_string().contains("Expected 'field=pattern'"));
+
+    let args = Args {
+        field_separator: ' ',
+        ..default_args()
+    };
+    assert!(CompiledFilters::from_args(&args).is_err());
+}
+
+#[test]
+fn test_compiled_filters_field_name_with_separator() {
+    let args = Args {
+        fields: vec![
+            "\"dc:creator\":alice".to_string(),
+            r"dc\:subject:rust:async".to_string(),
+            r"back\\slash:x".to_string(),
+        ],
+        ..default_args()
+    };
+    let filters = CompiledFilters::from_args(&args).unwrap();
+    assert_eq!(
+        filters.field_patterns,
+        vec![
+            ("dc:creator".to_string(), "alice".to_string()),
+            ("dc:subject".to_string(), "rust:async".to_string()),
+            (r"back\slash".to_string(), "x".to_string()),
+        ]
+    );
+
+    // An unclosed quote, or a quoted name without a separator, is an error
+    for spec in ["\"dc:creator:alice", "\"dc:creator\" alice"] {
+        let args = Args {
+            fields: vec![spec.to_string()],
+            ..default_args()
+        };
+        let error = CompiledFilters::from_args(&args).err().unwrap();
+        assert!(error.to_string().contains("Expected 'field:pattern'"));
+    }
+}
@@ -50,14 +50,37 @@ fn test_extract_frontmatter_multiline_tags() {
 #[test]
 fn test_tag_value_contains_tag() {
     let single = TagValue::Single("rust".to_string());
-    assert!(single.contains_tag("rust"));
-    assert!(single.contains_tag("RUST")); // case insensitive
-    assert!(!single.contains_tag("python"));
+    assert!(single.contains_tag("rust", ""));
+    assert!(single.contains_tag("RUST", "")); // case insensitive
+    assert!(!single.contains_tag("python", ""));
 
     let array = TagValue::Array(vec!["rust".to_string(), "cli".to_string()]);
-    assert!(array.contains_tag("rust"));
-    assert!(array.contains_tag("CLI")); // case insensitive
-    assert!(!array.contains_tag("python"));
+    assert!(array.contains_tag("rust", ""));
+    assert!(array.contains_tag("CLI", "")); // case insensitive
+    assert!(!array.contains_tag("python", ""));
+}
+
+#[test]
+fn test_extract_frontmatter_trims_padded_tags() {
+    let content = "---\ntags: [\" rust \", \"cli  \", \" \"]\n---\n# Content";
+    let path = PathBuf::from("test.md");
+    let fm = extract_frontmatter(content, &path).unwrap();
+
+    match &fm.tags {
+        Some(TagValue::Array(tags)) => assert_eq!(tags, &vec!["rust", "cli"]),
+        other => panic!("Expected array of tags, got {:?}", other),
+    }
+
+    // Whole-tag (glob) matching sees the trimmed tag
+    let metadata = Metadata {
+        frontmatter: Some(fm),
+        raw_content: content.to_string().into(),
+    };
+    let (yaml_regex, inline_regex) = compile_tag_glob("cli", "").unwrap();
+    assert!(metadata.has_tag_glob(&yaml_regex, &inline_regex, &TagOptions::default()));
+
+    let single = extract_frontmatter("---\ntags: \" cli \"\n---\n", &path).unwrap();
+    assert!(matches!(single.tags, Some(TagValue::Single(ref tag)) if tag == "cli"));
 }
 
 // Edge case tests
@@ -264,27 +287,215 @@ fn test_extract_frontmatter_only_opening_delimiter() {
 #[test]
 fn test_tag_value_single_empty() {
     let single = TagValue::Single("".to_string());
-    assert!(single.contains_tag(""));
-    assert!(!single.contains_tag("anything"));
+    assert!(single.contains_tag("", ""));
+    assert!(!single.contains_tag("anything", ""));
 }
 
 #[test]
 fn test_tag_value_array_empty() {
     let array = TagValue::Array(vec![]);
-    assert!(!array.contains_tag("anything"));
+    assert!(!array.contains_tag("anything", ""));
 }
 
 #[test]
 fn test_tag_value_array_with_empty_strings() {
     let array = TagValue::Array(vec!["".to_string(), "rust".to_string()]);
-    assert!(array.contains_tag("rust"));
-    assert!(array.contains_tag(""));
+    assert!(array.contains_tag("rust", ""));
+    assert!(array.contains_tag("", ""));
+}
+
+#[test]
+fn test_extract_frontmatter_tag_maps() {
+    let content =
+        "---\ntitle: Weighted\ntags:\n  - name: rust\n    weight: 3\n  - name: cli\n  - weight: 1\n---\n";
+    let path = PathBuf::from("test.md");
+    let fm = extract_frontmatter(content, &path).unwrap();
+
+    assert_eq!(fm.title, Some("Weighted".to_string()));
+    let tags = fm.tags.unwrap();
+    assert!(matches!(tags, TagValue::Maps(ref maps) if maps.len() == 3));
+    assert_eq!(tags.names(), vec!["rust", "cli"]);
+    assert!(tags.contains_tag("RUST", ""));
+    assert!(!tags.contains_tag("weight", ""));
 }
 
 #[test]
 fn test_tag_value_partial_match() {
     let single = TagValue::Single("rust-programming".to_string());
-    assert!(single.contains_tag("rust"));
-    assert!(single.contains_tag("programming"));
-    assert!(single.contains_tag("rust-prog"));
+    assert!(single.contains_tag("rust", ""));
+    assert!(single.contains_tag("programming", ""));
+    assert!(single.contains_tag("rust-prog", ""));
+}
+
+#[test]
+fn test_extract_frontmatter_after_html_comment() {
+    let content = "<!-- generated -->\n---\ntitle: Generated\ntags: [auto]\n---\n# Content";
+    let path = PathBuf::from("test.md");
+    let fm = extract_frontmatter(content, &path);
+
+    assert!(fm.is_some());
+    assert_eq!(fm.unwrap().title, Some("Generated".to_string()));
+}
+
+#[test]
+fn test_extract_frontmatter_after_shebang() {
+    let content = "#!/usr/bin/env runme\n---\ntitle: Script Note\n---\n# Content";
+    let path = PathBuf::from("test.md");
+    let fm = extract_frontmatter(content, &path);
+
+    assert!(fm.is_some());
+    assert_eq!(fm.unwrap().title, Some("Script Note".to_string()));
+}
+
+#[test]
+fn test_extract_frontmatter_only_one_preamble_line() {
+    let content = "<!-- one -->\n<!-- two -->\n---\ntitle: Test\n---\n# Content";
+    let path = PathBuf::from("test.md");
+    let fm = extract_frontmatter(content, &path);
+
+    // Only a single leading comment line is allowed before the frontmatter
+    assert!(fm.is_none());
+}
+
+#[test]
+fn test_descend_field_root() {
+    let content = "---\ndate: 2025-01-15\nfrontmatter:\n  title: Wrapped\n  tags: [rust, cli]\n  status: draft\n---\n";
+    let path = PathBuf::from("test.md");
+    let fm = extract_frontmatter(content, &path).unwrap();
+    assert!(fm.title.is_none());
+
+    let fm = descend_field_root(fm, "frontmatter", &path);
+    assert_eq!(fm.title, Some("Wrapped".to_string()));
+    assert!(fm.tags.unwrap().contains_tag("rust", ""));
+    assert!(fm.extra.contains_key("status"));
+    // Top-level keys outside the wrapper are kept
+    assert!(fm.extra.contains_key("date"));
+    assert!(!fm.extra.contains_key("frontmatter"));
+}
+
+#[test]
+fn test_descend_field_root_missing_or_scalar() {
+    let path = PathBuf::from("test.md");
+
+    let fm = extract_frontmatter("---\ntitle: Top\n---\n", &path).unwrap();
+    let fm = descend_field_root(fm, "fields", &path);
+    assert_eq!(fm.title, Some("Top".to_string()));
+
+    let fm = extract_frontmatter("---\ntitle: Top\nfields: none\n---\n", &path).unwrap();
+    let fm = descend_field_root(fm, "fields", &path);
+    assert_eq!(fm.title, Some("Top".to_string()));
+    assert!(fm.extra.contains_key("fields"));
+}
+
+#[test]
+fn test_merge_sidecar_overrides_frontmatter() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("note.md");
+    std::fs::write(
+        dir.path().join("note.md.meta.yml"),
+        "title: From Sidecar\ntags: [rust]\n",
+    )
+    .unwrap();
+
+    let fm = extract_frontmatter("---\ntitle: Inline\nstatus: draft\n---\n", &path);
+    let fm = merge_sidecar(fm, &path, ".meta.yml").unwrap();
+    assert_eq!(fm.title, Some("From Sidecar".to_string()));
+    assert!(fm.tags.unwrap().contains_tag("rust", ""));
+    assert!(fm.extra.contains_key("status"));
+
+    // Without frontmatter the sidecar stands alone; without a sidecar nothing changes
+    assert!(merge_sidecar(None, &path, ".meta.yml").is_some());
+    assert!(merge_sidecar(None, &path, ".missing.yml").is_none());
+}
+
+#[test]
+fn test_extract_end_matter() {
+    let content = "# Export\n\nBody text\n\n---\ntitle: Exported Note\ntags: [export]\n---\n\n";
+    let fm = extract_end_matter(content).unwrap();
+    assert_eq!(fm.title.as_deref(), Some("Exported Note"));
+
+    // A block followed by more prose doesn't reach EOF
+    let content = "Intro\n---\ntitle: Not Metadata\n---\nMore prose";
+    assert!(extract_end_matter(content).is_none());
+
+    // Prose between two horizontal rules isn't a mapping
+    let content = "Intro\n\n---\n\nJust a closing paragraph.\n\n---\n";
+    assert!(extract_end_matter(content).is_none());
+}
+
+#[test]
+fn test_extract_frontmatter_crlf() {
+    let content = "---\r\ntitle: Windows Note\r\ntags:\r\n  - rust\r\n  - cli\r\n---\r\n# Body\r\n";
+    let path = PathBuf::from("test.md");
+    let fm = extract_frontmatter(content, &path).unwrap();
+
+    assert_eq!(fm.title.as_deref(), Some("Windows Note"));
+    match fm.tags {
+        Some(TagValue::Array(tags)) => assert_eq!(tags, vec!["rust", "cli"]),
+        other => panic!("Expected array of tags, got {:?}", other),
+    }
+
+    // Block scalars keep no stray carriage returns
+    let content = "---\r\ntitle: |\r\n  Two\r\n  Lines\r\n---\r\n";
+    let fm = extract_frontmatter(content, &path).unwrap();
+    assert_eq!(fm.title.as_deref(), Some("Two\nLines"));
+}
+
+#[test]
+fn test_extract_frontmatter_formats() {
+    let path = PathBuf::from("test.qmd");
+    let title_of = |content: &str, format| {
+        extract_frontmatter_as(content, &path, format).and_then(|fm| fm.title)
+    };
+
+    // Auto goes by the fence
+    let toml = "+++\ntitle = \"From TOML\"\ntags = [\"rust\"]\ndate = 2024-05-01\n+++\n";
+    let fm = extract_frontmatter(toml, &path).unwrap();
+    assert_eq!(fm.title.as_deref(), Some("From TOML"));
+    assert!(fm.tags.unwrap().contains_tag("rust", ""));
+    // TOML dates read as the same string YAML would give
+    assert_eq!(fm.extra["date"], serde_yaml::Value::from("2024-05-01"));
+
+    // Forced formats parse whatever the fence holds
+    let toml_in_dashes = "---\ntitle = \"Dashes\"\n---\n";
+    assert_eq!(title_of(toml_in_dashes, FrontmatterFormat::Auto), None);
+    assert_eq!(
+        title_of(toml_in_dashes, FrontmatterFormat::Toml).as_deref(),
+        Some("Dashes")
+    );
+    let json = "---\n{\"title\": \"From JSON\", \"tags\": [\"a\", \"b\"]}\n---\n";
+    assert_eq!(
+        title_of(json, FrontmatterFormat::Json).as_deref(),
+        Some("From JSON")
+    );
+    let yaml_in_pluses = "+++\ntitle: Pluses\n+++\n";
+    assert_eq!(
+        title_of(yaml_in_pluses, FrontmatterFormat::Yaml).as_deref(),
+        Some("Pluses")
+    );
+
+    // A block is still required
+    assert!(extract_frontmatter_as("title = \"x\"\n", &path, FrontmatterFormat::Toml).is_none());
+}
+
+#[test]
+fn test_strip_frontmatter() {
+    assert_eq!(
+        strip_frontmatter("---\ntitle: Test\n---\n# Body\ntext\n"),
+        "# Body\ntext\n"
+    );
+    // The preamble line before the fence goes too
+    assert_eq!(
+        strip_frontmatter("<!-- note -->\n+++\ntitle = \"T\"\n+++\nBody"),
+        "Body"
+    );
+    // Without frontmatter the content is unchanged
+    assert_eq!(
+        strip_frontmatter("<!-- note -->\n# Body\n"),
+        "<!-- note -->\n# Body\n"
+    );
+    assert_eq!(
+        strip_frontmatter("# Body\n---\nrule\n"),
+        "# Body\n---\nrule\n"
+    );
 }
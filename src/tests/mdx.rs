@@ -0,0 +1,91 @@
+use crate::*;
+use std::io::Write;
+use std::path::PathBuf;
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_extract_mdx_meta_title() {
+    let content = "import Chart from './chart'\n\nexport const meta = {\n  title: \"Quarterly Report\",\n  tags: ['finance', 'q3'],\n  status: 'draft',\n};\n\n# Report";
+    let path = PathBuf::from("test.mdx");
+    let fm = extract_mdx_meta(content, &path);
+
+    assert!(fm.is_some());
+    let fm = fm.unwrap();
+    assert_eq!(fm.title, Some("Quarterly Report".to_string()));
+    assert!(fm.tags.unwrap().contains_tag("finance", ""));
+    assert!(fm.extra.contains_key("status"));
+}
+
+#[test]
+fn test_extract_mdx_meta_single_line_and_frontmatter_name() {
+    let content = "export const frontmatter = {title:\"Inline\", draft: true}\n\n# Body";
+    let path = PathBuf::from("test.mdx");
+    let fm = extract_mdx_meta(content, &path).unwrap();
+
+    assert_eq!(fm.title, Some("Inline".to_string()));
+    assert!(fm.extra.contains_key("draft"));
+}
+
+#[test]
+fn test_extract_mdx_meta_skips_comments_and_braces_in_strings() {
+    let content = "export const meta = {\n  // internal note\n  title: \"Braces {in} title\",\n}\n";
+    let path = PathBuf::from("test.mdx");
+    let fm = extract_mdx_meta(content, &path).unwrap();
+
+    assert_eq!(fm.title, Some("Braces {in} title".to_string()));
+}
+
+#[test]
+fn test_extract_mdx_meta_none_without_export() {
+    let content = "# Plain markdown\n\nexport default Layout";
+    let path = PathBuf::from("test.mdx");
+
+    assert!(extract_mdx_meta(content, &path).is_none());
+}
+
+#[test]
+fn test_mdx_meta_read_beyond_head_lines() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    writeln!(temp_file, "export const meta = {{").unwrap();
+    for i in 0..5 {
+        writeln!(temp_file, "  field{}: {},", i, i).unwrap();
+    }
+    writeln!(temp_file, "  title: 'Late Title',").unwrap();
+    writeln!(temp_file, "}}").unwrap();
+    writeln!(temp_file, "# Body").unwrap();
+    temp_file.flush().unwrap();
+
+    let options = ReadOptions {
+        head_lines: 2,
+        mdx: true,
+        ..Default::default()
+    };
+    let metadata = Metadata::from_file_with_options(temp_file.path(), &options).unwrap();
+    assert!(metadata.has_title("late title"));
+
+    // Without --mdx the export block is neither read in full nor parsed
+    let metadata = Metadata::from_file(temp_file.path(), 2, false, false).unwrap();
+    assert!(!metadata.has_title("late title"));
+}
+
+#[test]
+fn test_frontmatter_format_mdx_reads_the_export() {
+    let mut temp_file = NamedTempFile::new().unwrap();
+    write!(
+        temp_file,
+        "---\ntitle: Fenced\n---\nexport const meta = {{\n  title: 'Exported',\n  tags: [\"react\"],\n}};\n"
+    )
+    .unwrap();
+    temp_file.flush().unwrap();
+
+    let options = ReadOptions {
+        head_lines: 10,
+        frontmatter_format: FrontmatterFormat::Mdx,
+        mdx: true,
+        ..Default::default()
+    };
+    let metadata = Metadata::from_file_with_options(temp_file.path(), &options).unwrap();
+    assert!(metadata.has_title("exported"));
+    assert!(!metadata.has_title("fenced"));
+    assert_eq!(metadata.yaml_tags(), vec!["react"]);
+}
@@ -8,7 +8,7 @@ fn test_metadata_has_tag_yaml() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     let (pattern, regex) = (
@@ -19,7 +19,7 @@ fn test_metadata_has_tag_yaml() {
             .unwrap(),
     );
 
-    assert!(metadata.has_tag(&pattern, &regex));
+    assert!(metadata.has_tag(&pattern, &regex, &TagOptions::default()));
 }
 
 #[test]
@@ -27,7 +27,7 @@ fn test_metadata_has_tag_inline() {
     let content = "# Title\n\ntags: #rust #cli";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     let (pattern, regex) = (
@@ -38,7 +38,7 @@ fn test_metadata_has_tag_inline() {
             .unwrap(),
     );
 
-    assert!(metadata.has_tag(&pattern, &regex));
+    assert!(metadata.has_tag(&pattern, &regex, &TagOptions::default()));
 }
 
 #[test]
@@ -48,7 +48,7 @@ fn test_metadata_has_title_yaml() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     assert!(metadata.has_title("meeting"));
@@ -60,7 +60,7 @@ fn test_metadata_has_title_markdown() {
     let content = "# Meeting Notes 2025\n\nContent here";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     assert!(metadata.has_title("meeting"));
@@ -75,12 +75,12 @@ fn test_metadata_has_field() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
-    assert!(metadata.has_field("author", "john"));
-    assert!(metadata.has_field("status", "draft"));
-    assert!(!metadata.has_field("author", "jane"));
+    assert!(metadata.has_field("author", "john", FieldMatchOptions::default()));
+    assert!(metadata.has_field("status", "draft", FieldMatchOptions::default()));
+    assert!(!metadata.has_field("author", "jane", FieldMatchOptions::default()));
 }
 
 #[test]
@@ -97,6 +97,16 @@ fn test_matches_filename_with_regex() {
     assert!(!matches_filename(&path2, &regex));
 }
 
+#[test]
+fn test_matches_stem_ignores_extension() {
+    let regex = regex::Regex::new("2024$").unwrap();
+    let path = PathBuf::from("journal/2024.md");
+
+    assert!(matches_stem(&path, &regex));
+    assert!(!matches_filename(&path, &regex));
+    assert!(!matches_stem(&PathBuf::from("2024/notes.md"), &regex));
+}
+
 // Edge case tests
 
 #[test]
@@ -104,7 +114,7 @@ fn test_metadata_has_title_multiple_headings() {
     let content = "# First Heading\n\n## Second Heading\n\n# Third Heading";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     // Should match first level 1 heading
@@ -117,7 +127,7 @@ fn test_metadata_has_title_heading_with_trailing_hashes() {
     let content = "## Title Here ##\n\nContent";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     assert!(metadata.has_title("title"));
@@ -129,7 +139,7 @@ fn test_metadata_has_title_heading_no_space() {
     let content = "##TitleNoSpace\n\nContent";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     // May or may not match depending on implementation
@@ -142,7 +152,7 @@ fn test_metadata_has_title_empty_heading() {
     let content = "# \n\nContent";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     assert!(!metadata.has_title("anything"));
@@ -155,7 +165,7 @@ fn test_metadata_has_title_unicode() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     assert!(metadata.has_title("测试"));
@@ -167,7 +177,7 @@ fn test_metadata_has_tag_word_boundaries() {
     let content = "This is about #rust and #rustlang";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     let (pattern, regex) = (
@@ -179,7 +189,7 @@ fn test_metadata_has_tag_word_boundaries() {
     );
 
     // Should match #rust but not #rustlang
-    assert!(metadata.has_tag(&pattern, &regex));
+    assert!(metadata.has_tag(&pattern, &regex, &TagOptions::default()));
 }
 
 #[test]
@@ -187,7 +197,7 @@ fn test_metadata_has_tag_start_of_line() {
     let content = "#rust at start\nMiddle #rust text";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     let (pattern, regex) = (
@@ -198,7 +208,7 @@ fn test_metadata_has_tag_start_of_line() {
             .unwrap(),
     );
 
-    assert!(metadata.has_tag(&pattern, &regex));
+    assert!(metadata.has_tag(&pattern, &regex, &TagOptions::default()));
 }
 
 #[test]
@@ -206,7 +216,7 @@ fn test_metadata_has_tag_with_numbers() {
     let content = "Tags: #rust2024 #rust-2024";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     let (pattern, regex) = (
@@ -219,7 +229,7 @@ fn test_metadata_has_tag_with_numbers() {
 
     // Should not match rust2024 (word boundary), but SHOULD match rust-2024
     // because '-' is not a word character, so the pattern matches
-    assert!(metadata.has_tag(&pattern, &regex));
+    assert!(metadata.has_tag(&pattern, &regex, &TagOptions::default()));
 }
 
 #[test]
@@ -229,7 +239,7 @@ fn test_metadata_has_tag_special_chars() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     let (pattern, regex) = (
@@ -240,7 +250,7 @@ fn test_metadata_has_tag_special_chars() {
             .unwrap(),
     );
 
-    assert!(metadata.has_tag(&pattern, &regex));
+    assert!(metadata.has_tag(&pattern, &regex, &TagOptions::default()));
 }
 
 #[test]
@@ -250,12 +260,12 @@ fn test_metadata_has_field_nested_object() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     // yaml_value_contains doesn't recursively search nested objects
     // so we test with a flat field instead
-    assert!(metadata.has_field("status", "active"));
+    assert!(metadata.has_field("status", "active", FieldMatchOptions::default()));
 }
 
 #[test]
@@ -265,11 +275,11 @@ fn test_metadata_has_field_array_value() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
-    assert!(metadata.has_field("categories", "tech"));
-    assert!(metadata.has_field("categories", "rust"));
+    assert!(metadata.has_field("categories", "tech", FieldMatchOptions::default()));
+    assert!(metadata.has_field("categories", "rust", FieldMatchOptions::default()));
 }
 
 #[test]
@@ -279,11 +289,11 @@ fn test_metadata_has_field_number_value() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
-    assert!(metadata.has_field("version", "42"));
-    assert!(metadata.has_field("rating", "4.5"));
+    assert!(metadata.has_field("version", "42", FieldMatchOptions::default()));
+    assert!(metadata.has_field("rating", "4.5", FieldMatchOptions::default()));
 }
 
 #[test]
@@ -293,11 +303,11 @@ fn test_metadata_has_field_boolean_value() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
-    assert!(metadata.has_field("published", "true"));
-    assert!(metadata.has_field("draft", "false"));
+    assert!(metadata.has_field("published", "true", FieldMatchOptions::default()));
+    assert!(metadata.has_field("draft", "false", FieldMatchOptions::default()));
 }
 
 #[test]
@@ -307,11 +317,15 @@ fn test_metadata_has_field_case_insensitive() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
-    assert!(metadata.has_field("status", "active"));
-    assert!(metadata.has_field("status", &"ACTIVE".to_lowercase()));
+    assert!(metadata.has_field("status", "active", FieldMatchOptions::default()));
+    assert!(metadata.has_field(
+        "status",
+        &"ACTIVE".to_lowercase(),
+        FieldMatchOptions::default()
+    ));
 }
 
 #[test]
@@ -321,11 +335,11 @@ fn test_metadata_has_field_empty_value() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     // Empty field should not match anything
-    assert!(!metadata.has_field("status", "anything"));
+    assert!(!metadata.has_field("status", "anything", FieldMatchOptions::default()));
 }
 
 #[test]
@@ -335,10 +349,10 @@ fn test_metadata_has_field_nonexistent() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
-    assert!(!metadata.has_field("nonexistent", "value"));
+    assert!(!metadata.has_field("nonexistent", "value", FieldMatchOptions::default()));
 }
 
 #[test]
@@ -368,6 +382,30 @@ fn test_matches_filename_with_path() {
     assert!(matches_filename(&path, &regex));
 }
 
+#[test]
+fn test_matches_path_directory_component() {
+    let regex = regex::Regex::new("drafts/").unwrap();
+
+    assert!(matches_path(&PathBuf::from("notes/drafts/idea.md"), &regex));
+    assert!(matches_path(&PathBuf::from("drafts/idea.md"), &regex));
+    assert!(!matches_path(&PathBuf::from("notes/drafts.md"), &regex));
+    // Filename-only matching can't see directories
+    assert!(!matches_filename(
+        &PathBuf::from("notes/drafts/idea.md"),
+        &regex
+    ));
+}
+
+#[test]
+fn test_matches_path_normalizes_separators() {
+    let regex = regex::Regex::new("^notes/drafts/").unwrap();
+
+    assert!(matches_path(
+        &PathBuf::from("notes\\drafts\\idea.md"),
+        &regex
+    ));
+}
+
 #[test]
 fn test_matches_filename_special_chars() {
     let regex = regex::RegexBuilder::new(r"file\[1\]")
@@ -385,7 +423,7 @@ fn test_metadata_has_title_level_2_heading() {
     let content = "## Second Level Heading\n\nContent";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     // Should match level 2 headings as well
@@ -399,7 +437,7 @@ fn test_metadata_has_title_both_yaml_and_markdown() {
     let fm = extract_frontmatter(content, &path);
     let metadata = Metadata {
         frontmatter: fm,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     // Should match both
@@ -412,7 +450,7 @@ fn test_metadata_no_frontmatter_no_title() {
     let content = "Just plain content without headings";
     let metadata = Metadata {
         frontmatter: None,
-        raw_content: content.to_string(),
+        raw_content: content.to_string().into(),
     };
 
     assert!(!metadata.has_title("content"));
@@ -451,3 +489,397 @@ fn test_matches_filename_parent_directory() {
     let current_path = PathBuf::from(".");
     assert!(!matches_filename(&current_path, &regex));
 }
+
+#[test]
+fn test_metadata_field_values() {
+    let content = "---\nstatus: active\ntopics: [rust, cli]\ncount: 3\n---\n# Content";
+    let path = PathBuf::from("test.md");
+    let metadata = Metadata {
+        frontmatter: extract_frontmatter(content, &path),
+        raw_content: content.to_string().into(),
+    };
+
+    assert_eq!(metadata.field_values("status", false), vec!["active"]);
+    assert_eq!(metadata.field_values("topics", false), vec!["rust", "cli"]);
+    assert_eq!(metadata.field_values("count", false), vec!["3"]);
+    assert!(metadata.field_values("missing", false).is_empty());
+}
+
+#[test]
+fn test_metadata_field_values_inline() {
+    let content = "# Note\n\nstatus: draft\n";
+    let metadata = Metadata {
+        frontmatter: None,
+        raw_content: content.to_string().into(),
+    };
+
+    assert_eq!(metadata.field_values("status", false), vec!["draft"]);
+
+    // With --inline-metadata-prefix, indented lines aren't inline fields
+    let indented = Metadata {
+        frontmatter: None,
+        raw_content: "# Note\n\n    status: draft\n".to_string().into(),
+    };
+    assert_eq!(indented.field_values("status", false), vec!["draft"]);
+    assert!(indented.field_values("status", true).is_empty());
+}
+
+#[test]
+fn test_metadata_has_tag_glob() {
+    let (yaml_regex, inline_regex) = compile_tag_glob("proj-*", "").unwrap();
+    let path = PathBuf::from("test.md");
+
+    let cases = vec![
+        // Flat YAML tags
+        ("---\ntags: [proj-alpha]\n---", true),
+        ("---\ntags: [PROJ-Beta]\n---", true),
+        // Hierarchical YAML tags
+        ("---\ntags: [work/proj-gamma]\n---", true),
+        ("---\ntags: [proj-delta/backend]\n---", true),
+        // The glob must match the whole tag, not a substring
+        ("---\ntags: [myproj-alpha]\n---", false),
+        ("---\ntags: [project]\n---", false),
+        // Inline tags, flat and hierarchical
+        ("# Note\n\n#proj-alpha #other", true),
+        ("# Note\n\n#work/proj-beta", true),
+        ("# Note\n\n#myproj-alpha", false),
+    ];
+
+    for (content, expected) in cases {
+        let metadata = Metadata {
+            frontmatter: extract_frontmatter(content, &path),
+            raw_content: content.to_string().into(),
+        };
+        assert_eq!(
+            metadata.has_tag_glob(&yaml_regex, &inline_regex, &TagOptions::default()),
+            expected,
+            "Unexpected result for: {}",
+            content
+        );
+    }
+}
+
+#[test]
+fn test_compile_tag_glob_question_mark() {
+    let (yaml_regex, _) = compile_tag_glob("#v?", "").unwrap();
+    assert!(yaml_regex.is_match("v1"));
+    assert!(!yaml_regex.is_match("v10"));
+    // Other regex metacharacters stay literal
+    let (yaml_regex, _) = compile_tag_glob("c++", "").unwrap();
+    assert!(yaml_regex.is_match("C++"));
+}
+
+#[test]
+fn test_metadata_has_field_key() {
+    let content = "---\ntitle: Note\nstatus: \"\"\n---\n# Body";
+    let path = PathBuf::from("test.md");
+    let metadata = Metadata {
+        frontmatter: extract_frontmatter(content, &path),
+        raw_content: content.to_string().into(),
+    };
+
+    assert!(metadata.has_field_key("status", false));
+    assert!(metadata.has_field_key("title", false));
+    assert!(!metadata.has_field_key("date", false));
+    assert!(!metadata.has_field_key("tags", false));
+
+    let inline = Metadata {
+        frontmatter: None,
+        raw_content: "# Note\ndate: 2025-01-15\n".to_string().into(),
+    };
+    assert!(inline.has_field_key("date", false));
+    assert!(inline.has_field_key("Date", false));
+}
+
+#[test]
+fn test_metadata_first_paragraph() {
+    let content = "---\ntitle: Note\n---\n\n# Heading\n\nFirst line of prose\ncontinues here.\n\nSecond paragraph.";
+    let path = PathBuf::from("test.md");
+    let metadata = Metadata {
+        frontmatter: extract_frontmatter(content, &path),
+        raw_content: content.to_string().into(),
+    };
+    assert_eq!(
+        metadata.first_paragraph(),
+        Some("First line of prose continues here.".to_string())
+    );
+
+    // A heading right after the prose ends the paragraph
+    let metadata = Metadata {
+        frontmatter: None,
+        raw_content: "# Title\nIntro text\n## Section".to_string().into(),
+    };
+    assert_eq!(metadata.first_paragraph(), Some("Intro text".to_string()));
+
+    let metadata = Metadata {
+        frontmatter: None,
+        raw_content: "# Only a heading\n\n".to_string().into(),
+    };
+    assert_eq!(metadata.first_paragraph(), None);
+}
+
+#[test]
+fn test_metadata_has_title_regex_heading() {
+    let content = "# 2024-05-01 Standup ##\n\nNotes";
+    let metadata = Metadata {
+        frontmatter: None,
+        raw_content: content.to_string().into(),
+    };
+
+    assert!(metadata.has_title_regex(&Regex::new(r"^\d{4}-\d{2}").unwrap()));
+    assert!(metadata.has_title_regex(&Regex::new(r"Standup$").unwrap()));
+    assert!(!metadata.has_title_regex(&Regex::new(r"^Standup").unwrap()));
+}
+
+#[test]
+fn test_metadata_has_title_regex_yaml() {
+    let content = "---\ntitle: Weekly Review\n---\n# Body";
+    let path = PathBuf::from("test.md");
+    let fm = extract_frontmatter(content, &path);
+    let metadata = Metadata {
+        frontmatter: fm,
+        raw_content: content.to_string().into(),
+    };
+
+    assert!(metadata.has_title_regex(&Regex::new(r"^Weekly").unwrap()));
+    assert!(!metadata.has_title_regex(&Regex::new(r"^\d{4}").unwrap()));
+}
+
+#[test]
+fn test_metadata_has_heading_level() {
+    let content = "# Meeting\n\n## Agenda\n\n### Notes";
+    let metadata = Metadata {
+        frontmatter: None,
+        raw_content: content.to_string().into(),
+    };
+
+    assert!(metadata.has_heading(Some(2), "agenda"));
+    assert!(metadata.has_heading(None, "agenda"));
+    // Same text at the wrong level doesn't match
+    assert!(!metadata.has_heading(Some(1), "agenda"));
+    assert!(!metadata.has_heading(Some(3), "agenda"));
+    assert!(!metadata.has_heading(Some(2), "notes"));
+}
+
+#[test]
+fn test_parse_heading_spec() {
+    assert_eq!(
+        parse_heading_spec("2:Agenda").unwrap(),
+        (Some(2), "agenda".to_string())
+    );
+    assert_eq!(
+        parse_heading_spec("Agenda").unwrap(),
+        (None, "agenda".to_string())
+    );
+    // A non-numeric prefix is part of the text
+    assert_eq!(
+        parse_heading_spec("Note: draft").unwrap(),
+        (None, "note: draft".to_string())
+    );
+    assert!(parse_heading_spec("7:Agenda").is_err());
+    assert!(parse_heading_spec("0:Agenda").is_err());
+}
+
+#[test]
+fn test_metadata_link_targets() {
+    let content = "See [docs](https://example.com/guide \"Guide\") and [prev](../x.md).\n\
+                   ![diagram](<img/flow chart.png>) links [[Other Note|alias]] and [[Topic#Section]].";
+    let metadata = Metadata {
+        frontmatter: None,
+        raw_content: content.to_string().into(),
+    };
+
+    assert_eq!(
+        metadata.link_targets(),
+        vec![
+            "https://example.com/guide",
+            "../x.md",
+            "img/flow chart.png",
+            "Other Note",
+            "Topic"
+        ]
+    );
+}
+
+#[test]
+fn test_metadata_has_title_skips_code_fences() {
+    let content = "Setup notes\n\n```bash\n# not a title\nmake\n```\n\n~~~~\n# also not\n~~~~\n\n## Real Heading";
+    let metadata = Metadata {
+        frontmatter: None,
+        raw_content: content.to_string().into(),
+    };
+
+    assert!(!metadata.has_title("not a title"));
+    assert!(!metadata.has_title("also not"));
+    assert!(metadata.has_title("real heading"));
+    assert!(!metadata.has_title_regex(&Regex::new("^not").unwrap()));
+}
+
+#[test]
+fn test_lines_outside_code_fences() {
+    // A shorter or different marker doesn't close the fence
+    let content = "a\n````\n```\n~~~\nb\n````\nc\n```\nunclosed";
+    assert_eq!(
+        lines_outside_code_fences(content).collect::<Vec<_>>(),
+        vec!["a", "c"]
+    );
+}
+
+#[test]
+fn test_metadata_summary() {
+    let content = "---\ntitle: Essay\n---\n# On Walking\n\nWalking is the best way\nto think.\n\nSecond paragraph.";
+    let metadata = Metadata {
+        frontmatter: None,
+        raw_content: content.to_string().into(),
+    };
+
+    assert_eq!(
+        metadata.summary(200).as_deref(),
+        Some("Walking is the best way to think.")
+    );
+    // Truncated excerpts end with an ellipsis and stay within the limit
+    let short = metadata.summary(13).unwrap();
+    assert_eq!(short, "Walking is t…");
+    assert_eq!(short.chars().count(), 13);
+
+    let empty = Metadata {
+        frontmatter: None,
+        raw_content: "# Only a heading".to_string().into(),
+    };
+    assert_eq!(empty.summary(200), None);
+}
+
+#[test]
+fn test_metadata_has_tag_with_prefix() {
+    let path = PathBuf::from("test.md");
+    let (pattern, regex) = compile_tag_pattern("rust", "team/").unwrap();
+    let options = TagOptions {
+        prefix: "team/".to_string(),
+        ..Default::default()
+    };
+
+    let cases = vec![
+        // Tags inside the namespace match the unprefixed query
+        ("---\ntags: [team/rust, team/ops]\n---", true),
+        ("---\ntags: [Team/Rust]\n---", true),
+        ("# Note\n\n#team/rust", true),
+        // Tags outside it don't, even when they contain the query
+        ("---\ntags: [rust]\n---", false),
+        ("---\ntags: [other/rust]\n---", false),
+        ("---\ntags: [myteam/rust]\n---", false),
+        ("# Note\n\n#rust #other/rust", false),
+    ];
+
+    for (content, expected) in cases {
+        let metadata = Metadata {
+            frontmatter: extract_frontmatter(content, &path),
+            raw_content: content.to_string().into(),
+        };
+        assert_eq!(
+            metadata.has_tag(&pattern, &regex, &options),
+            expected,
+            "{}",
+            content
+        );
+    }
+}
+
+#[test]
+fn test_metadata_has_tag_in_tag_fields() {
+    let content = "---\ncategories: [news, \" world \"]\nkeywords: release\n---\n";
+    let path = PathBuf::from("test.md");
+    let metadata = Metadata {
+        frontmatter: extract_frontmatter(content, &path),
+        raw_content: content.to_string().into(),
+    };
+    let (pattern, regex) = compile_tag_pattern("news", "").unwrap();
+    let options = TagOptions {
+        fields: vec!["categories".to_string(), "keywords".to_string()],
+        ..Default::default()
+    };
+
+    // Only consulted when registered with --tag-field
+    assert!(!metadata.has_tag(&pattern, &regex, &TagOptions::default()));
+    assert!(metadata.has_tag(&pattern, &regex, &options));
+    let (pattern, regex) = compile_tag_pattern("release", "").unwrap();
+    assert!(metadata.has_tag(&pattern, &regex, &options));
+
+    let (yaml_regex, inline_regex) = compile_tag_glob("wor*", "").unwrap();
+    assert!(metadata.has_tag_glob(&yaml_regex, &inline_regex, &options));
+}
+
+#[test]
+fn test_metadata_effective_title() {
+    let path = PathBuf::from("test.md");
+    let title_of = |content: &str| {
+        let metadata = Metadata {
+            frontmatter: extract_frontmatter(content, &path),
+            raw_content: content.to_string().into(),
+        };
+        metadata.effective_title().map(str::to_string)
+    };
+
+    // The YAML title wins over headings
+    assert_eq!(
+        title_of("---\ntitle: From YAML\n---\n# From Heading\n").as_deref(),
+        Some("From YAML")
+    );
+    // Without one, the first H1 is used, ATX or Setext
+    assert_eq!(
+        title_of("---\ntags: [a]\n---\n## Intro\n# Main Title #\n").as_deref(),
+        Some("Main Title")
+    );
+    assert_eq!(
+        title_of("Setext Title\n============\n\nBody\n").as_deref(),
+        Some("Setext Title")
+    );
+    // Comments in the frontmatter and fenced code are not headings
+    assert_eq!(
+        title_of("---\n# comment: x\n---\n```sh\n# not a title\n```\n"),
+        None
+    );
+    assert_eq!(title_of("Just prose.\n\n## Only H2\n"), None);
+}
+
+#[test]
+fn test_metadata_resolved_title() {
+    let path = PathBuf::from("notes/2024-q1-plan.md");
+    let title_of = |content: &str| {
+        let metadata = Metadata {
+            frontmatter: extract_frontmatter(content, &path),
+            raw_content: content.to_string().into(),
+        };
+        metadata.resolved_title(&path)
+    };
+
+    assert_eq!(
+        title_of("---\ntitle: From YAML\n---\n# From Heading\n"),
+        "From YAML"
+    );
+    assert_eq!(title_of("# From Heading\n\nBody\n"), "From Heading");
+    // Without a title or H1, the file name is used
+    assert_eq!(
+        title_of("---\ntags: [a]\n---\nJust prose.\n"),
+        "2024-q1-plan"
+    );
+}
+
+#[test]
+fn test_metadata_inline_tags() {
+    let content = "---\ntags: [yaml]\n---\n# Heading\nSome #rust and #work/ops, issue #42\nSee [docs](#setup) or https://example.com/#frag\n```\n#not-a-tag\n```\n#last";
+    let metadata = Metadata {
+        frontmatter: None,
+        raw_content: content.to_string().into(),
+    };
+
+    assert_eq!(metadata.inline_tags(), vec!["rust", "work/ops", "last"]);
+}
+
+#[test]
+fn test_task_counts() {
+    let metadata = Metadata {
+        frontmatter: None,
+        raw_content: "# Todo\n- [ ] write docs\n  - [x] draft outline\n* [X] review\n- [] not a task\n```\n- [ ] in code\n```\n".to_string().into(),
+    };
+    assert_eq!(metadata.task_counts(), (1, 2));
+}
@@ -10,5 +10,17 @@ mod enumeration;
 mod file_ops;
 mod filters;
 mod frontmatter;
+mod mdx;
 mod metadata;
+mod output;
+mod sorting;
 mod yaml_helpers;
+
+use crate::Args;
+use clap::Parser;
+
+/// Builds `Args` with every option at its command-line default.
+/// Tests override only the fields they care about via struct update syntax.
+fn default_args() -> Args {
+    Args::parse_from(["fmd"])
+}
@@ -0,0 +1,262 @@
+use super::default_args;
+use crate::*;
+use std::path::PathBuf;
+
+// Helper to build a match from file content
+fn create_test_match(content: &str) -> FileMatch {
+    let path = PathBuf::from("notes/test.md");
+    FileMatch {
+        metadata: Some(Metadata {
+            frontmatter: extract_frontmatter(content, &path),
+            raw_content: content.to_string().into(),
+        }),
+        path,
+    }
+}
+
+// Helper to build a highlighter with color forced on or off
+fn create_highlighter(titles: &[&str], tags: &[&str], color: ColorChoice) -> Highlighter {
+    let args = Args {
+        titles: titles.iter().map(|t| t.to_string()).collect(),
+        tags: tags.iter().map(|t| t.to_string()).collect(),
+        color,
+        ..default_args()
+    };
+    Highlighter::from_args(&args).unwrap()
+}
+
+#[test]
+fn test_output_template_parse() {
+    let template = OutputTemplate::parse("{path}\\t{title} by {author}").unwrap();
+    assert_eq!(
+        template.segments,
+        vec![
+            TemplateSegment::Path,
+            TemplateSegment::Literal("\t".to_string()),
+            TemplateSegment::Title,
+            TemplateSegment::Literal(" by ".to_string()),
+            TemplateSegment::Author,
+        ]
+    );
+}
+
+#[test]
+fn test_output_template_unknown_placeholder() {
+    let result = OutputTemplate::parse("{path} {nope}");
+    assert!(result.is_err());
+    assert!(result.unwrap_err().to_string().contains("{nope}"));
+}
+
+#[test]
+fn test_output_template_render() {
+    let file_match =
+        create_test_match("---\ntitle: Weekly Sync\nauthor: Jane\ntags: [work, meeting]\n---\n");
+    let highlighter = create_highlighter(&[], &[], ColorChoice::Never);
+
+    let template = OutputTemplate::parse("{path}|{title}|{tags}|{author}").unwrap();
+    assert_eq!(
+        template.render(&file_match, &highlighter),
+        "notes/test.md|Weekly Sync|work,meeting|Jane"
+    );
+
+    // Missing fields render empty; the title falls back to the first H1
+    let bare = create_test_match("# Just a heading");
+    assert_eq!(
+        template.render(&bare, &highlighter),
+        "notes/test.md|Just a heading||"
+    );
+    // ... and then to the file name, so it is never empty
+    let untitled = create_test_match("Only prose");
+    assert_eq!(
+        template.render(&untitled, &highlighter),
+        "notes/test.md|test||"
+    );
+}
+
+#[test]
+fn test_highlighter_title_spans() {
+    let highlighter = create_highlighter(&["sync"], &[], ColorChoice::Always);
+    let highlighted = highlighter.title("Weekly Sync");
+    assert!(highlighted.starts_with("Weekly \u{1b}["));
+    assert!(highlighted.contains("Sync"));
+
+    let plain = create_highlighter(&["sync"], &[], ColorChoice::Never);
+    assert_eq!(plain.title("Weekly Sync"), "Weekly Sync");
+}
+
+#[test]
+fn test_highlighter_tags() {
+    let highlighter = create_highlighter(&[], &["#Work"], ColorChoice::Always);
+    assert!(highlighter.tag("work").contains('\u{1b}'));
+    assert_eq!(highlighter.tag("meeting"), "meeting");
+}
+
+#[test]
+fn test_highlighter_path_name_spans() {
+    let args = Args {
+        names: vec!["^note".to_string()],
+        color: ColorChoice::Always,
+        ..default_args()
+    };
+    let highlighter = Highlighter::from_args(&args).unwrap();
+
+    // Only the file name is searched, so the "note" in the directory stays plain
+    let highlighted = highlighter.path(Path::new("notes/notes-2024.md"));
+    assert!(highlighted.starts_with("notes/\u{1b}["));
+    assert!(highlighted.ends_with("-2024.md"));
+
+    let plain = Highlighter::from_args(&Args {
+        color: ColorChoice::Never,
+        ..args
+    })
+    .unwrap();
+    assert_eq!(
+        plain.path(Path::new("notes/notes-2024.md")),
+        "notes/notes-2024.md"
+    );
+}
+
+#[test]
+fn test_use_color_never_for_machine_output() {
+    let json = Args {
+        color: ColorChoice::Always,
+        json: true,
+        ..default_args()
+    };
+    assert!(!use_color(&json));
+
+    let nul = Args {
+        color: ColorChoice::Always,
+        nul: true,
+        ..default_args()
+    };
+    assert!(!use_color(&nul));
+
+    let separator = Args {
+        color: ColorChoice::Always,
+        separator: Some(";".to_string()),
+        ..default_args()
+    };
+    assert!(!use_color(&separator));
+}
+
+#[test]
+fn test_group_matches_by_tag() {
+    let mut a = create_test_match("---\ntags: [rust, cli]\n---\n");
+    a.path = PathBuf::from("a.md");
+    let mut b = create_test_match("---\ntags: rust\n---\n");
+    b.path = PathBuf::from("b.md");
+    let mut untagged = create_test_match("# No tags");
+    untagged.path = PathBuf::from("c.md");
+
+    let groups = group_matches(
+        &[a, b, untagged],
+        GroupBy::Tag,
+        &TagOptions::default(),
+        false,
+    );
+    let keys: Vec<&str> = groups.keys().map(String::as_str).collect();
+    assert_eq!(keys, vec!["cli", "rust"]);
+    assert_eq!(
+        groups["rust"],
+        vec![PathBuf::from("a.md"), PathBuf::from("b.md")]
+    );
+    assert_eq!(groups["cli"], vec![PathBuf::from("a.md")]);
+}
+
+#[test]
+fn test_group_matches_by_tag_uses_tag_options() {
+    let mut a = create_test_match("---\ntags: [topic/rust, misc]\n---\nSee #topic/cli\n");
+    a.path = PathBuf::from("a.md");
+    let mut b = create_test_match("---\nkeywords: [Topic/rust]\n---\n");
+    b.path = PathBuf::from("b.md");
+
+    let options = TagOptions {
+        prefix: "topic/".to_string(),
+        fields: vec!["keywords".to_string()],
+    };
+    let groups = group_matches(&[a, b], GroupBy::Tag, &options, false);
+    let keys: Vec<&str> = groups.keys().map(String::as_str).collect();
+    assert_eq!(keys, vec!["cli", "rust"]);
+    assert_eq!(
+        groups["rust"],
+        vec![PathBuf::from("a.md"), PathBuf::from("b.md")]
+    );
+}
+
+#[test]
+fn test_group_matches_by_author() {
+    let jane = create_test_match("---\nauthor: Jane\n---\n");
+    let inline = create_test_match("# Note\nauthor: Bob\n");
+
+    let groups = group_matches(
+        &[jane, inline],
+        GroupBy::Author,
+        &TagOptions::default(),
+        false,
+    );
+    let keys: Vec<&str> = groups.keys().map(String::as_str).collect();
+    assert_eq!(keys, vec!["Bob", "Jane"]);
+}
+
+#[test]
+fn test_output_template_description() {
+    let file_match = create_test_match("---\ntitle: Post\n---\n# Post\n\nA short summary.\n");
+    let highlighter = create_highlighter(&[], &[], ColorChoice::Never);
+
+    let template = OutputTemplate::parse("{title}: {description}").unwrap();
+    assert_eq!(
+        template.render(&file_match, &highlighter),
+        "Post: A short summary."
+    );
+}
+
+#[test]
+fn test_frontmatter_map() {
+    let file_match = create_test_match("---\ntitle: Plan\ntags: [project]\nstatus: active\n---\n");
+    let map = frontmatter_map(&file_match).unwrap();
+    assert_eq!(
+        map.keys().map(String::as_str).collect::<Vec<_>>(),
+        vec!["status", "tags", "title"]
+    );
+    assert_eq!(map["title"], serde_yaml::Value::from("Plan"));
+
+    let plain = create_test_match("# No frontmatter");
+    assert!(frontmatter_map(&plain).unwrap().is_empty());
+}
+
+#[test]
+fn test_match_lines() {
+    let content = "# Log\n\nauthor: Jane Doe\nWorking on #rust today.\nTODO: docs\n";
+    let file_match = create_test_match(content);
+    let metadata = file_match.metadata.as_ref().unwrap();
+    let args = Args {
+        tags: vec!["rust".to_string()],
+        authors: vec!["jane".to_string()],
+        greps: vec!["^TODO".to_string()],
+        ..default_args()
+    };
+    let filters = CompiledFilters::from_args(&args).unwrap();
+
+    assert_eq!(match_lines(metadata, &filters), vec![2, 3, 4]);
+    // No inline-capable filter, no lines
+    let filters = CompiledFilters::from_args(&default_args()).unwrap();
+    assert!(match_lines(metadata, &filters).is_empty());
+}
+
+#[test]
+fn test_collect_tags() {
+    let matches = vec![
+        create_test_match("---\ntags: [rust, cli]\ncategories: [news]\n---\nAbout #async\n"),
+        create_test_match("---\ntags: rust\n---\n"),
+        FileMatch {
+            path: PathBuf::from("unread.md"),
+            metadata: None,
+        },
+    ];
+
+    let tags: Vec<String> = collect_tags(&matches, &[]).into_iter().collect();
+    assert_eq!(tags, ["async", "cli", "rust"]);
+    let tags = collect_tags(&matches, &["categories".to_string()]);
+    assert!(tags.contains("news"));
+}
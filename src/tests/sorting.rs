@@ -0,0 +1,280 @@
+use super::default_args;
+use crate::*;
+use filetime::FileTime;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+// Helper to create a file with a fixed modification time (seconds since epoch)
+fn create_file_with_mtime(dir: &TempDir, name: &str, mtime: i64) -> PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, "# Content").unwrap();
+    filetime::set_file_mtime(&path, FileTime::from_unix_time(mtime, 0)).unwrap();
+    path
+}
+
+// Helper to sort plain paths through sort_matches
+fn sort_paths(files: &mut Vec<PathBuf>, args: &Args) {
+    let mut matches: Vec<FileMatch> = files
+        .drain(..)
+        .map(|path| FileMatch {
+            path,
+            metadata: None,
+        })
+        .collect();
+    sort_matches(&mut matches, args);
+    files.extend(matches.into_iter().map(|m| m.path));
+}
+
+#[test]
+fn sort_matches_by_path_default() {
+    let mut files = vec![
+        PathBuf::from("b.md"),
+        PathBuf::from("c.md"),
+        PathBuf::from("a.md"),
+    ];
+
+    sort_paths(&mut files, &default_args());
+    assert_eq!(
+        files,
+        vec![
+            PathBuf::from("a.md"),
+            PathBuf::from("b.md"),
+            PathBuf::from("c.md")
+        ]
+    );
+}
+
+#[test]
+fn sort_matches_by_path_reverse() {
+    let mut files = vec![PathBuf::from("a.md"), PathBuf::from("b.md")];
+    let args = Args {
+        reverse: true,
+        ..default_args()
+    };
+
+    sort_paths(&mut files, &args);
+    assert_eq!(files, vec![PathBuf::from("b.md"), PathBuf::from("a.md")]);
+}
+
+#[test]
+fn sort_matches_by_path_order() {
+    let sorted_with = |order| {
+        let mut files: Vec<PathBuf> = ["a10.md", "B.md", "a2.md", "a02.md", "b.md"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        let args = Args {
+            sort_order: order,
+            ..default_args()
+        };
+        sort_paths(&mut files, &args);
+        files
+            .into_iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        sorted_with(PathOrder::Byte),
+        ["B.md", "a02.md", "a10.md", "a2.md", "b.md"]
+    );
+    assert_eq!(
+        sorted_with(PathOrder::Ci),
+        ["a02.md", "a10.md", "a2.md", "B.md", "b.md"]
+    );
+    // Numbers compare by value; equal ones fall back to byte order
+    assert_eq!(
+        sorted_with(PathOrder::Natural),
+        ["a02.md", "a2.md", "a10.md", "B.md", "b.md"]
+    );
+}
+
+#[test]
+fn sort_matches_by_mtime() {
+    let temp_dir = TempDir::new().unwrap();
+    let newest = create_file_with_mtime(&temp_dir, "a_newest.md", 1_700_000_300);
+    let oldest = create_file_with_mtime(&temp_dir, "b_oldest.md", 1_700_000_100);
+    let middle = create_file_with_mtime(&temp_dir, "c_middle.md", 1_700_000_200);
+
+    let mut files = vec![newest.clone(), oldest.clone(), middle.clone()];
+    let args = Args {
+        sort: SortKey::Mtime,
+        ..default_args()
+    };
+    sort_paths(&mut files, &args);
+    assert_eq!(files, vec![oldest.clone(), middle.clone(), newest.clone()]);
+
+    let args = Args {
+        sort: SortKey::Mtime,
+        reverse: true,
+        ..default_args()
+    };
+    sort_paths(&mut files, &args);
+    assert_eq!(files, vec![newest, middle, oldest]);
+}
+
+#[test]
+fn sort_matches_by_ctime() {
+    let temp_dir = TempDir::new().unwrap();
+    // The change time can't be set, so create the files in order
+    let first = create_file_with_mtime(&temp_dir, "b_first.md", 1_700_000_300);
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    let second = create_file_with_mtime(&temp_dir, "a_second.md", 1_700_000_100);
+    let missing = temp_dir.path().join("missing.md");
+
+    let mut files = vec![missing.clone(), second.clone(), first.clone()];
+    let args = Args {
+        sort: SortKey::Ctime,
+        ..default_args()
+    };
+    sort_paths(&mut files, &args);
+    assert_eq!(files, vec![first.clone(), second.clone(), missing.clone()]);
+
+    let args = Args {
+        sort: SortKey::Ctime,
+        reverse: true,
+        ..default_args()
+    };
+    sort_paths(&mut files, &args);
+    assert_eq!(files, vec![second, first, missing]);
+}
+
+#[test]
+fn sort_matches_by_mtime_unreadable_sorts_last() {
+    let temp_dir = TempDir::new().unwrap();
+    let existing = create_file_with_mtime(&temp_dir, "exists.md", 1_700_000_000);
+    let missing = temp_dir.path().join("missing.md");
+
+    for reverse in [false, true] {
+        let mut files = vec![missing.clone(), existing.clone()];
+        let args = Args {
+            sort: SortKey::Mtime,
+            reverse,
+            ..default_args()
+        };
+        sort_paths(&mut files, &args);
+        assert_eq!(files, vec![existing.clone(), missing.clone()]);
+    }
+}
+
+// Helper to build a match with a single frontmatter line
+fn versioned_match(name: &str, frontmatter_line: &str) -> FileMatch {
+    let path = PathBuf::from(name);
+    let content = format!("---\n{}\n---\n", frontmatter_line);
+    FileMatch {
+        metadata: Some(Metadata {
+            frontmatter: extract_frontmatter(&content, &path),
+            raw_content: content.into(),
+        }),
+        path,
+    }
+}
+
+fn sorted_names(mut matches: Vec<FileMatch>, args: &Args) -> Vec<String> {
+    sort_matches(&mut matches, args);
+    matches
+        .iter()
+        .map(|m| m.path.display().to_string())
+        .collect()
+}
+
+#[test]
+fn sort_matches_by_numeric_field() {
+    let matches = vec![
+        versioned_match("a.md", "version: 10"),
+        versioned_match("b.md", "title: Unversioned"),
+        versioned_match("c.md", "version: 2"),
+        versioned_match("d.md", "version: 9.5"),
+    ];
+    let args = Args {
+        sort_field: Some("version".to_string()),
+        sort_field_kind: SortFieldKind::Numeric,
+        ..default_args()
+    };
+    assert_eq!(
+        sorted_names(matches, &args),
+        vec!["c.md", "d.md", "a.md", "b.md"]
+    );
+}
+
+#[test]
+fn sort_matches_by_semver_field() {
+    let matches = vec![
+        versioned_match("a.md", "version: \"1.10.0\""),
+        versioned_match("b.md", "version: v1.2.3"),
+        versioned_match("c.md", "version: \"1.9\""),
+        versioned_match("d.md", "version: not-a-version"),
+    ];
+    let args = Args {
+        sort_field: Some("version".to_string()),
+        sort_field_kind: SortFieldKind::Semver,
+        ..default_args()
+    };
+    assert_eq!(
+        sorted_names(matches, &args),
+        vec!["b.md", "c.md", "a.md", "d.md"]
+    );
+
+    // Lexical order puts "1.10.0" first; unusable values stay last when reversed
+    let matches = vec![
+        versioned_match("a.md", "version: \"1.10.0\""),
+        versioned_match("c.md", "version: \"1.9\""),
+        versioned_match("e.md", "title: none"),
+    ];
+    let args = Args {
+        sort_field: Some("version".to_string()),
+        reverse: true,
+        ..default_args()
+    };
+    assert_eq!(sorted_names(matches, &args), vec!["c.md", "a.md", "e.md"]);
+}
+
+#[test]
+fn sort_matches_by_latest_date() {
+    let matches = || {
+        vec![
+            versioned_match("a.md", "date: 2024-03-01"),
+            versioned_match("b.md", "{date: 2023-01-01, updated: 2025-01-01}"),
+            versioned_match("c.md", "title: Undated"),
+        ]
+    };
+    let args = Args {
+        sort: SortKey::Date,
+        ..default_args()
+    };
+    assert_eq!(sorted_names(matches(), &args), vec!["a.md", "b.md", "c.md"]);
+
+    // Undated files stay last when reversed
+    let args = Args {
+        reverse: true,
+        ..args
+    };
+    assert_eq!(sorted_names(matches(), &args), vec!["b.md", "a.md", "c.md"]);
+}
+
+#[test]
+fn sort_matches_by_date_with_mtime_fallback() {
+    let temp_dir = TempDir::new().unwrap();
+    // 2024-06-01
+    let undated = create_file_with_mtime(&temp_dir, "undated.md", 1_717_200_000);
+    let mut matches = vec![
+        versioned_match("late.md", "date: 2025-01-01"),
+        FileMatch {
+            path: undated.clone(),
+            metadata: None,
+        },
+        versioned_match("early.md", "date: 2024-01-01"),
+    ];
+    let args = Args {
+        sort: SortKey::Date,
+        date_fallback_mtime: true,
+        ..default_args()
+    };
+    sort_matches(&mut matches, &args);
+    let paths: Vec<PathBuf> = matches.into_iter().map(|m| m.path).collect();
+    assert_eq!(
+        paths,
+        vec![PathBuf::from("early.md"), undated, PathBuf::from("late.md")]
+    );
+}
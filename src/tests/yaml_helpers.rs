@@ -32,7 +32,7 @@ fn yaml_value_string_positive_cases() {
     for (value, expectations) in test_cases {
         for (pattern, should_match) in expectations {
             assert_eq!(
-                yaml_value_contains(&value, pattern),
+                yaml_value_contains(&value, pattern, false),
                 should_match,
                 "Pattern '{}' failed",
                 pattern
@@ -56,7 +56,7 @@ fn yaml_value_string_negative_cases() {
 
     for (value, expectations) in test_cases {
         for (pattern, should_match) in expectations {
-            assert_eq!(yaml_value_contains(&value, pattern), should_match);
+            assert_eq!(yaml_value_contains(&value, pattern, false), should_match);
         }
     }
 }
@@ -80,7 +80,7 @@ fn yaml_value_number_types() {
 
     for (value, expectations) in test_cases {
         for (pattern, should_match) in expectations {
-            assert_eq!(yaml_value_contains(&value, pattern), should_match);
+            assert_eq!(yaml_value_contains(&value, pattern, false), should_match);
         }
     }
 }
@@ -110,7 +110,50 @@ fn yaml_value_boolean_types() {
 
     for (value, expectations) in test_cases {
         for (pattern, should_match) in expectations {
-            assert_eq!(yaml_value_contains(&value, pattern), should_match);
+            assert_eq!(yaml_value_contains(&value, pattern, false), should_match);
+        }
+    }
+}
+
+#[test]
+fn yaml_value_boolean_shorthands() {
+    let test_cases = vec![
+        (
+            Value::Bool(true),
+            vec![
+                ("yes", true),
+                ("on", true),
+                ("1", true),
+                ("no", false),
+                ("off", false),
+            ],
+        ),
+        (
+            Value::Bool(false),
+            vec![
+                ("off", true),
+                ("no", true),
+                ("0", true),
+                ("yes", false),
+                ("on", false),
+            ],
+        ),
+        // Strings still match by substring only
+        (
+            Value::String("true".to_string()),
+            vec![("yes", false), ("true", true)],
+        ),
+    ];
+
+    for (value, expectations) in test_cases {
+        for (pattern, should_match) in expectations {
+            assert_eq!(
+                yaml_value_contains(&value, pattern, false),
+                should_match,
+                "{:?} vs {}",
+                value,
+                pattern
+            );
         }
     }
 }
@@ -148,7 +191,7 @@ fn yaml_value_sequence_positive_cases() {
 
     for (value, expectations) in test_cases {
         for (pattern, should_match) in expectations {
-            assert_eq!(yaml_value_contains(&value, pattern), should_match);
+            assert_eq!(yaml_value_contains(&value, pattern, false), should_match);
         }
     }
 }
@@ -171,7 +214,7 @@ fn yaml_value_sequence_negative_cases() {
 
     for (value, expectations) in test_cases {
         for (pattern, should_match) in expectations {
-            assert_eq!(yaml_value_contains(&value, pattern), should_match);
+            assert_eq!(yaml_value_contains(&value, pattern, false), should_match);
         }
     }
 }
@@ -184,9 +227,9 @@ fn yaml_value_sequence_mixed_types() {
         Value::Bool(true),
     ]);
 
-    assert!(yaml_value_contains(&value, "text"));
-    assert!(yaml_value_contains(&value, "123"));
-    assert!(yaml_value_contains(&value, "true"));
+    assert!(yaml_value_contains(&value, "text", false));
+    assert!(yaml_value_contains(&value, "123", false));
+    assert!(yaml_value_contains(&value, "true", false));
 }
 
 #[test]
@@ -197,10 +240,10 @@ fn yaml_value_sequence_with_numbers() {
         Value::Number(serde_yaml::Number::from(3)),
     ]);
 
-    assert!(yaml_value_contains(&value, "1"));
-    assert!(yaml_value_contains(&value, "2"));
-    assert!(yaml_value_contains(&value, "3"));
-    assert!(!yaml_value_contains(&value, "4"));
+    assert!(yaml_value_contains(&value, "1", false));
+    assert!(yaml_value_contains(&value, "2", false));
+    assert!(yaml_value_contains(&value, "3", false));
+    assert!(!yaml_value_contains(&value, "4", false));
 }
 
 #[test]
@@ -211,8 +254,8 @@ fn yaml_value_sequence_nested() {
     ]);
 
     // Should match nested strings through recursion
-    assert!(yaml_value_contains(&value, "nested"));
-    assert!(yaml_value_contains(&value, "top"));
+    assert!(yaml_value_contains(&value, "nested", false));
+    assert!(yaml_value_contains(&value, "top", false));
 }
 
 #[test]
@@ -221,9 +264,9 @@ fn yaml_value_unsupported_types() {
 
     // Null
     let null_value = Value::Null;
-    assert!(!yaml_value_contains(&null_value, "null"));
-    assert!(!yaml_value_contains(&null_value, ""));
-    assert!(!yaml_value_contains(&null_value, "anything"));
+    assert!(!yaml_value_contains(&null_value, "null", false));
+    assert!(!yaml_value_contains(&null_value, "", false));
+    assert!(!yaml_value_contains(&null_value, "anything", false));
 
     // Mapping
     use serde_yaml::Mapping;
@@ -233,13 +276,36 @@ fn yaml_value_unsupported_types() {
         Value::String("value".to_string()),
     );
     let mapping_value = Value::Mapping(map);
-    assert!(!yaml_value_contains(&mapping_value, "key"));
-    assert!(!yaml_value_contains(&mapping_value, "value"));
+    assert!(!yaml_value_contains(&mapping_value, "key", false));
+    assert!(!yaml_value_contains(&mapping_value, "value", false));
 
     // Tagged
     let tagged_value = Value::Tagged(Box::new(serde_yaml::value::TaggedValue {
         tag: serde_yaml::value::Tag::new("!custom"),
         value: Value::String("tagged".to_string()),
     }));
-    assert!(!yaml_value_contains(&tagged_value, "tagged"));
+    assert!(!yaml_value_contains(&tagged_value, "tagged", false));
+}
+
+#[test]
+fn yaml_value_mapping_deep() {
+    let value: Value = serde_yaml::from_str("status: active\nowner:\n  team: platform").unwrap();
+
+    // Mappings are only searched in deep mode, across keys and values
+    assert!(!yaml_value_contains(&value, "active", false));
+    assert!(yaml_value_contains(&value, "active", true));
+    assert!(yaml_value_contains(&value, "owner", true));
+    assert!(yaml_value_contains(&value, "platform", true));
+    assert!(!yaml_value_contains(&value, "missing", true));
+}
+
+#[test]
+fn yaml_value_sequence_of_mappings_deep() {
+    let value: Value =
+        serde_yaml::from_str("- name: alpha\n  state: done\n- plain entry\n").unwrap();
+
+    assert!(yaml_value_contains(&value, "plain", false));
+    assert!(!yaml_value_contains(&value, "done", false));
+    assert!(yaml_value_contains(&value, "done", true));
+    assert!(yaml_value_contains(&value, "alpha", true));
 }
@@ -17,6 +17,8 @@ fn run_fmd(args: &[&str], dir: &TempDir) -> String {
     let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
         .args(args)
         .current_dir(dir.path())
+        .env_remove("FMD_DIRS")
+        .env("FMD_CONFIG", "")
         .output()
         .expect("Failed to execute fmd");
 
@@ -112,6 +114,22 @@ fn test_filter_by_yaml_title() {
     assert!(!output.contains("other.md"));
 }
 
+#[test]
+fn test_filter_by_title_regex() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "dated.md", "# 2024-03-15 Retro\n\nNotes");
+    create_test_file(&temp_dir, "undated.md", "# Retro 2024-03-15\n\nNotes");
+
+    let output = run_fmd(&["--title-regex", r"^\d{4}-\d{2}"], &temp_dir);
+
+    assert!(output.contains("dated.md"));
+    assert!(!output.contains("undated.md"));
+}
+
 #[test]
 fn test_filter_by_markdown_heading() {
     let temp_dir = tempfile::Builder::new()
@@ -590,8 +608,37 @@ fn test_nonexistent_directory() {
         .output()
         .expect("Failed to execute fmd");
 
-    // Should handle gracefully
-    assert!(output.status.success());
+    // A missing search root is an error, so typos don't look like empty results
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No such directory"));
+
+    // --allow-missing-dirs restores the plain "no matches" status
+    let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["/nonexistent/directory/path", "--allow-missing-dirs"])
+        .output()
+        .expect("Failed to execute fmd");
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn test_mixed_existing_and_missing_directories() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("notes")).unwrap();
+    create_test_file(&temp_dir, "notes/a.md", "# A");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["notes", "missing"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute fmd");
+
+    assert_eq!(output.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("a.md"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Directory not found: missing"));
 }
 
 // Full-text search tests
@@ -656,3 +703,2481 @@ fn test_special_chars_in_filename() {
     assert!(output.contains("file[1].md") || output.contains("file"));
     assert!(output.contains("file (2).md") || output.contains("file"));
 }
+
+#[test]
+fn test_frontmatter_after_html_comment() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "generated.md",
+        "<!-- generated -->\n---\ntags: [rust]\n---\n# Content",
+    );
+    create_test_file(&temp_dir, "other.md", "---\ntags: [python]\n---\n# Content");
+
+    let output = run_fmd(&["--tag", "rust"], &temp_dir);
+
+    assert!(output.contains("generated.md"));
+    assert!(!output.contains("other.md"));
+}
+
+#[test]
+fn test_sort_by_mtime_reverse() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    let old = create_test_file(&temp_dir, "a_old.md", "---\ntags: [rust]\n---\n");
+    let new = create_test_file(&temp_dir, "b_new.md", "---\ntags: [rust]\n---\n");
+    filetime::set_file_mtime(&old, filetime::FileTime::from_unix_time(1_600_000_000, 0)).unwrap();
+    filetime::set_file_mtime(&new, filetime::FileTime::from_unix_time(1_700_000_000, 0)).unwrap();
+
+    let output = run_fmd(
+        &["--tag", "rust", "--sort", "mtime", "--reverse"],
+        &temp_dir,
+    );
+    let lines: Vec<&str> = output.lines().collect();
+
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("b_new.md"));
+    assert!(lines[1].ends_with("a_old.md"));
+}
+
+#[test]
+fn test_author_all_and_logic() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "coauthored.md",
+        "---\nauthor: Alice and Bob\n---\n# Paper",
+    );
+    create_test_file(&temp_dir, "solo.md", "---\nauthor: Alice\n---\n# Paper");
+
+    let output = run_fmd(&["-a", "alice", "-a", "bob", "--author-all"], &temp_dir);
+
+    assert!(output.contains("coauthored.md"));
+    assert!(!output.contains("solo.md"));
+}
+
+// Exit status tests
+
+#[test]
+fn test_exit_status_reflects_matches() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "rust.md", "---\ntags: [rust]\n---\n# Content");
+
+    let status = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_fmd"))
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute fmd")
+            .status
+            .code()
+    };
+
+    assert_eq!(status(&["--tag", "rust"]), Some(0));
+    assert_eq!(status(&["--tag", "python"]), Some(1));
+    assert_eq!(status(&["--tag", "python", "--no-status"]), Some(0));
+    assert_eq!(status(&["--name", "[invalid"]), Some(2));
+}
+
+#[test]
+fn test_mdx_meta_export() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "post.mdx",
+        "export const meta = {\n  title: 'Launch Post',\n  tags: ['news'],\n}\n\n# Hello",
+    );
+    create_test_file(
+        &temp_dir,
+        "other.mdx",
+        "export const meta = { title: 'Other' }\n",
+    );
+
+    let output = run_fmd(
+        &["--mdx", "--glob", "**/*.mdx", "--field", "title:launch"],
+        &temp_dir,
+    );
+    assert!(output.contains("post.mdx"));
+    assert!(!output.contains("other.mdx"));
+
+    let output = run_fmd(
+        &["--mdx", "--glob", "**/*.mdx", "--title", "launch"],
+        &temp_dir,
+    );
+    assert!(output.contains("post.mdx"));
+    assert!(!output.contains("other.mdx"));
+
+    let output = run_fmd(&["--glob", "**/*.mdx", "--title", "launch"], &temp_dir);
+    assert!(output.is_empty());
+
+    // --frontmatter-format mdx selects the export parser too
+    let output = run_fmd(
+        &[
+            "--frontmatter-format",
+            "mdx",
+            "--ext",
+            "mdx",
+            "--title",
+            "launch",
+            "--tag",
+            "news",
+        ],
+        &temp_dir,
+    );
+    assert_eq!(output.trim(), "./post.mdx");
+}
+
+#[test]
+fn test_filter_by_path() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("blog/drafts")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("notes/drafts")).unwrap();
+    create_test_file(&temp_dir, "blog/drafts/post.md", "---\ntags: [rust]\n---\n");
+    create_test_file(
+        &temp_dir,
+        "notes/drafts/idea.md",
+        "---\ntags: [python]\n---\n",
+    );
+    create_test_file(&temp_dir, "blog/published.md", "---\ntags: [rust]\n---\n");
+    create_test_file(&temp_dir, "drafts.md", "---\ntags: [rust]\n---\n");
+
+    let output = run_fmd(&["--path", "drafts/"], &temp_dir);
+    assert!(output.contains("post.md"));
+    assert!(output.contains("idea.md"));
+    assert!(!output.contains("published.md"));
+    assert!(!output.contains("drafts.md"));
+
+    // ANDs with other filters
+    let output = run_fmd(&["--path", "drafts/", "--tag", "rust"], &temp_dir);
+    assert!(output.contains("post.md"));
+    assert!(!output.contains("idea.md"));
+}
+
+#[test]
+fn test_encoding_option() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    // "---\ntitle: 会議メモ\n---\n" encoded as Shift_JIS
+    let mut bytes = b"---\ntitle: ".to_vec();
+    bytes.extend_from_slice(&[137, 239, 139, 99, 131, 129, 131, 130]);
+    bytes.extend_from_slice(b"\n---\n");
+    fs::write(temp_dir.path().join("legacy.md"), bytes).unwrap();
+
+    let output = run_fmd(&["--encoding", "shift_jis", "--title", "会議"], &temp_dir);
+    assert!(output.contains("legacy.md"));
+
+    let output = run_fmd(&["--title", "会議"], &temp_dir);
+    assert!(!output.contains("legacy.md"));
+
+    let status = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["--encoding", "klingon", "--title", "x"])
+        .current_dir(temp_dir.path())
+        .output()
+        .expect("Failed to execute fmd")
+        .status;
+    assert_eq!(status.code(), Some(2));
+}
+
+#[test]
+fn test_tag_spec_prefixes() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "ready.md", "---\ntags: [rust, cli]\n---\n");
+    create_test_file(
+        &temp_dir,
+        "draft.md",
+        "---\ntags: [rust, cli, draft]\n---\n",
+    );
+    create_test_file(&temp_dir, "python.md", "---\ntags: [python, cli]\n---\n");
+
+    let output = run_fmd(&["--tags", "+rust +cli -draft"], &temp_dir);
+    assert!(output.contains("ready.md"));
+    assert!(!output.contains("draft.md"));
+    assert!(!output.contains("python.md"));
+}
+
+#[test]
+fn test_field_logic_modes() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "both.md",
+        "---\nstatus: done\npriority: high\n---\n",
+    );
+    create_test_file(
+        &temp_dir,
+        "done.md",
+        "---\nstatus: done\npriority: low\n---\n",
+    );
+    create_test_file(
+        &temp_dir,
+        "none.md",
+        "---\nstatus: open\npriority: low\n---\n",
+    );
+
+    let output = run_fmd(&["-f", "status:done", "-f", "priority:high"], &temp_dir);
+    assert!(output.contains("both.md"));
+    assert!(!output.contains("done.md"));
+    assert!(!output.contains("none.md"));
+
+    let output = run_fmd(
+        &[
+            "-f",
+            "status:done",
+            "-f",
+            "priority:high",
+            "--field-logic",
+            "any",
+        ],
+        &temp_dir,
+    );
+    assert!(output.contains("both.md"));
+    assert!(output.contains("done.md"));
+    assert!(!output.contains("none.md"));
+}
+
+#[test]
+fn test_field_values_summary() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "a.md", "---\nstatus: active\n---\n");
+    create_test_file(&temp_dir, "b.md", "---\nstatus: draft\n---\n");
+    create_test_file(&temp_dir, "c.md", "---\nstatus: active\n---\n");
+
+    let output = run_fmd(&["--field-values", "status"], &temp_dir);
+    assert_eq!(output, "active\t2\ndraft\t1\n");
+}
+
+#[test]
+fn test_tag_glob() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "alpha.md", "---\ntags: [proj-alpha]\n---\n");
+    create_test_file(&temp_dir, "beta.md", "# Beta\n\n#work/proj-beta");
+    create_test_file(&temp_dir, "other.md", "---\ntags: [project]\n---\n");
+
+    let output = run_fmd(&["--tag-glob", "--tag", "proj-*"], &temp_dir);
+    assert!(output.contains("alpha.md"));
+    assert!(output.contains("beta.md"));
+    assert!(!output.contains("other.md"));
+
+    // Without --tag-glob the '*' is literal
+    let output = run_fmd(&["--tag", "proj-*"], &temp_dir);
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_print_dates() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "dated.md",
+        "---\ndate: 2025-01-15\nupdated: 2025-02-01\n---\n",
+    );
+    create_test_file(&temp_dir, "undated.md", "# No dates");
+
+    let output = run_fmd(&["--print-dates"], &temp_dir);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("dated.md\t2025-01-15,2025-02-01"));
+    assert!(lines[1].ends_with("undated.md\t"));
+
+    let output = run_fmd(&["--print-dates", "--json"], &temp_dir);
+    let records: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(
+        records[0]["dates"],
+        serde_json::json!(["2025-01-15", "2025-02-01"])
+    );
+    assert_eq!(records[1]["dates"], serde_json::json!([]));
+
+    // Plain --json carries no dates
+    let output = run_fmd(&["--json"], &temp_dir);
+    let records: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert!(records[0]["path"].as_str().unwrap().ends_with("dated.md"));
+    assert!(records[0].get("dates").is_none());
+}
+
+#[test]
+fn test_json_file_stats() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    let content = "---\ntitle: Sized\n---\n";
+    create_test_file(&temp_dir, "sized.md", content);
+
+    let output = run_fmd(&["--json"], &temp_dir);
+    let records: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(records[0]["size_bytes"], content.len() as u64);
+
+    let modified = records[0]["modified"].as_str().unwrap();
+    assert!(chrono::DateTime::parse_from_rfc3339(modified).is_ok());
+}
+
+#[test]
+fn test_frontmatter_aliases() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "by.md", "---\nby: Jane Doe\n---\n");
+    create_test_file(
+        &temp_dir,
+        "published.md",
+        "---\npublished: 2025-03-01\n---\n",
+    );
+
+    let output = run_fmd(&["--author", "Jane"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(
+        &["--resolve-frontmatter-aliases", "--author", "Jane"],
+        &temp_dir,
+    );
+    assert!(output.contains("by.md"));
+
+    let output = run_fmd(
+        &[
+            "--resolve-frontmatter-aliases",
+            "--date-after",
+            "2025-01-01",
+        ],
+        &temp_dir,
+    );
+    assert!(output.contains("published.md"));
+
+    let output = run_fmd(&["--field-alias", "author=by", "-a", "jane"], &temp_dir);
+    assert!(output.contains("by.md"));
+}
+
+#[test]
+fn test_body_head_after_long_frontmatter() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    let fields: String = (0..30).map(|i| format!("key{}: value\n", i)).collect();
+    let content = format!("---\n{}---\n# Heading\nauthor: Jane Doe\n", fields);
+    create_test_file(&temp_dir, "long.md", &content);
+
+    let output = run_fmd(&["--author", "Jane"], &temp_dir);
+    assert!(output.contains("long.md"));
+
+    let output = run_fmd(&["--author", "Jane", "--body-head", "1"], &temp_dir);
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_tags_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "rust.md", "---\ntags: [rust]\n---\n");
+    create_test_file(&temp_dir, "cli.md", "# CLI\n\n#cli");
+    create_test_file(&temp_dir, "web.md", "---\ntags: web\n---\n");
+    create_test_file(&temp_dir, "other.md", "---\ntags: [other]\n---\n");
+    fs::write(
+        temp_dir.path().join("tags.txt"),
+        "# projects\nrust\n\ncli\nweb\n",
+    )
+    .unwrap();
+
+    let output = run_fmd(&["--tags-file", "tags.txt"], &temp_dir);
+    assert!(output.contains("rust.md"));
+    assert!(output.contains("cli.md"));
+    assert!(output.contains("web.md"));
+    assert!(!output.contains("other.md"));
+}
+
+#[test]
+fn test_inline_metadata_prefix() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "indented.md", "# Note\n  author: Jane\n");
+    create_test_file(&temp_dir, "column0.md", "# Note\nauthor: Jane\n");
+
+    let output = run_fmd(&["--author", "jane"], &temp_dir);
+    assert!(output.contains("indented.md"));
+    assert!(output.contains("column0.md"));
+
+    let output = run_fmd(&["--author", "jane", "--inline-metadata-prefix"], &temp_dir);
+    assert!(!output.contains("indented.md"));
+    assert!(output.contains("column0.md"));
+}
+
+#[test]
+fn test_format_and_color() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "sync.md",
+        "---\ntitle: Weekly Sync\ntags: [work]\n---\n",
+    );
+
+    let output = run_fmd(&["-T", "sync", "--format", "{title}|{tags}"], &temp_dir);
+    assert_eq!(output, "Weekly Sync|work\n");
+
+    let output = run_fmd(&["-T", "sync", "--long"], &temp_dir);
+    assert!(output.ends_with("sync.md\tWeekly Sync\twork\n"));
+
+    // Forced color highlights the matched part of the title
+    let output = run_fmd(&["-T", "sync", "--long", "--color", "always"], &temp_dir);
+    assert!(output.contains("\u{1b}["));
+
+    // Machine-readable output never carries color codes
+    let output = run_fmd(
+        &["-T", "sync", "--long", "--color", "always", "-0"],
+        &temp_dir,
+    );
+    assert!(!output.contains('\u{1b}'));
+    let output = run_fmd(&["-T", "sync", "--json", "--color", "always"], &temp_dir);
+    assert!(!output.contains('\u{1b}'));
+}
+
+#[test]
+fn test_color_path_output() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "meeting.md", "# Meeting");
+
+    let output = run_fmd(&["--name", "meeting", "--color", "never"], &temp_dir);
+    assert!(output.contains("meeting.md"));
+    assert!(!output.contains('\u{1b}'));
+
+    let output = run_fmd(&["--name", "meeting", "--color", "always"], &temp_dir);
+    assert!(output.contains("\u{1b}["));
+}
+
+#[test]
+fn test_group_by_tag() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "a.md", "---\ntags: [rust, cli]\n---\n");
+    create_test_file(&temp_dir, "b.md", "---\ntags: [rust]\n---\n");
+
+    let output = run_fmd(&["--group-by", "tag"], &temp_dir);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "cli");
+    assert!(lines[1].ends_with("a.md"));
+    assert_eq!(lines[2], "");
+    assert_eq!(lines[3], "rust");
+    assert!(lines[4].ends_with("a.md"));
+    assert!(lines[5].ends_with("b.md"));
+
+    let output = run_fmd(&["--group-by", "tag", "--json"], &temp_dir);
+    let groups: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(groups["rust"].as_array().unwrap().len(), 2);
+    assert_eq!(groups["cli"].as_array().unwrap().len(), 1);
+}
+
+#[test]
+fn test_field_root() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "wrapped.md",
+        "---\nfrontmatter:\n  title: Wrapped Post\n  tags: [rust]\n---\n",
+    );
+
+    let output = run_fmd(&["-t", "rust"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(&["--field-root", "frontmatter", "-t", "rust"], &temp_dir);
+    assert!(output.contains("wrapped.md"));
+
+    let output = run_fmd(&["--field-root", "frontmatter", "-T", "wrapped"], &temp_dir);
+    assert!(output.contains("wrapped.md"));
+}
+
+#[test]
+fn test_has_field_and_missing_field() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "statused.md",
+        "---\nstatus: draft\ndate: 2025-01-15\n---\n",
+    );
+    create_test_file(&temp_dir, "dateless.md", "---\ntitle: Undated\n---\n");
+
+    let output = run_fmd(&["--has-field", "status"], &temp_dir);
+    assert!(output.contains("statused.md"));
+    assert!(!output.contains("dateless.md"));
+
+    let output = run_fmd(&["--missing-field", "date"], &temp_dir);
+    assert!(output.contains("dateless.md"));
+    assert!(!output.contains("statused.md"));
+
+    // Composes with value filters
+    let output = run_fmd(&["--has-field", "date", "-f", "status:final"], &temp_dir);
+    assert!(output.is_empty());
+
+    // --field-missing is the same filter; an empty value still counts as present
+    create_test_file(&temp_dir, "blank.md", "---\nstatus:\n---\n");
+    let output = run_fmd(&["--field-missing", "status"], &temp_dir);
+    assert_eq!(output.trim(), "./dateless.md");
+}
+
+#[test]
+fn test_glob_directory_argument() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("vault/work/notes")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("vault/home/notes")).unwrap();
+    create_test_file(&temp_dir, "vault/work/notes/a.md", "# A");
+    create_test_file(&temp_dir, "vault/home/notes/b.md", "# B");
+    create_test_file(&temp_dir, "vault/top.md", "# Top");
+
+    let output = run_fmd(&["vault/*/notes"], &temp_dir);
+    assert!(output.contains("a.md"));
+    assert!(output.contains("b.md"));
+    assert!(!output.contains("top.md"));
+}
+
+#[test]
+fn test_fmd_dirs_env_var() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("notes")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("other")).unwrap();
+    create_test_file(&temp_dir, "notes/in_notes.md", "# Notes");
+    create_test_file(&temp_dir, "other/in_other.md", "# Other");
+
+    let run_with_env = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+            .args(args)
+            .current_dir(temp_dir.path())
+            .env("FMD_DIRS", "notes")
+            .env("FMD_CONFIG", "")
+            .output()
+            .expect("Failed to execute fmd");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    // FMD_DIRS replaces the default '.'
+    let output = run_with_env(&[]);
+    assert!(output.contains("in_notes.md"));
+    assert!(!output.contains("in_other.md"));
+
+    // Explicit directories always win
+    let output = run_with_env(&["other"]);
+    assert!(output.contains("in_other.md"));
+    assert!(!output.contains("in_notes.md"));
+}
+
+#[test]
+fn test_name_full() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("docs/api")).unwrap();
+    create_test_file(&temp_dir, "docs/api/endpoints.md", "# Endpoints");
+    create_test_file(&temp_dir, "docs/guide.md", "# Guide");
+
+    // --name only sees the filename
+    let output = run_fmd(&["--name", "docs/api"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(&["--name", "docs/api", "--name-full"], &temp_dir);
+    assert!(output.contains("endpoints.md"));
+    assert!(!output.contains("guide.md"));
+}
+
+#[test]
+fn test_since_commit() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .args(["-c", "user.name=fmd", "-c", "user.email=fmd@example.com"])
+            .args(args)
+            .current_dir(temp_dir.path())
+            .output()
+            .expect("Failed to execute git")
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    };
+
+    git(&["init", "-q"]);
+    create_test_file(&temp_dir, "changed.md", "---\ntags: [reviewed]\n---\n");
+    create_test_file(&temp_dir, "unchanged.md", "---\ntags: [reviewed]\n---\n");
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "initial"]);
+    create_test_file(
+        &temp_dir,
+        "changed.md",
+        "---\ntags: [reviewed]\n---\nEdited\n",
+    );
+
+    let output = run_fmd(&["--since-commit", "HEAD", "--tag", "reviewed"], &temp_dir);
+    assert!(output.contains("changed.md"));
+    assert!(!output.contains("unchanged.md"));
+}
+
+#[test]
+fn test_since_commit_rejects_options() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+    create_test_file(&temp_dir, "note.md", "# Note");
+
+    for rev in ["--since-commit=--foo", "--since-commit=--output=out.txt"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+            .arg(rev)
+            .current_dir(temp_dir.path())
+            .env_remove("FMD_DIRS")
+            .env("FMD_CONFIG", "")
+            .output()
+            .expect("Failed to execute fmd");
+
+        assert_eq!(output.status.code(), Some(2));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("must not start with '-'"));
+        assert!(output.stdout.is_empty());
+    }
+    assert!(!temp_dir.path().join("out.txt").exists());
+}
+
+#[test]
+fn test_since_commit_outside_git_repository() {
+    // System temp dirs are outside this repository's work tree
+    let temp_dir = TempDir::new().unwrap();
+    create_test_file(&temp_dir, "note.md", "# Note");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["--since-commit", "HEAD"])
+        .current_dir(temp_dir.path())
+        .env("GIT_CEILING_DIRECTORIES", temp_dir.path().parent().unwrap())
+        .output()
+        .expect("Failed to execute fmd");
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("requires a git repository"));
+}
+
+#[test]
+fn test_description_filter_and_placeholder() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "post.md",
+        "---\ntitle: Launch\n---\n# Launch\n\nWe shipped the new search.\n",
+    );
+    create_test_file(&temp_dir, "other.md", "# Other\n\nNothing to see.\n");
+
+    let output = run_fmd(&["--description", "shipped"], &temp_dir);
+    assert!(output.contains("post.md"));
+    assert!(!output.contains("other.md"));
+
+    let output = run_fmd(
+        &["--description", "shipped", "--format", "{description}"],
+        &temp_dir,
+    );
+    assert_eq!(output, "We shipped the new search.\n");
+}
+
+#[test]
+fn test_exclusive_dates() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "bound.md", "---\ndate: 2025-01-15\n---\n");
+
+    let output = run_fmd(&["--date-after", "2025-01-15"], &temp_dir);
+    assert!(output.contains("bound.md"));
+
+    let output = run_fmd(
+        &["--date-after", "2025-01-15", "--exclusive-dates"],
+        &temp_dir,
+    );
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_deep_fields() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "nested.md",
+        "---\nmetadata:\n  status: active\n---\n",
+    );
+
+    let output = run_fmd(&["-f", "metadata:active"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(&["-f", "metadata:active", "--deep-fields"], &temp_dir);
+    assert!(output.contains("nested.md"));
+}
+
+#[test]
+fn test_cache_many_files() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    for i in 0..500 {
+        let dir = format!("dir{}", i % 10);
+        fs::create_dir_all(temp_dir.path().join(&dir)).unwrap();
+        create_test_file(
+            &temp_dir,
+            &format!("{}/note{}.md", dir, i),
+            "---\ntags: [bench]\n---\n",
+        );
+    }
+    let cache_path = temp_dir.path().join("fmd-cache.json");
+    let cache_arg = cache_path.to_str().unwrap();
+
+    let uncached = run_fmd(&["-t", "bench"], &temp_dir);
+    assert_eq!(uncached.lines().count(), 500);
+
+    // The first run writes the cache; the second reuses it
+    for _ in 0..2 {
+        let output = run_fmd(&["-t", "bench", "--cache", cache_arg], &temp_dir);
+        let mut lines: Vec<&str> = output.lines().collect();
+        let mut expected: Vec<&str> = uncached.lines().collect();
+        lines.sort();
+        expected.sort();
+        assert_eq!(lines, expected);
+    }
+    assert!(cache_path.exists());
+
+    // New and deleted files are picked up
+    create_test_file(&temp_dir, "dir3/new.md", "---\ntags: [bench]\n---\n");
+    fs::remove_file(temp_dir.path().join("dir0/note0.md")).unwrap();
+    let output = run_fmd(&["-t", "bench", "--cache", cache_arg], &temp_dir);
+    assert!(output.contains("new.md"));
+    assert!(!output.contains("/note0.md") && !output.contains("\\note0.md"));
+    assert_eq!(output.lines().count(), 500);
+}
+
+#[test]
+fn test_grep_body() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    let long_body = "filler line\n".repeat(100);
+    create_test_file(
+        &temp_dir,
+        "deep.md",
+        &format!("---\ntags: [work]\n---\n{}TODO: late item\n", long_body),
+    );
+    create_test_file(&temp_dir, "clean.md", "---\ntags: [work]\n---\nDone.\n");
+
+    // The whole file is searched, not just the head window
+    let output = run_fmd(&["--grep", "^TODO"], &temp_dir);
+    assert!(output.contains("deep.md"));
+    assert!(!output.contains("clean.md"));
+
+    let output = run_fmd(&["--grep", "todo", "-i", "--full-text"], &temp_dir);
+    assert!(output.contains("deep.md"));
+
+    // Combined with metadata filters (AND logic)
+    let output = run_fmd(&["--grep", "^TODO", "-t", "home"], &temp_dir);
+    assert!(output.is_empty());
+    let output = run_fmd(&["--grep", "^TODO", "-t", "work"], &temp_dir);
+    assert!(output.contains("deep.md"));
+}
+
+#[test]
+fn test_progress_with_piped_stderr() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "work.md", "---\ntags: [work]\n---\n");
+    create_test_file(&temp_dir, "home.md", "---\ntags: [home]\n---\n");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["-t", "work", "--progress"])
+        .current_dir(temp_dir.path())
+        .env_remove("FMD_DIRS")
+        .env("FMD_CONFIG", "")
+        .output()
+        .unwrap();
+
+    // stderr is a pipe, so the bar stays off and stdout is untouched
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(stdout.contains("work.md"));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_filter_by_heading_level() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "meeting.md", "# Weekly\n\n## Agenda\n");
+    create_test_file(&temp_dir, "top.md", "# Agenda\n");
+
+    let output = run_fmd(&["--heading", "2:agenda"], &temp_dir);
+    assert!(output.contains("meeting.md"));
+    assert!(!output.contains("top.md"));
+
+    let output = run_fmd(&["--heading", "Agenda"], &temp_dir);
+    assert!(output.contains("meeting.md"));
+    assert!(output.contains("top.md"));
+
+    let output = run_fmd(&["--heading", "3:Agenda"], &temp_dir);
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_list_only_skips_content_filters() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "alpha.md", "---\ntags: [work]\n---\n");
+    create_test_file(&temp_dir, "beta.md", "# No tags");
+    create_test_file(&temp_dir, "notes.txt", "not markdown");
+
+    // Content filters are ignored, so both markdown files are listed
+    let output = run_fmd(&["--list-only", "-t", "nothing-has-this"], &temp_dir);
+    assert!(output.contains("alpha.md"));
+    assert!(output.contains("beta.md"));
+    assert!(!output.contains("notes.txt"));
+
+    // Name predicates still apply, and counts go to stderr
+    let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["--list-only", "--name", "^alpha", "-v"])
+        .current_dir(temp_dir.path())
+        .env_remove("FMD_DIRS")
+        .env("FMD_CONFIG", "")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("alpha.md"));
+    assert!(!stdout.contains("beta.md"));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("2 files enumerated, 1 after --name/--path filters"));
+}
+
+#[test]
+fn test_end_matter_title() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    let body = "Paragraph.\n\n".repeat(20);
+    create_test_file(
+        &temp_dir,
+        "exported.md",
+        &format!("{}---\ntitle: Quarterly Report\n---\n", body),
+    );
+
+    let output = run_fmd(&["--title", "quarterly"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(&["--title", "quarterly", "--end-matter"], &temp_dir);
+    assert!(output.contains("exported.md"));
+}
+
+#[test]
+fn test_group_by_dir() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("projects")).unwrap();
+    fs::create_dir_all(temp_dir.path().join("journal")).unwrap();
+    create_test_file(&temp_dir, "projects/fmd.md", "# fmd");
+    create_test_file(&temp_dir, "journal/today.md", "# Today");
+
+    let output = run_fmd(&["--group-by-dir"], &temp_dir);
+    let headers: Vec<&str> = output.lines().filter(|line| line.ends_with(":")).collect();
+    assert_eq!(headers.len(), 2);
+    assert!(headers[0].contains("journal"));
+    assert!(headers[1].contains("projects"));
+    assert!(output
+        .lines()
+        .any(|line| line.starts_with("  ") && line.contains("today.md")));
+}
+
+#[test]
+fn test_no_drafts() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "wip.md", "---\ndraft: true\n---\n# WIP");
+    create_test_file(&temp_dir, "live.md", "---\ndraft: false\n---\n# Live");
+
+    let output = run_fmd(&["--no-drafts"], &temp_dir);
+    assert!(output.contains("live.md"));
+    assert!(!output.contains("wip.md"));
+
+    let output = run_fmd(&[], &temp_dir);
+    assert!(output.contains("wip.md"));
+}
+
+#[test]
+fn test_stdin_nul_delimited_paths() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "my note.md", "---\ntags: [work]\n---\n");
+    create_test_file(&temp_dir, "other note.md", "---\ntags: [home]\n---\n");
+    create_test_file(&temp_dir, "unlisted.md", "---\ntags: [work]\n---\n");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["--stdin", "-0", "-t", "work"])
+        .current_dir(temp_dir.path())
+        .env_remove("FMD_DIRS")
+        .env("FMD_CONFIG", "")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"my note.md\0other note.md\0")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    // Only the listed file with the tag matches; output stays NUL-delimited
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "my note.md\0");
+}
+
+#[test]
+fn test_links_to() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "external.md",
+        "# Reading\n\nSee [the guide](https://example.com/guide).",
+    );
+    create_test_file(
+        &temp_dir,
+        "relative.md",
+        "# Child\n\nBack to [parent](../x.md).",
+    );
+    create_test_file(
+        &temp_dir,
+        "plain.md",
+        "# Plain\n\nMentions x.md without a link.",
+    );
+
+    let output = run_fmd(&["--links-to", "https://example.com"], &temp_dir);
+    assert!(output.contains("external.md"));
+    assert!(!output.contains("relative.md"));
+
+    let output = run_fmd(&["--links-to", "../x.md"], &temp_dir);
+    assert!(output.contains("relative.md"));
+    assert!(!output.contains("plain.md"));
+    assert!(!output.contains("external.md"));
+}
+
+#[test]
+fn test_depth_min() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("a/b/c")).unwrap();
+    create_test_file(&temp_dir, "index.md", "# Index");
+    create_test_file(&temp_dir, "a/one.md", "# One");
+    create_test_file(&temp_dir, "a/b/two.md", "# Two");
+    create_test_file(&temp_dir, "a/b/c/three.md", "# Three");
+
+    let output = run_fmd(&["--depth-min", "2", "--depth", "3"], &temp_dir);
+    assert!(!output.contains("index.md"));
+    assert!(output.contains("one.md"));
+    assert!(output.contains("two.md"));
+    assert!(!output.contains("three.md"));
+}
+
+#[test]
+fn test_title_ignores_code_fence_comments() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "script.md",
+        "Intro\n```sh\n# not a title\necho hi\n```\n",
+    );
+
+    let output = run_fmd(&["--title", "not a title"], &temp_dir);
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_filter_by_coauthor() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "paper.md",
+        "---\nauthors:\n  - Alice\n  - Bob\n---\n# Paper",
+    );
+    create_test_file(&temp_dir, "solo.md", "---\nauthor: Carol\n---\n# Solo");
+
+    for author in ["alice", "bob"] {
+        let output = run_fmd(&["--author", author], &temp_dir);
+        assert!(output.contains("paper.md"));
+        assert!(!output.contains("solo.md"));
+    }
+    let output = run_fmd(&["--author", "dave"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(&["--author", "alice", "--format", "{author}"], &temp_dir);
+    assert_eq!(output, "Alice, Bob\n");
+}
+
+#[test]
+fn test_sort_field_semver() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "a.md", "---\nversion: 1.10.0\n---\n");
+    create_test_file(&temp_dir, "b.md", "---\nversion: 1.2.0\n---\n");
+    create_test_file(&temp_dir, "c.md", "---\nversion: 1.9.1\n---\n");
+
+    let output = run_fmd(
+        &["--sort-field", "version", "--sort-field-kind", "semver"],
+        &temp_dir,
+    );
+    let names: Vec<&str> = output
+        .lines()
+        .map(|line| line.rsplit(['/', '\\']).next().unwrap())
+        .collect();
+    assert_eq!(names, vec!["b.md", "c.md", "a.md"]);
+}
+
+#[test]
+fn test_summary_output() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "essay.md",
+        "---\ntags: [essay]\n---\n# Title\n\nA short essay about notes.\n",
+    );
+
+    let output = run_fmd(&["-t", "essay", "--summary"], &temp_dir);
+    assert!(output.ends_with("essay.md\tA short essay about notes.\n"));
+
+    let output = run_fmd(
+        &["-t", "essay", "--summary", "--summary-length", "7"],
+        &temp_dir,
+    );
+    assert!(output.ends_with("\tA shor…\n"));
+
+    let output = run_fmd(&["-t", "essay", "--summary", "--json"], &temp_dir);
+    let records: serde_json::Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(records[0]["summary"], "A short essay about notes.");
+}
+
+#[test]
+fn test_print_frontmatter() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "plan.md",
+        "---\ntitle: Launch Plan\ntags: [project, q3]\n---\n# Plan",
+    );
+    create_test_file(&temp_dir, "bare.md", "# Bare");
+
+    let output = run_fmd(&["-t", "project", "--print-frontmatter"], &temp_dir);
+    assert!(output.contains("plan.md\n"));
+    assert!(output.contains("  title: Launch Plan"));
+    assert!(output.contains("  - q3"));
+
+    let output = run_fmd(
+        &[
+            "-t",
+            "project",
+            "--print-frontmatter",
+            "--print-frontmatter-format",
+            "json",
+        ],
+        &temp_dir,
+    );
+    assert!(output.contains("\"title\": \"Launch Plan\""));
+    assert!(output.contains("\"project\""));
+
+    let output = run_fmd(&["--name", "bare", "--print-frontmatter"], &temp_dir);
+    assert!(output.ends_with("bare.md\n  {}\n"));
+}
+
+#[test]
+fn test_config_precedence() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    for dir in ["vault/inner", "outside"] {
+        fs::create_dir_all(temp_dir.path().join(dir)).unwrap();
+        create_test_file(
+            &temp_dir,
+            &format!("{}/work.md", dir),
+            "---\ntags: [work]\n---\n",
+        );
+        create_test_file(
+            &temp_dir,
+            &format!("{}/home.md", dir),
+            "---\ntags: [home]\n---\n",
+        );
+    }
+    create_test_file(
+        &temp_dir,
+        "vault/inner/post.md",
+        "---\ntags: [work]\npublished: 2024-06-01\n---\n",
+    );
+    create_test_file(&temp_dir, "global.toml", "tag = \"home\"\n");
+    create_test_file(
+        &temp_dir,
+        "vault/.fmdrc",
+        "tag = \"work\"\nfield-alias = [\"date=published\"]\n",
+    );
+
+    let run_with_config = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+            .args(args)
+            .current_dir(temp_dir.path())
+            .env_remove("FMD_DIRS")
+            .env("FMD_CONFIG", "global.toml")
+            .output()
+            .expect("Failed to execute fmd");
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    // The global config applies outside the vault
+    let output = run_with_config(&["outside"]);
+    assert!(output.contains("home.md"));
+    assert!(!output.contains("work.md"));
+
+    // The nearest .fmdrc (in an ancestor of the search dir) overrides it
+    let output = run_with_config(&["vault/inner"]);
+    assert!(output.contains("work.md"));
+    assert!(!output.contains("home.md"));
+
+    // Command-line options override both
+    let output = run_with_config(&["vault/inner", "-t", "home"]);
+    assert!(output.contains("home.md"));
+    assert!(!output.contains("work.md"));
+
+    // The alias from .fmdrc makes `published` count as a date
+    let output = run_with_config(&["vault/inner", "--date-after", "2024-01-01"]);
+    assert!(output.contains("post.md"));
+    assert!(!output.contains("work.md"));
+}
+
+#[test]
+fn test_dir_config_from_expanded_dirs() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    fs::create_dir_all(temp_dir.path().join("vault/a/notes")).unwrap();
+    create_test_file(&temp_dir, "vault/.fmdrc", "tag = \"work\"\n");
+    create_test_file(
+        &temp_dir,
+        "vault/a/notes/work.md",
+        "---\ntags: [work]\n---\n",
+    );
+    create_test_file(
+        &temp_dir,
+        "vault/a/notes/home.md",
+        "---\ntags: [home]\n---\n",
+    );
+    create_test_file(&temp_dir, "roots.txt", "missing\nvault/a/notes\n");
+
+    // Both a glob dir and --dirs-from find the .fmdrc above the search dir
+    for args in [&["vault/*/notes"][..], &["--dirs-from", "roots.txt"]] {
+        let output = run_fmd(args, &temp_dir);
+        assert!(output.contains("work.md"), "{:?}", args);
+        assert!(!output.contains("home.md"), "{:?}", args);
+    }
+}
+
+#[test]
+fn test_filename_as_title() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "2024-06-15 Meeting with Bob.md", "Notes only.\n");
+
+    let output = run_fmd(&["--title", "meeting"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(&["--title", "meeting", "--filename-as-title"], &temp_dir);
+    assert!(output.contains("Meeting with Bob.md"));
+}
+
+#[test]
+fn test_any_name_or_tag() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "2024-review.md", "---\ntags: [misc]\n---\n");
+    create_test_file(&temp_dir, "plan.md", "---\ntags: [important]\n---\n");
+    create_test_file(&temp_dir, "other.md", "---\ntags: [misc]\n---\n");
+
+    // By default the name is a pre-filter, so both must match
+    let output = run_fmd(&["--name", "2024", "--tag", "important"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(
+        &["--any", "--name", "2024", "--tag", "important"],
+        &temp_dir,
+    );
+    assert!(output.contains("2024-review.md"));
+    assert!(output.contains("plan.md"));
+    assert!(!output.contains("other.md"));
+}
+
+#[test]
+fn test_any_name_or_path() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    fs::create_dir(temp_dir.path().join("sub")).unwrap();
+    create_test_file(&temp_dir, "foo.md", "# Foo\n");
+    create_test_file(&temp_dir, "sub/baz.md", "# Baz\n");
+    create_test_file(&temp_dir, "bar.md", "# Bar\n");
+
+    // Without --any both must match
+    let output = run_fmd(&["--name", "foo", "--path", "sub"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(&["--any", "--name", "foo", "--path", "sub"], &temp_dir);
+    assert!(output.contains("foo.md"));
+    assert!(output.contains("baz.md"));
+    assert!(!output.contains("bar.md"));
+}
+
+#[test]
+fn test_mmap_matches_normal_reading() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    let filler = "A line of filler text in a very long note.\n".repeat(2000);
+    create_test_file(
+        &temp_dir,
+        "big.md",
+        &format!("---\ntags: [archive]\n---\n{}Late #footer tag\n", filler),
+    );
+    create_test_file(
+        &temp_dir,
+        "small.md",
+        "---\ntags: [archive]\n---\n#footer\n",
+    );
+    create_test_file(&temp_dir, "other.md", "---\ntags: [misc]\n---\n");
+
+    for args in [
+        vec!["--full-text", "--tag", "footer"],
+        vec!["--tag", "archive"],
+        vec!["--grep", "footer"],
+    ] {
+        let normal = run_fmd(&args, &temp_dir);
+        let mapped = run_fmd(&[args.as_slice(), &["--mmap"]].concat(), &temp_dir);
+        assert_eq!(mapped, normal);
+        assert!(normal.contains("big.md"));
+        assert!(normal.contains("small.md"));
+    }
+}
+
+#[test]
+fn test_tag_prefix() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "team.md", "---\ntags: [team/rust]\n---\n");
+    create_test_file(&temp_dir, "plain.md", "---\ntags: [rust]\n---\n");
+    create_test_file(&temp_dir, "inline.md", "Notes #team/rust\n");
+
+    let output = run_fmd(&["--tag", "rust", "--tag-prefix", "team/"], &temp_dir);
+    assert!(output.contains("team.md"));
+    assert!(output.contains("inline.md"));
+    assert!(!output.contains("plain.md"));
+}
+
+#[test]
+fn test_tag_field() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "seo.md", "---\ncategories: [news]\n---\n");
+    create_test_file(&temp_dir, "tagged.md", "---\ntags: [news]\n---\n");
+
+    let output = run_fmd(&["--tag", "news"], &temp_dir);
+    assert!(!output.contains("seo.md"));
+    assert!(output.contains("tagged.md"));
+
+    let output = run_fmd(&["--tag", "news", "--tag-field", "categories"], &temp_dir);
+    assert!(output.contains("seo.md"));
+    assert!(output.contains("tagged.md"));
+}
+
+#[test]
+fn test_files_with_errors() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    let oversized = format!("---\n{}", "key: value\n".repeat(1001));
+    create_test_file(&temp_dir, "oversized.md", &oversized);
+    create_test_file(&temp_dir, "fine.md", "---\ntags: [ok]\n---\n");
+
+    // Normally the broken file is skipped silently
+    let output = run_fmd(&["-t", "ok"], &temp_dir);
+    assert!(output.contains("fine.md"));
+    assert!(!output.contains("oversized.md"));
+
+    let output = run_fmd(&["--files-with-errors"], &temp_dir);
+    assert_eq!(output.lines().count(), 1);
+    assert!(output.contains("oversized.md: "));
+    assert!(output.contains("exceeds maximum size"));
+    assert!(!output.contains("fine.md"));
+}
+
+#[test]
+fn test_files_with_errors_grep() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    // The scan window parses, but the streamed --grep read hits invalid UTF-8
+    let mut bytes = "# Notes\n".repeat(30).into_bytes();
+    bytes.extend_from_slice(&[0xff, 0xfe, b'\n']);
+    fs::write(temp_dir.path().join("binary-tail.md"), bytes).unwrap();
+    create_test_file(&temp_dir, "fine.md", "# Fine\n");
+
+    let output = run_fmd(&["--files-with-errors", "--grep", "TODO"], &temp_dir);
+    assert_eq!(output.lines().count(), 1);
+    assert!(output.contains("binary-tail.md: "));
+}
+
+#[test]
+fn test_invert_title() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "draft.md",
+        "---\ntitle: Draft plan\ntags: [rust]\n---\n",
+    );
+    create_test_file(
+        &temp_dir,
+        "done.md",
+        "---\ntitle: Final plan\ntags: [rust]\n---\n",
+    );
+    create_test_file(
+        &temp_dir,
+        "other.md",
+        "---\ntitle: Notes\ntags: [go]\n---\n",
+    );
+
+    let output = run_fmd(
+        &["-t", "rust", "--title", "draft", "--invert", "title"],
+        &temp_dir,
+    );
+    assert!(output.contains("done.md"));
+    assert!(!output.contains("draft.md"));
+    assert!(!output.contains("other.md"));
+}
+
+#[test]
+fn test_show_match_after_context() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "log.md",
+        "# Log\nStarted #rust work\nNext step\nUnrelated\nEnd with #rust",
+    );
+
+    let output = run_fmd(&["-t", "rust", "--show-match"], &temp_dir);
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].ends_with("log.md"));
+    assert_eq!(lines[1..], ["2:Started #rust work", "5:End with #rust"]);
+
+    // Trailing context stops at the end of the file
+    let output = run_fmd(&["-t", "rust", "--show-match", "-A", "1"], &temp_dir);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines[1..],
+        ["2:Started #rust work", "3-Next step", "5:End with #rust"]
+    );
+}
+
+#[test]
+fn test_after_context_requires_show_match() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["-A", "1"])
+        .current_dir(temp_dir.path())
+        .env_remove("FMD_DIRS")
+        .env("FMD_CONFIG", "")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_name_only_skips_reading() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    // Neither file could be parsed, but --name alone never reads them
+    let oversized = format!("---\n{}", "key: value\n".repeat(1001));
+    create_test_file(&temp_dir, "2024-oversized.md", &oversized);
+    fs::write(temp_dir.path().join("2024-binary.md"), [0xff, 0xfe, b'\n']).unwrap();
+    create_test_file(&temp_dir, "2023-other.md", "# Content");
+
+    let output = run_fmd(&["--name", "2024"], &temp_dir);
+    assert!(output.contains("2024-oversized.md"));
+    assert!(output.contains("2024-binary.md"));
+    assert!(!output.contains("2023-other.md"));
+
+    // A content filter still drops them
+    let output = run_fmd(&["--name", "2024", "-t", "x"], &temp_dir);
+    assert!(output.is_empty());
+}
+
+#[test]
+fn test_title_falls_back_to_first_h1() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "a.md", "---\ntitle: Zebra\n---\n");
+    create_test_file(&temp_dir, "b.md", "# apple\n\nBody\n");
+    create_test_file(&temp_dir, "c.md", "No title here\n");
+
+    let output = run_fmd(&["--format", "{title}"], &temp_dir);
+    let titles: Vec<&str> = output.lines().collect();
+    // The file name is the last resort
+    assert_eq!(titles, ["Zebra", "apple", "c"]);
+
+    // Case-insensitive by title, untitled files last
+    let output = run_fmd(&["--sort", "title"], &temp_dir);
+    let order: Vec<&str> = output.lines().collect();
+    assert!(order[0].ends_with("b.md"));
+    assert!(order[1].ends_with("a.md"));
+    assert!(order[2].ends_with("c.md"));
+}
+
+#[test]
+fn test_frontmatter_format() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "hugo.md", "+++\ntags = [\"rust\"]\n+++\n");
+    create_test_file(&temp_dir, "dashes.md", "---\ntags = [\"rust\"]\n---\n");
+    create_test_file(&temp_dir, "json.md", "---\n{\"tags\": [\"go\"]}\n---\n");
+
+    let output = run_fmd(&["-t", "rust"], &temp_dir);
+    assert!(output.contains("hugo.md"));
+    assert!(!output.contains("dashes.md"));
+
+    let output = run_fmd(&["-t", "rust", "--frontmatter-format", "toml"], &temp_dir);
+    assert!(output.contains("hugo.md"));
+    assert!(output.contains("dashes.md"));
+
+    let output = run_fmd(&["-t", "go", "--frontmatter-format", "json"], &temp_dir);
+    assert!(output.contains("json.md"));
+}
+
+#[test]
+fn test_include_dateless() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "old.md", "---\ndate: 2023-06-01\n---\n");
+    create_test_file(&temp_dir, "new.md", "---\ndate: 2024-06-01\n---\n");
+    create_test_file(&temp_dir, "undated.md", "# No date\n");
+
+    let output = run_fmd(&["--date-before", "2024-01-01"], &temp_dir);
+    assert!(output.contains("old.md"));
+    assert!(!output.contains("undated.md"));
+
+    let output = run_fmd(
+        &["--date-before", "2024-01-01", "--include-dateless"],
+        &temp_dir,
+    );
+    assert!(output.contains("old.md"));
+    assert!(output.contains("undated.md"));
+    assert!(!output.contains("new.md"));
+}
+
+#[test]
+fn test_grep_lines() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    let body = format!("# Tasks\n{}TODO: late\n", "filler\n".repeat(20));
+    create_test_file(&temp_dir, "tasks.md", &format!("TODO: early\n{}", body));
+    create_test_file(&temp_dir, "done.md", "# Done\n");
+
+    let output = run_fmd(&["--grep", "^TODO", "--grep-lines"], &temp_dir);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("tasks.md:1:TODO: early"));
+    // Hits past the --head window are still found
+    assert!(lines[1].ends_with("tasks.md:23:TODO: late"));
+
+    // Without it only the path is printed
+    let output = run_fmd(&["--grep", "^TODO"], &temp_dir);
+    assert_eq!(output.lines().count(), 1);
+}
+
+#[test]
+fn test_tags_subcommand() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+    fs::create_dir(temp_dir.path().join("vault")).unwrap();
+    fs::create_dir(temp_dir.path().join("other")).unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "vault/a.md",
+        "---\ntags: [rust, cli]\n---\nNotes #inline\n",
+    );
+    create_test_file(&temp_dir, "vault/b.md", "---\ntags: rust\n---\n");
+    create_test_file(&temp_dir, "other/c.md", "---\ntags: [elsewhere]\n---\n");
+
+    let output = run_fmd(&["tags", "vault"], &temp_dir);
+    let tags: Vec<&str> = output.lines().collect();
+    assert_eq!(tags, ["cli", "inline", "rust"]);
+
+    // Top-level filters still apply, and plain searches are unchanged
+    let output = run_fmd(&["-t", "cli", "tags"], &temp_dir);
+    assert!(output.contains("inline"));
+    assert!(!output.contains("elsewhere"));
+    let output = run_fmd(&["vault"], &temp_dir);
+    assert_eq!(output.lines().count(), 2);
+}
+
+#[test]
+fn test_directory_named_tags() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+    fs::create_dir(temp_dir.path().join("tags")).unwrap();
+
+    create_test_file(&temp_dir, "tags/rust.md", "---\ntags: [rust]\n---\n");
+    create_test_file(&temp_dir, "top.md", "---\ntags: [top]\n---\n");
+
+    // A bare `tags` is the subcommand, listing the tags under the current directory
+    let output = run_fmd(&["tags"], &temp_dir);
+    let tags: Vec<&str> = output.lines().collect();
+    assert_eq!(tags, ["rust", "top"]);
+
+    // `./tags` searches the directory
+    let output = run_fmd(&["./tags"], &temp_dir);
+    assert_eq!(output.trim(), "./tags/rust.md");
+}
+
+#[test]
+fn test_field_separator() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "link.md",
+        "---\nurl: https://example.com/page\n---\n",
+    );
+    create_test_file(&temp_dir, "other.md", "---\nurl: https://other.org\n---\n");
+
+    let output = run_fmd(
+        &[
+            "--field-separator",
+            "=",
+            "--field",
+            "url=https://example.com",
+        ],
+        &temp_dir,
+    );
+    assert!(output.contains("link.md"));
+    assert!(!output.contains("other.md"));
+}
+
+#[test]
+fn test_stem() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "2024.md", "# Year\n");
+    create_test_file(&temp_dir, "2024-01.md", "# Month\n");
+
+    let output = run_fmd(&["--name", "2024$"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(&["--stem", "2024$"], &temp_dir);
+    assert!(output.contains("2024.md"));
+    assert!(!output.contains("2024-01.md"));
+
+    // -i applies too
+    create_test_file(&temp_dir, "Draft.md", "# Draft\n");
+    let output = run_fmd(&["--stem", "^draft$", "-i"], &temp_dir);
+    assert!(output.contains("Draft.md"));
+}
+
+#[test]
+fn test_tag_maps() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "weighted.md",
+        "---\ntitle: Weighted\ntags:\n  - name: rust\n    weight: 3\n---\n# Body\n",
+    );
+    create_test_file(&temp_dir, "plain.md", "---\ntags: [go]\n---\n");
+
+    let output = run_fmd(&["-t", "rust"], &temp_dir);
+    assert!(output.contains("weighted.md"));
+    assert!(!output.contains("plain.md"));
+
+    // The rest of the frontmatter still parses
+    let output = run_fmd(&["--title", "Weighted"], &temp_dir);
+    assert!(output.contains("weighted.md"));
+}
+
+#[test]
+fn test_errors_json() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "broken.md",
+        "---\ntitle: [unclosed\n---\n#rust\n",
+    );
+
+    let run = |extra: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_fmd"))
+            .args(["-t", "rust"])
+            .args(extra)
+            .current_dir(temp_dir.path())
+            .env_remove("FMD_DIRS")
+            .env("FMD_CONFIG", "")
+            .output()
+            .expect("Failed to execute fmd")
+    };
+
+    // Human-readable by default
+    let output = run(&[]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("Warning: Failed to parse YAML frontmatter in"));
+
+    let output = run(&["--errors-json"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let line = stderr.lines().next().unwrap();
+    let report: serde_json::Value = serde_json::from_str(line).unwrap();
+    assert_eq!(report["kind"], "frontmatter");
+    assert!(report["path"].as_str().unwrap().ends_with("broken.md"));
+    assert!(!report["message"].as_str().unwrap().is_empty());
+    // The inline tag still matches
+    assert!(String::from_utf8_lossy(&output.stdout).contains("broken.md"));
+}
+
+#[test]
+fn test_tag_count_bounds() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "untagged.md", "# Nothing here\n");
+    create_test_file(
+        &temp_dir,
+        "tagged.md",
+        "---\ntags: [a, b, c]\n---\n# Tagged\n",
+    );
+
+    let output = run_fmd(&["--max-tags", "0"], &temp_dir);
+    assert_eq!(output.trim(), "./untagged.md");
+
+    let output = run_fmd(&["--min-tags", "3"], &temp_dir);
+    assert_eq!(output.trim(), "./tagged.md");
+}
+
+#[test]
+fn test_name_or_content() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "todo.md", "# Untagged todo\n");
+    create_test_file(&temp_dir, "notes.md", "---\ntags: [rust]\n---\n");
+    create_test_file(&temp_dir, "other.md", "# Neither\n");
+
+    // By default --name and --tag must both match
+    let output = run_fmd(&["--name", "^todo", "-t", "rust"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(
+        &["--name", "^todo", "-t", "rust", "--name-or-content"],
+        &temp_dir,
+    );
+    assert!(output.contains("todo.md"));
+    assert!(output.contains("notes.md"));
+    assert!(!output.contains("other.md"));
+}
+
+#[test]
+fn test_name_or_content_with_grep() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "foo.md", "# Nothing to do here\n");
+    create_test_file(&temp_dir, "bar.md", "# Bar\nTODO: write\n");
+    create_test_file(&temp_dir, "baz.md", "# Baz\n");
+
+    // foo.md matches only the name, bar.md only the grep
+    let output = run_fmd(
+        &["--name", "foo", "--name-or-content", "--grep", "TODO"],
+        &temp_dir,
+    );
+    assert!(output.contains("foo.md"));
+    assert!(output.contains("bar.md"));
+    assert!(!output.contains("baz.md"));
+}
+
+#[test]
+fn test_name_or_content_keeps_exclusions() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "todo-draft.md", "---\ndraft: true\n---\n");
+    create_test_file(&temp_dir, "todo-old.md", "---\ntags: [archive]\n---\n");
+    create_test_file(&temp_dir, "todo-title.md", "---\ntitle: Skip me\n---\n");
+    create_test_file(&temp_dir, "todo-keep.md", "# Keep\n");
+    create_test_file(&temp_dir, "notes.md", "---\ntags: [rust]\n---\n");
+
+    let output = run_fmd(
+        &[
+            "--name",
+            "^todo",
+            "-t",
+            "rust",
+            "--name-or-content",
+            "--no-drafts",
+            "--tags=-archive",
+            "--title",
+            "skip",
+            "--invert",
+            "title",
+        ],
+        &temp_dir,
+    );
+    assert!(output.contains("todo-keep.md"));
+    assert!(output.contains("notes.md"));
+    assert!(!output.contains("todo-draft.md"));
+    assert!(!output.contains("todo-old.md"));
+    assert!(!output.contains("todo-title.md"));
+}
+
+#[test]
+fn test_one_file_system_walk() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+    fs::create_dir_all(temp_dir.path().join("sub/deep")).unwrap();
+
+    create_test_file(&temp_dir, "top.md", "# Top\n");
+    create_test_file(&temp_dir, "sub/deep/nested.md", "# Nested\n");
+
+    let output = run_fmd(&["--one-file-system"], &temp_dir);
+    assert_eq!(output, "./sub/deep/nested.md\n./top.md\n");
+}
+
+#[test]
+fn test_field_all() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "abc.md", "---\ncategories: [a, b, c]\n---\n");
+    create_test_file(&temp_dir, "a.md", "---\ncategories: [a]\n---\n");
+
+    let output = run_fmd(&["--field-all", "categories:a,b"], &temp_dir);
+    assert_eq!(output.trim(), "./abc.md");
+
+    // --field matches either file
+    let output = run_fmd(&["--field", "categories:a"], &temp_dir);
+    assert_eq!(output.lines().count(), 2);
+}
+
+#[test]
+fn test_field_eq() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "done.md", "---\nstatus: done\n---\n");
+    create_test_file(&temp_dir, "not_done.md", "---\nstatus: not-done\n---\n");
+
+    let output = run_fmd(&["--field-eq", "status:DONE"], &temp_dir);
+    assert_eq!(output.trim(), "./done.md");
+
+    // --field matches the substring in both files
+    let output = run_fmd(&["--field", "status:done"], &temp_dir);
+    assert_eq!(output.lines().count(), 2);
+}
+
+#[test]
+fn test_min_max_links() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "hub.md", "# Hub\n[a](a.md)\n[[b]]\n");
+    create_test_file(&temp_dir, "orphan.md", "# Orphan\n");
+
+    let output = run_fmd(&["--full-text", "--min-links", "2"], &temp_dir);
+    assert_eq!(output.trim(), "./hub.md");
+
+    let output = run_fmd(&["--full-text", "--max-links", "0"], &temp_dir);
+    assert_eq!(output.trim(), "./orphan.md");
+}
+
+#[test]
+fn test_field_name_with_colon() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "a.md", "---\n\"dc:creator\": alice\n---\n");
+    create_test_file(&temp_dir, "b.md", "---\n\"dc:creator\": bob\n---\n");
+
+    let output = run_fmd(&["--field", "\"dc:creator\":alice"], &temp_dir);
+    assert_eq!(output.trim(), "./a.md");
+
+    let output = run_fmd(&["--field", r"dc\:creator:bob"], &temp_dir);
+    assert_eq!(output.trim(), "./b.md");
+}
+
+#[test]
+fn test_watch_prints_again_on_change() {
+    use std::io::BufRead;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+    create_test_file(&temp_dir, "a.md", "---\ntags: [project]\n---\n");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["--watch", "--tag", "project"])
+        .current_dir(&temp_dir)
+        .env_remove("FMD_DIRS")
+        .env("FMD_CONFIG", "")
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let (sender, lines) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout).lines() {
+            if sender.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    let next_line = || lines.recv_timeout(Duration::from_secs(10));
+
+    assert_eq!(next_line().as_deref(), Ok("./a.md"));
+    create_test_file(&temp_dir, "b.md", "---\ntags: [project]\n---\n");
+
+    // A blank line, then the new full list
+    let second: Vec<String> = (0..3).map_while(|_| next_line().ok()).collect();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert_eq!(second, ["", "./a.md", "./b.md"]);
+}
+
+#[test]
+fn test_watch_glob_dir() {
+    use std::io::BufRead;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+    fs::create_dir_all(temp_dir.path().join("v/a/notes")).unwrap();
+    create_test_file(&temp_dir, "v/a/notes/one.md", "# One\n");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["--watch", "--allow-missing-dirs", "v/*/notes", "missing"])
+        .current_dir(&temp_dir)
+        .env_remove("FMD_DIRS")
+        .env("FMD_CONFIG", "")
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdout = child.stdout.take().unwrap();
+    let (sender, lines) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout).lines() {
+            if sender.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    let next_line = || lines.recv_timeout(Duration::from_secs(10));
+
+    assert_eq!(next_line().as_deref(), Ok("v/a/notes/one.md"));
+    create_test_file(&temp_dir, "v/a/notes/two.md", "# Two\n");
+
+    let second: Vec<String> = (0..3).map_while(|_| next_line().ok()).collect();
+    child.kill().unwrap();
+    child.wait().unwrap();
+    assert_eq!(second, ["", "v/a/notes/one.md", "v/a/notes/two.md"]);
+}
+
+#[test]
+fn test_head_zero_reads_only_frontmatter() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+    create_test_file(
+        &temp_dir,
+        "note.md",
+        "---\ntitle: Note\n---\n# Note\n\nSome text.\nauthor: bob\n",
+    );
+
+    let output = run_fmd(&["--head", "0", "--author", "bob"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(
+        &["--head", "0", "--body-head", "5", "--author", "bob"],
+        &temp_dir,
+    );
+    assert_eq!(output.trim(), "./note.md");
+}
+
+#[test]
+fn test_dirs_from() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    for dir in ["work", "home", "other"] {
+        fs::create_dir(temp_dir.path().join(dir)).unwrap();
+    }
+    create_test_file(&temp_dir, "work/a.md", "# A");
+    create_test_file(&temp_dir, "home/b.md", "# B");
+    create_test_file(&temp_dir, "other/c.md", "# C");
+    create_test_file(
+        &temp_dir,
+        "roots.txt",
+        "# Search roots\nwork\nhome\nmissing\n",
+    );
+
+    let output = run_fmd(&["--dirs-from", "roots.txt"], &temp_dir);
+    let mut files: Vec<&str> = output.lines().collect();
+    files.sort();
+    assert_eq!(files, ["home/b.md", "work/a.md"]);
+}
+
+#[test]
+fn test_cat_strip_frontmatter() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "a.md",
+        "---\ntags: [rust]\n---\n# A\nBody of a\n",
+    );
+    create_test_file(&temp_dir, "b.md", "# B\nSee #rust");
+
+    let output = run_fmd(&["--tag", "rust", "--cat"], &temp_dir);
+    assert_eq!(
+        output,
+        "==> ./a.md <==\n---\ntags: [rust]\n---\n# A\nBody of a\n\n==> ./b.md <==\n# B\nSee #rust\n"
+    );
+
+    let output = run_fmd(
+        &["--tag", "rust", "--cat", "--strip-frontmatter"],
+        &temp_dir,
+    );
+    assert_eq!(
+        output,
+        "==> ./a.md <==\n# A\nBody of a\n\n==> ./b.md <==\n# B\nSee #rust\n"
+    );
+
+    // With -0 each file's contents end with a NUL instead
+    let output = run_fmd(
+        &["--tag", "rust", "--cat", "--strip-frontmatter", "-0"],
+        &temp_dir,
+    );
+    assert_eq!(output, "# A\nBody of a\n\0# B\nSee #rust\0");
+}
+
+#[test]
+fn test_alias() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "renamed.md",
+        "---\ntitle: New Title\naliases:\n  - Old Title\n  - nt\n---\n",
+    );
+    create_test_file(&temp_dir, "other.md", "---\ntitle: Old Title notes\n---\n");
+
+    let output = run_fmd(&["--alias", "old title"], &temp_dir);
+    assert_eq!(output.trim(), "./renamed.md");
+}
+
+#[test]
+fn test_query_file() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "a.md", "---\ntags: [rust]\nauthor: alice\n---\n");
+    create_test_file(&temp_dir, "b.md", "---\ntags: [rust]\nauthor: bob\n---\n");
+    create_test_file(&temp_dir, "c.md", "---\ntags: [go]\nauthor: alice\n---\n");
+    create_test_file(
+        &temp_dir,
+        "rust.fmdq",
+        "# Rust notes\n--tag rust\n--sort mtime\n",
+    );
+
+    let output = run_fmd(&["--query-file", "rust.fmdq"], &temp_dir);
+    assert_eq!(output.lines().count(), 2);
+
+    // Command-line options add to the query, and override its single values
+    let output = run_fmd(
+        &[
+            "--query-file",
+            "rust.fmdq",
+            "--author",
+            "alice",
+            "--sort",
+            "path",
+        ],
+        &temp_dir,
+    );
+    assert_eq!(output.trim(), "./a.md");
+}
+
+#[test]
+fn test_newest_and_oldest() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "jan.md", "---\ndate: 2025-01-10\n---\n");
+    create_test_file(&temp_dir, "mar.md", "---\ndate: 2025-03-10\n---\n");
+    create_test_file(&temp_dir, "feb.md", "---\ndate: 2025-02-10\n---\n");
+    create_test_file(&temp_dir, "undated.md", "# No date\n");
+
+    let output = run_fmd(&["--newest", "2"], &temp_dir);
+    assert_eq!(output, "./mar.md\n./feb.md\n");
+
+    let output = run_fmd(&["--oldest", "1"], &temp_dir);
+    assert_eq!(output, "./jan.md\n");
+
+    let output = run_fmd(&["--sort", "date", "--limit", "3"], &temp_dir);
+    assert_eq!(output, "./jan.md\n./feb.md\n./mar.md\n");
+}
+
+#[test]
+fn test_separator() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "a.md", "# A\n");
+    create_test_file(&temp_dir, "b.md", "# B\n");
+
+    let output = run_fmd(&["--separator", "\\t"], &temp_dir);
+    assert_eq!(output, "./a.md\t./b.md\t");
+
+    let output = run_fmd(&["--null-separator"], &temp_dir);
+    assert_eq!(output, "./a.md\0./b.md\0");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["--separator", "\\q"])
+        .current_dir(temp_dir.path())
+        .env_remove("FMD_DIRS")
+        .env("FMD_CONFIG", "")
+        .output()
+        .expect("Failed to execute fmd");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_separator_with_line_outputs() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "a.md",
+        "---\ndate: 2024-01-01\n---\nFirst note.\n",
+    );
+    create_test_file(
+        &temp_dir,
+        "b.md",
+        "---\ndate: 2024-02-01\n---\nSecond note.\n",
+    );
+
+    let output = run_fmd(&["--separator", ";", "--print-dates"], &temp_dir);
+    assert_eq!(output, "./a.md\t2024-01-01;./b.md\t2024-02-01;");
+
+    let output = run_fmd(&["--separator", ";", "--summary"], &temp_dir);
+    assert_eq!(output, "./a.md\tFirst note.;./b.md\tSecond note.;");
+
+    let output = run_fmd(&["-0", "--grep", "note", "--grep-lines"], &temp_dir);
+    assert_eq!(output, "./a.md:4:First note.\0./b.md:4:Second note.\0");
+
+    let output = run_fmd(&["--separator", "\\t", "--field-values", "date"], &temp_dir);
+    assert_eq!(output, "2024-01-01\t1\t2024-02-01\t1\t");
+
+    // Multi-line layouts refuse a separator rather than ignore it
+    for mode in ["--group-by-dir", "--show-match", "--print-frontmatter"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+            .args([mode, "--separator", ";"])
+            .current_dir(temp_dir.path())
+            .env_remove("FMD_DIRS")
+            .env("FMD_CONFIG", "")
+            .output()
+            .expect("Failed to execute fmd");
+        assert_eq!(output.status.code(), Some(2), "{}", mode);
+    }
+}
+
+#[test]
+fn test_sidecar_ext() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "note.md", "---\ntitle: Note\n---\n# Note\n");
+    create_test_file(&temp_dir, "note.md.meta.yml", "tags: [published]\n");
+    create_test_file(&temp_dir, "plain.md", "---\ntitle: Plain\n---\n");
+
+    let output = run_fmd(&["-t", "published"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(
+        &["-t", "published", "--sidecar-ext", ".meta.yml"],
+        &temp_dir,
+    );
+    assert_eq!(output.trim(), "./note.md");
+
+    // Files without a sidecar keep their own frontmatter
+    let output = run_fmd(&["-T", "plain", "--sidecar-ext", ".meta.yml"], &temp_dir);
+    assert_eq!(output.trim(), "./plain.md");
+}
+
+#[test]
+fn test_toml_output() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(
+        &temp_dir,
+        "a.md",
+        "---\ntitle: Release \"notes\"\ntags: [rust, cli]\ndate: 2025-01-15\n---\n",
+    );
+    create_test_file(&temp_dir, "b.md", "Just text #rust\n");
+
+    let output = run_fmd(&["-t", "rust", "--toml"], &temp_dir);
+    let document: toml::Table = output.parse().unwrap();
+    let records = document["match"].as_array().unwrap();
+    assert_eq!(records.len(), 2);
+
+    assert_eq!(records[0]["path"].as_str(), Some("./a.md"));
+    assert_eq!(records[0]["title"].as_str(), Some("Release \"notes\""));
+    assert_eq!(
+        records[0]["tags"],
+        toml::Value::Array(vec!["cli".into(), "rust".into()])
+    );
+    assert_eq!(
+        records[0]["dates"],
+        toml::Value::Array(vec!["2025-01-15".into()])
+    );
+
+    // No title means no key, rather than an empty string
+    assert!(records[1].get("title").is_none());
+    assert_eq!(records[1]["tags"], toml::Value::Array(vec!["rust".into()]));
+}
+
+#[test]
+fn test_task_filters() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "open.md", "# Week\n- [ ] plan\n- [x] review\n");
+    create_test_file(&temp_dir, "done.md", "# Last week\n- [x] plan\n");
+
+    let output = run_fmd(&["--has-open-tasks"], &temp_dir);
+    assert_eq!(output.trim(), "./open.md");
+
+    let output = run_fmd(&["--has-tasks"], &temp_dir);
+    assert_eq!(output.lines().count(), 2);
+
+    // Tasks past --head are only seen with --full-text
+    let late = format!("# Later\n{}- [ ] follow up\n", "text\n".repeat(20));
+    create_test_file(&temp_dir, "late.md", &late);
+    let output = run_fmd(&["--has-open-tasks"], &temp_dir);
+    assert!(!output.contains("late.md"));
+    let output = run_fmd(&["--has-open-tasks", "--full-text"], &temp_dir);
+    assert!(output.contains("late.md"));
+}
+
+#[test]
+fn test_require_and_no_frontmatter() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "with.md", "---\ntitle: With\n---\n");
+    create_test_file(&temp_dir, "without.md", "# Without\n");
+
+    let output = run_fmd(&["--require-frontmatter"], &temp_dir);
+    assert_eq!(output.trim(), "./with.md");
+
+    let output = run_fmd(&["--no-frontmatter"], &temp_dir);
+    assert_eq!(output.trim(), "./without.md");
+}
+
+#[test]
+fn test_open_requires_terminal() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, "a.md", "---\ntags: [rust]\n---\n");
+
+    // stdout is a pipe here, so --open refuses instead of launching anything
+    let output = Command::new(env!("CARGO_BIN_EXE_fmd"))
+        .args(["-t", "rust", "--open"])
+        .current_dir(temp_dir.path())
+        .env_remove("FMD_DIRS")
+        .env("FMD_CONFIG", "")
+        .output()
+        .expect("Failed to execute fmd");
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--open needs a terminal"));
+}
+
+#[test]
+fn test_hidden() {
+    let temp_dir = tempfile::Builder::new()
+        .prefix("test_")
+        .tempdir_in(".")
+        .unwrap();
+
+    create_test_file(&temp_dir, ".secret.md", "# Secret\n");
+    create_test_file(&temp_dir, "public.md", "# Public\n");
+
+    let output = run_fmd(&["--glob", "**/.*.md"], &temp_dir);
+    assert!(output.is_empty());
+
+    let output = run_fmd(&["--hidden", "--glob", "**/.*.md"], &temp_dir);
+    assert_eq!(output.trim(), "./.secret.md");
+}